@@ -13,6 +13,14 @@ mod tests {
 
     use super::wiki_info::{get_most_similar_page, page_from_title, Page};
 
+    fn page(title: &str, content: &str) -> Page {
+        Page {
+            title: title.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_page_from_title() {
         let page = page_from_title("Paris").unwrap();
@@ -23,7 +31,7 @@ mod tests {
     fn test_get_most_sim_pages() {
         let main: Page = page_from_title("Paris").unwrap();
 
-        let pages_to_check: Vec<Page> = vec![
+        let pages_to_check: Vec<Page> = [
             "France",
             "European Union",
             "World War I",
@@ -174,6 +182,7 @@ mod tests {
             title: "Test Page".to_string(),
             content: "The quick brown fox jumps over the lazy dog.".to_string(),
             links: vec![],
+            ..Default::default()
         };
         let cleaned = clean_document(&page);
         assert!(cleaned.content.contains("quick"));
@@ -206,11 +215,13 @@ mod tests {
             title: "Page 1".to_string(),
             content: "The quick brown fox jumps over the lazy dog.".to_string(),
             links: vec![],
+            ..Default::default()
         };
         let page2 = Page {
             title: "Page 2".to_string(),
             content: "The quick brown cat sleeps under the lazy dog.".to_string(),
             links: vec![],
+            ..Default::default()
         };
         let similarity = get_page_similarity(&page1, &page2);
         assert!(similarity > 0.5);
@@ -253,4 +264,3396 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_page_sinks() {
+        use crate::wiki_info::{
+            sink::{CallbackSink, ChannelSink, FileSink, NdjsonSink, PageSink},
+            WikiError,
+        };
+
+        let page = Page {
+            title: "Test Page".to_string(),
+            content: "some content".to_string(),
+            links: vec![],
+            ..Default::default()
+        };
+
+        let mut collected: Vec<Page> = Vec::new();
+        collected.accept(&page).unwrap();
+        assert_eq!(collected, vec![page.clone()]);
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut channel_sink = ChannelSink::new(sender);
+        channel_sink.accept(&page).unwrap();
+        assert_eq!(receiver.recv().unwrap().title, "Test Page");
+
+        let mut seen = 0;
+        let mut callback_sink = CallbackSink::new(|_: &Page| {
+            seen += 1;
+            Ok(())
+        });
+        callback_sink.accept(&page).unwrap();
+        callback_sink.accept(&page).unwrap();
+        assert_eq!(seen, 2);
+
+        let mut ndjson_sink = NdjsonSink::new(Vec::new());
+        ndjson_sink.accept(&page).unwrap();
+        ndjson_sink.accept(&page).unwrap();
+        let output = String::from_utf8(ndjson_sink.into_inner()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(serde_json::from_str::<Page>(lines[1]).unwrap(), page);
+
+        let dir = std::env::temp_dir().join(format!("wiki-info-sink-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("pages.ndjson");
+
+        let mut file_sink = FileSink::create(&path).unwrap();
+        file_sink.accept(&page).unwrap();
+        file_sink.accept(&page).unwrap();
+        file_sink.flush().unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        let pages: Vec<Page> = written
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(pages, vec![page.clone(), page]);
+
+        assert!(matches!(
+            FileSink::create(dir.join("missing").join("pages.ndjson")),
+            Err(WikiError::IOError(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rank_outlinks_without_links() {
+        let page = Page {
+            title: "Lonely Page".to_string(),
+            content: "a page that links nowhere".to_string(),
+            links: vec![],
+            ..Default::default()
+        };
+
+        assert!(crate::wiki_info::rank_outlinks_by_similarity(&page).is_empty());
+    }
+
+    #[test]
+    fn test_category_statistics() {
+        use crate::wiki_info::categories::{category_overlap_matrix, most_common_categories};
+
+        let with_categories = |title: &str, categories: &[&str]| Page {
+            title: title.to_string(),
+            categories: categories.iter().map(|c| c.to_string()).collect(),
+            ..Default::default()
+        };
+
+        let pages = vec![
+            with_categories("Paris", &["Capitals in Europe", "Cities in France"]),
+            with_categories("Lyon", &["Cities in France"]),
+            with_categories("Berlin", &["Capitals in Europe"]),
+            with_categories("Marseille", &["Cities in France", "Port cities"]),
+        ];
+
+        let common = most_common_categories(&pages, 2);
+        assert_eq!(
+            common,
+            vec![
+                ("Cities in France".to_string(), 3),
+                ("Capitals in Europe".to_string(), 2)
+            ]
+        );
+
+        let overlap = category_overlap_matrix(&pages);
+        let capitals = overlap
+            .categories
+            .iter()
+            .position(|c| c == "Capitals in Europe")
+            .unwrap();
+        let cities = overlap
+            .categories
+            .iter()
+            .position(|c| c == "Cities in France")
+            .unwrap();
+        assert_eq!(overlap.counts[capitals][cities], 1);
+        assert_eq!(overlap.counts[cities][cities], 3);
+    }
+
+    #[test]
+    fn test_process_content_categories() {
+        let html = r#"
+        <main id="content">
+            <p>Body text.</p>
+            <div id="catlinks">
+                <a href="/wiki/Category:Capitals_in_Europe">Capitals in Europe</a>
+                <a href="/wiki/Category:Caf%C3%A9s_in_Paris">Cafés in Paris</a>
+            </div>
+        </main>
+        "#;
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let element = document.select(&selector).next().unwrap();
+
+        let page = crate::wiki_info::process_content(element, "Test Page");
+        assert_eq!(
+            page.categories,
+            vec![
+                "Capitals in Europe".to_string(),
+                "Cafés in Paris".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_entity_type() {
+        use crate::wiki_info::classify::EntityType;
+
+        let person = Page {
+            title: "Ada Lovelace".to_string(),
+            content: "Ada Lovelace (born Augusta Ada Byron; 1815 – 1852) was an English mathematician and writer.".to_string(),
+            categories: vec!["1815 births".to_string(), "1852 deaths".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(person.entity_type(), EntityType::Person);
+
+        let place = Page {
+            title: "Paris".to_string(),
+            content: "Paris is the capital and largest city of France.".to_string(),
+            infobox_type: Some("ib-settlement vcard".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(place.entity_type(), EntityType::Place);
+
+        let work = Page {
+            title: "The Gunslinger".to_string(),
+            content: "The Gunslinger is a fantasy novel by Stephen King.".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(work.entity_type(), EntityType::Work);
+
+        let concept = Page {
+            title: "Entropy".to_string(),
+            content: "Entropy is a scientific concept.".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(concept.entity_type(), EntityType::Concept);
+    }
+
+    #[test]
+    fn test_process_content_infobox_type() {
+        let html = r#"
+        <main id="content">
+            <table class="infobox ib-settlement vcard"><tr><td>Country</td></tr></table>
+            <p>Paris is the capital of France.</p>
+        </main>
+        "#;
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let element = document.select(&selector).next().unwrap();
+
+        let page = crate::wiki_info::process_content(element, "Paris");
+        assert_eq!(page.infobox_type.as_deref(), Some("ib-settlement vcard"));
+    }
+
+    #[test]
+    fn test_extract_tables() {
+        use crate::wiki_info::{
+            process_content_with,
+            tables::{extract_tables, extract_tables_from},
+            ParseOptions,
+        };
+
+        let html = r#"
+        <main id="content">
+            <p>Population by year.</p>
+            <table class="wikitable">
+                <caption>Population</caption>
+                <tr><th>Year</th><th>City</th><th>Population</th></tr>
+                <tr><td rowspan="2">1900</td><td><a href="/wiki/Paris">Paris</a></td><td>2714068</td></tr>
+                <tr><td>Lyon</td><td>459099</td></tr>
+            </table>
+        </main>
+        "#;
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let element = document.select(&selector).next().unwrap();
+
+        let tables = extract_tables_from(element);
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].caption.as_deref(), Some("Population"));
+        assert_eq!(tables[0].headers, vec!["Year", "City", "Population"]);
+        assert_eq!(tables[0].rows.len(), 2);
+        assert_eq!(tables[0].rows[1][0].text, "1900");
+        assert_eq!(tables[0].rows[1][1].text, "Lyon");
+        assert_eq!(
+            tables[0].rows[0][1].links[0].outlink,
+            "https://en.wikipedia.org/wiki/Paris"
+        );
+
+        let options = ParseOptions {
+            include_tables: false,
+            ..Default::default()
+        };
+        let page = process_content_with(element, "Test Page", &options);
+        assert_eq!(extract_tables(&page), tables);
+        assert!(!page.content.contains("2714068"));
+        assert!(page.content.contains("Population by year."));
+    }
+
+    #[test]
+    fn test_page_truncated() {
+        let page = Page {
+            title: "Test Page".to_string(),
+            content: "One two three. Four five six. Seven eight nine.".to_string(),
+            ..Default::default()
+        };
+
+        let (cut, was_truncated) = page.truncated(7);
+        assert!(was_truncated);
+        assert_eq!(cut.content, "One two three. Four five six.");
+
+        let (cut, was_truncated) = page.truncated(2);
+        assert!(was_truncated);
+        assert_eq!(cut.content, "One two");
+
+        let (same, was_truncated) = page.truncated(100);
+        assert!(!was_truncated);
+        assert_eq!(same, page);
+    }
+
+    #[test]
+    fn test_similarity_with_max_words() {
+        use crate::wiki_info::{get_page_similarity_with, VectorOptions};
+
+        let page1 = Page {
+            title: "Page 1".to_string(),
+            content: "Quick brown fox. Lazy sleeping dog.".to_string(),
+            ..Default::default()
+        };
+        let page2 = Page {
+            title: "Page 2".to_string(),
+            content: "Quick brown fox. Angry barking cat.".to_string(),
+            ..Default::default()
+        };
+
+        let options = VectorOptions {
+            max_words: Some(3),
+            ..Default::default()
+        };
+        let sim = get_page_similarity_with(&page1, &page2, &options);
+        assert!(
+            sim > 0.99,
+            "Only the identical first sentences should be compared"
+        );
+    }
+
+    #[test]
+    fn test_pivoted_length_normalization() {
+        use crate::wiki_info::{
+            get_page_similarity_with, score_vectors, LengthNormalization, VectorOptions,
+        };
+
+        let primary = page("", "apple banana cherry");
+        let short = page("", "apple banana grape");
+        let long = page(
+            "",
+            "apple banana cherry kiwi mango papaya melon lemon lime orange peach plum pear fig",
+        );
+
+        let cosine = VectorOptions::default();
+        let pivoted = VectorOptions {
+            length_normalization: LengthNormalization::Pivoted {
+                slope: 0.3,
+                pivot: None,
+            },
+            ..Default::default()
+        };
+        let unpivoted = VectorOptions {
+            length_normalization: LengthNormalization::Pivoted {
+                slope: 1.0,
+                pivot: None,
+            },
+            ..Default::default()
+        };
+
+        let cos_long = get_page_similarity_with(&primary, &long, &cosine);
+        assert!((get_page_similarity_with(&primary, &long, &unpivoted) - cos_long).abs() < 1e-9);
+
+        let cosine_ratio = cos_long / get_page_similarity_with(&primary, &short, &cosine);
+        let pivoted_ratio = get_page_similarity_with(&primary, &long, &pivoted)
+            / get_page_similarity_with(&primary, &short, &pivoted);
+        assert!(
+            pivoted_ratio < cosine_ratio,
+            "Long pages should lose ground under pivoted normalization"
+        );
+
+        let forward = get_page_similarity_with(&primary, &long, &pivoted);
+        assert!((forward - get_page_similarity_with(&long, &primary, &pivoted)).abs() < 1e-12);
+
+        // the default pivot only depends on the candidates, not on the primary
+        let pool = [vec![1.0, 0.0], vec![3.0, 4.0]];
+        let explicit = LengthNormalization::Pivoted {
+            slope: 0.3,
+            pivot: Some(3.0),
+        };
+        for primary in [vec![1.0, 1.0], vec![0.0, 10.0]] {
+            assert_eq!(
+                score_vectors(&primary, &pool, pivoted.length_normalization),
+                score_vectors(&primary, &pool, explicit)
+            );
+        }
+    }
+
+    #[test]
+    fn test_process_content_see_also() {
+        let html = r#"
+        <main id="content">
+            <p>Body with <a href="/wiki/Body_Link">a body link</a>.</p>
+            <div class="mw-heading mw-heading2"><h2 id="See_also">See also</h2></div>
+            <ul><li><a href="/wiki/Related_Page">Related Page</a></li></ul>
+            <div class="mw-heading mw-heading2"><h2 id="References">References</h2></div>
+            <p><a href="/wiki/Some_Source">Some Source</a></p>
+        </main>
+        "#;
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let element = document.select(&selector).next().unwrap();
+
+        let page = crate::wiki_info::process_content(element, "Test Page");
+        assert_eq!(page.links.len(), 3);
+        assert_eq!(page.see_also.len(), 1);
+        assert_eq!(page.see_also[0].title, "Related Page");
+    }
+
+    #[test]
+    fn test_vocabulary_export() {
+        use crate::wiki_info::{
+            export::{export_stop_words, export_vocabulary, fit_vocabulary, ExportFormat},
+            stop_words::StopWords,
+            CleanOptions, VectorOptions,
+        };
+
+        let pages = vec![page("", "The quick brown fox"), page("", "The brown dog")];
+
+        let vocab = fit_vocabulary(&pages, &VectorOptions::default());
+        let terms: Vec<&str> = vocab.iter().map(|entry| entry.term.as_str()).collect();
+        assert_eq!(terms, vec!["quick", "brown", "fox", "dog"]);
+        assert_eq!(vocab[1].df, 2);
+
+        let mut csv = Vec::new();
+        export_vocabulary(&vocab, &mut csv, ExportFormat::Csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("term,id,df\nquick,0,1\nbrown,1,2\n"));
+
+        let mut json = Vec::new();
+        export_stop_words(&CleanOptions::default(), &mut json, ExportFormat::Json).unwrap();
+        let stop_words: Vec<String> = serde_json::from_slice(&json).unwrap();
+        assert!(stop_words.contains(&"the".to_string()));
+
+        let custom = CleanOptions {
+            stop_words: StopWords::from_words(["fox", "dog"]),
+            ..Default::default()
+        };
+        let mut csv = Vec::new();
+        export_stop_words(&custom, &mut csv, ExportFormat::Csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "term\ndog\nfox\n");
+
+        let disabled = CleanOptions {
+            remove_stop_words: false,
+            ..custom
+        };
+        let mut json = Vec::new();
+        export_stop_words(&disabled, &mut json, ExportFormat::Json).unwrap();
+        assert_eq!(json, b"[]");
+    }
+
+    #[test]
+    fn test_process_content_summary() {
+        let html = r#"
+        <main id="content">
+            <table class="infobox"><tr><td>Infobox text</td></tr></table>
+            <p>Paris is the <a href="/wiki/Capital_city">capital</a> of France.</p>
+            <p>It is  large.</p>
+            <div class="mw-heading mw-heading2"><h2 id="History">History</h2></div>
+            <p>Paris was founded long ago.</p>
+        </main>
+        "#;
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let element = document.select(&selector).next().unwrap();
+
+        let page = crate::wiki_info::process_content(element, "Paris");
+        assert_eq!(page.summary, "Paris is the capital of France. It is large.");
+    }
+
+    #[test]
+    fn test_term_overlap_report() {
+        use crate::wiki_info::overlap::term_overlap_report;
+
+        let page1 = Page {
+            content: "fox fox brown lazy".to_string(),
+            ..Default::default()
+        };
+        let page2 = Page {
+            content: "fox brown cat".to_string(),
+            ..Default::default()
+        };
+
+        let report = term_overlap_report(&page1, &page2);
+        let terms: Vec<&str> = report.iter().map(|o| o.term.as_str()).collect();
+        assert_eq!(terms, vec!["fox", "brown"]);
+        assert!((report[0].weight_a - 0.5).abs() < 1e-9);
+        assert!((report[0].weight_b - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_term_set_jaccard() {
+        use crate::wiki_info::{term_set::term_sets, VectorOptions};
+
+        let pages = vec![
+            page("", "quick brown fox quick"),
+            page("", "quick brown dog"),
+            page("", "empty"),
+        ];
+
+        let sets = term_sets(&pages, &VectorOptions::default());
+        assert_eq!(sets[0].len(), 3);
+        assert_eq!(sets[0].intersection_len(&sets[1]), 2);
+        assert!((sets[0].jaccard(&sets[1]) - 0.5).abs() < 1e-9);
+        assert_eq!(sets[0].jaccard(&sets[2]), 0.0);
+        assert!((sets[0].jaccard(&sets[0]) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_process_content_coordinates() {
+        let html = r#"
+        <main id="content">
+            <span id="coordinates"><span class="geo-dec">48.8567°N 2.3508°E</span>
+            <span class="geo">48.8567; 2.3508</span></span>
+            <p>Paris is the capital of France.</p>
+        </main>
+        "#;
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let element = document.select(&selector).next().unwrap();
+
+        let page = crate::wiki_info::process_content(element, "Paris");
+        let (latitude, longitude) = page.coordinates.unwrap().into();
+        assert!((latitude - 48.8567).abs() < 1e-9);
+        assert!((longitude - 2.3508).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_hashing_vectorizer() {
+        use crate::wiki_info::hashing::HashingVectorizer;
+
+        let vectorizer = HashingVectorizer::new(1024);
+        let page1 = Page {
+            content: "The quick brown fox jumps over the lazy dog.".to_string(),
+            ..Default::default()
+        };
+        let page2 = Page {
+            content: "The quick brown cat sleeps under the lazy dog.".to_string(),
+            ..Default::default()
+        };
+
+        let vec1 = vectorizer.vectorize(&page1);
+        let vec2 = vectorizer.vectorize(&page2);
+        assert_eq!(vec1.len(), 1024);
+        assert_eq!(vec1, vectorizer.vectorize(&page1));
+        assert!(cosine_sim(&vec1, &vec2) > 0.5);
+        assert!(cosine_sim(&vec1, &vec1) > 0.99);
+    }
+
+    #[test]
+    fn test_process_content_skips_boilerplate() {
+        use crate::wiki_info::{process_content_with, ParseOptions};
+
+        let html = r#"
+        <main id="content">
+            <div role="note" class="hatnote navigation-not-searchable">For other uses, see <a href="/wiki/Paris_(disambiguation)">Paris (disambiguation)</a>.</div>
+            <p>Paris is the capital of <a href="/wiki/France">France</a>.</p>
+            <div class="navbox"><a href="/wiki/Lyon">Lyon</a> Cities of France</div>
+        </main>
+        "#;
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let element = document.select(&selector).next().unwrap();
+
+        let page = crate::wiki_info::process_content(element, "Paris");
+        assert_eq!(page.links.len(), 1);
+        assert_eq!(page.links[0].title, "France");
+        assert!(!page.content.contains("Cities of France"));
+        assert!(!page.content.contains("other uses"));
+
+        let options = ParseOptions {
+            skip_classes: vec![],
+            ..Default::default()
+        };
+        let page = process_content_with(element, "Paris", &options);
+        assert_eq!(page.links.len(), 3);
+    }
+
+    #[test]
+    fn test_topic_router() {
+        use crate::wiki_info::{hashing::HashingVectorizer, router::TopicRouter};
+
+        let mut router = TopicRouter::new(HashingVectorizer::new(512));
+        router.add_example("sports", &page("", "football match goal team league"));
+        router.add_example("sports", &page("", "tennis match player tournament"));
+        router.add_example("science", &page("", "physics experiment theory particle"));
+
+        assert_eq!(router.topics().len(), 2);
+        assert_eq!(router.topics()[0].examples, 2);
+
+        let (label, _) = router
+            .classify(&page("", "league team won the match"))
+            .unwrap();
+        assert_eq!(label, "sports");
+
+        let (label, _) = router
+            .classify(&page("", "particle physics theory"))
+            .unwrap();
+        assert_eq!(label, "science");
+    }
+
+    #[test]
+    fn test_process_content_strips_artifacts() {
+        let html = r##"
+        <main id="content">
+            <p>Paris is the capital of France.<sup class="reference"><a href="#cite_note-1">[1]</a></sup>
+            It is old.<sup class="noprint Inline-Template Template-Fact">[<i><a href="/wiki/Wikipedia:Citation_needed">citation needed</a></i>]</sup></p>
+            <div class="mw-heading mw-heading2"><h2 id="History">History</h2><span class="mw-editsection"><span class="mw-editsection-bracket">[</span><a href="/w/index.php?title=Paris&amp;action=edit&amp;section=1">edit</a><span class="mw-editsection-bracket">]</span></span></div>
+            <p>Founded long ago.</p>
+        </main>
+        "##;
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let element = document.select(&selector).next().unwrap();
+
+        let page = crate::wiki_info::process_content(element, "Paris");
+        assert!(!page.content.contains('['), "Content: {}", page.content);
+        assert!(!page.content.contains("edit"));
+        assert!(page.links.is_empty());
+        assert_eq!(page.summary, "Paris is the capital of France. It is old.");
+    }
+
+    #[test]
+    fn test_tiled_similarity_matrix() {
+        use crate::wiki_info::{
+            stop_words::StopWords,
+            tiled::TiledSimilarityMatrix,
+            tokenize::{SharedTokenizer, WhitespaceTokenizer},
+            CleanOptions, VectorOptions, WikiError,
+        };
+
+        let pages = vec![
+            page("A", "quick brown fox"),
+            page("B", "quick brown dog"),
+            page("C", "lazy sleeping cat"),
+            page("D", "quick brown fox"),
+            page("E", "brown cat"),
+        ];
+
+        let dir = std::env::temp_dir().join(format!("wiki-info-tiles-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let options = VectorOptions::default();
+        let matrix = TiledSimilarityMatrix::compute(&pages, &options, 2, &dir).unwrap();
+        assert_eq!(matrix.size(), 5);
+
+        for i in 0..pages.len() {
+            for j in 0..pages.len() {
+                let expected = get_page_similarity(&pages[i], &pages[j]);
+                let expected = if expected.is_nan() { 0.0 } else { expected };
+                assert!((matrix.get(i, j).unwrap() - expected).abs() < 1e-9);
+            }
+        }
+
+        // resuming over the same pages reuses the tiles, a different set is rejected
+        std::fs::remove_file(dir.join("tile_0_2.bin")).unwrap();
+        let resumed = TiledSimilarityMatrix::compute(&pages, &options, 2, &dir).unwrap();
+        assert!((resumed.get(0, 3).unwrap() - 1.0).abs() < 1e-9);
+        assert!(matches!(
+            TiledSimilarityMatrix::compute(&pages[..3], &options, 2, &dir),
+            Err(WikiError::InputError(_))
+        ));
+
+        // so are edited pages and different vector options
+        let mut edited = pages.clone();
+        edited[2].content = "lazy sleeping dog".to_string();
+        assert!(matches!(
+            TiledSimilarityMatrix::compute(&edited, &options, 2, &dir),
+            Err(WikiError::InputError(_))
+        ));
+        let capped = VectorOptions {
+            max_words: Some(2),
+            ..Default::default()
+        };
+        assert!(matches!(
+            TiledSimilarityMatrix::compute(&pages, &capped, 2, &dir),
+            Err(WikiError::InputError(_))
+        ));
+        let custom_stop_words = VectorOptions {
+            clean: CleanOptions {
+                stop_words: StopWords::from_words(["fox"]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(matches!(
+            TiledSimilarityMatrix::compute(&pages, &custom_stop_words, 2, &dir),
+            Err(WikiError::InputError(_))
+        ));
+
+        // custom tokenizers can not be compared, so they never resume
+        let custom_tokenizer = VectorOptions {
+            clean: CleanOptions {
+                tokenizer: SharedTokenizer::new(WhitespaceTokenizer),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(matches!(
+            TiledSimilarityMatrix::compute(&pages, &custom_tokenizer, 2, &dir),
+            Err(WikiError::InputError(_))
+        ));
+        let fresh = dir.join("custom");
+        assert!(TiledSimilarityMatrix::compute(&pages, &custom_tokenizer, 2, &fresh).is_ok());
+        assert!(TiledSimilarityMatrix::compute(&pages, &custom_tokenizer, 2, &fresh).is_err());
+
+        let opened = TiledSimilarityMatrix::open(&dir).unwrap();
+        assert_eq!(opened.tile_size(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_wikitext() {
+        use crate::wiki_info::{process_content, wikitext::parse_wikitext};
+
+        let source = r#"{{Short description|Capital of France}}
+{{Infobox French commune
+| name = Paris
+| coordinates = {{coord|48|51|24|N|2|21|08|E|display=inline,title}}
+}}
+'''Paris''' is the [[Capital city|capital]] of [[France]].<ref>Some source</ref>
+<!-- hidden note -->
+
+== History ==
+Founded by the [[Parisii (Gaul)|Parisii]]. See [https://example.com the site].
+{| class="wikitable"
+| Table || cells
+|}
+
+== See also ==
+* [[Lyon]]
+[[File:Paris.jpg|thumb|A view of [[Paris]]]]
+[[Category:Capitals in Europe]]
+[[fr:Paris]]
+"#;
+
+        let page = parse_wikitext("Paris", source);
+        assert_eq!(page.summary, "Paris is the capital of France.");
+        assert_eq!(page.infobox_type.as_deref(), Some("ib-french-commune"));
+        assert_eq!(page.categories, vec!["Capitals in Europe".to_string()]);
+        assert_eq!(
+            page.links[0].outlink,
+            "https://en.wikipedia.org/wiki/Capital_city"
+        );
+        assert_eq!(page.links.len(), 4);
+        assert_eq!(page.see_also.len(), 1);
+        assert_eq!(page.see_also[0].title, "Lyon");
+        assert!(page
+            .content
+            .contains("Founded by the Parisii. See the site."));
+        assert!(!page.content.contains("cells"));
+        assert!(!page.content.contains("thumb"));
+
+        let (latitude, longitude) = page.coordinates.unwrap().into();
+        assert!((latitude - 48.85666).abs() < 1e-4);
+        assert!((longitude - 2.35222).abs() < 1e-4);
+
+        // leading colons and sister projects are inline links, not page metadata
+        let page = parse_wikitext(
+            "Test",
+            "See [[:Category:Capitals in Europe|capitals]], [[:fr:Paris]], \
+             [[wikt:capital|the word]], [[mw:Help:Links]] and [[:Lyon]].\n[[de:Paris]]",
+        );
+        assert_eq!(page.categories, Vec::<String>::new());
+        assert_eq!(
+            page.interwiki,
+            vec![("de".to_string(), "Paris".to_string())]
+        );
+        assert_eq!(page.links.len(), 1);
+        assert_eq!(page.links[0].target, "Lyon");
+        assert!(page
+            .content
+            .contains("See capitals, fr:Paris, the word, mw:Help:Links and Lyon."));
+    }
+
+    #[test]
+    fn test_clean_meta_content_normalizes() {
+        // decomposed "e" + combining acute, and a leftover entity
+        let raw = "Caf\u{65}\u{301} Society &amp; Co.&#160;Ltd";
+        let cleaned = crate::wiki_info::clean_meta_content(raw);
+        assert_eq!(cleaned, "Caf\u{e9} Society & Co. Ltd");
+    }
+
+    #[test]
+    fn test_corpus_export_sparse_matrix() {
+        use crate::wiki_info::corpus::Corpus;
+
+        let mut corpus = Corpus::new();
+        assert_eq!(corpus.add_page(&page("A", "quick brown fox fox")), 0);
+        assert_eq!(corpus.add_page(&page("B", "lazy brown dog")), 1);
+        assert_eq!(corpus.terms(), &["quick", "brown", "fox", "lazy", "dog"]);
+        assert_eq!(corpus.term_id("brown"), Some(1));
+
+        let path =
+            std::env::temp_dir().join(format!("wiki-info-corpus-{}.mtx", std::process::id()));
+        corpus.export_sparse_matrix(&path).unwrap();
+
+        let matrix = std::fs::read_to_string(&path).unwrap();
+        let mut lines = matrix.lines();
+        assert_eq!(
+            lines.next(),
+            Some("%%MatrixMarket matrix coordinate real general")
+        );
+        assert_eq!(lines.next(), Some("2 5 6"));
+        assert_eq!(lines.next(), Some("1 1 0.25"));
+
+        let vocab_path = format!("{}.vocab", path.display());
+        let titles_path = format!("{}.titles", path.display());
+        assert_eq!(
+            std::fs::read_to_string(&vocab_path)
+                .unwrap()
+                .lines()
+                .count(),
+            5
+        );
+        assert_eq!(std::fs::read_to_string(&titles_path).unwrap(), "A\nB\n");
+
+        for file in [path.display().to_string(), vocab_path, titles_path] {
+            std::fs::remove_file(file).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_wiki_graph_from_pages() {
+        use crate::wiki_info::{graph::WikiGraph, HyperLink};
+
+        let link =
+            |slug: &str| HyperLink::from_href(slug.replace('_', " "), &format!("/wiki/{}", slug));
+
+        let pages = vec![
+            Page {
+                title: "Rust".to_string(),
+                links: vec![
+                    link("Mozilla"),
+                    link("Cargo_(software)"),
+                    link("Mozilla#History"),
+                ],
+                ..Default::default()
+            },
+            Page {
+                title: "Mozilla".to_string(),
+                links: vec![link("Rust"), link("Firefox"), link("Caf%C3%A9")],
+                ..Default::default()
+            },
+            Page {
+                title: "Café".to_string(),
+                links: vec![link("Mozilla")],
+                ..Default::default()
+            },
+        ];
+
+        let graph = WikiGraph::from_pages(&pages);
+        assert_eq!(graph.node_count(), 5);
+        assert_eq!(graph.edge_count(), 6);
+
+        // encoded targets land on the node of the decoded title
+        let cafe = graph.node_id("Café").unwrap();
+        assert_eq!(graph.inlinks(cafe), vec![graph.node_id("Mozilla").unwrap()]);
+
+        // links stored without a target fall back to their url
+        let stored = HyperLink {
+            outlink: "https://en.wikipedia.org/wiki/Caf%C3%A9".to_string(),
+            ..Default::default()
+        };
+        let graph_from_stored = WikiGraph::from_pages(&[Page {
+            title: "Rust".to_string(),
+            links: vec![stored],
+            ..Default::default()
+        }]);
+        assert!(graph_from_stored.node_id("Café").is_some());
+
+        let rust = graph.node_id("Rust").unwrap();
+        let mozilla = graph.node_id("Mozilla").unwrap();
+        assert_eq!(graph.inlinks(mozilla), vec![rust, cafe]);
+        assert!(graph.node_id("Cargo (software)").is_some());
+
+        #[cfg(feature = "petgraph")]
+        {
+            let petgraph = graph.to_petgraph();
+            assert_eq!(petgraph.node_count(), 5);
+            assert_eq!(petgraph.edge_count(), 6);
+            assert!(petgraph::algo::has_path_connecting(
+                &petgraph,
+                petgraph::graph::NodeIndex::new(mozilla),
+                petgraph::graph::NodeIndex::new(graph.node_id("Cargo (software)").unwrap()),
+                None
+            ));
+        }
+    }
+
+    #[test]
+    fn test_process_content_structured_blocks() {
+        use crate::wiki_info::{process_content_with, Block, ParseOptions};
+
+        let html = r#"<main id="content">
+            <p>Lead paragraph.<sup class="reference">[1]</sup></p>
+            <h2 id="History">History</h2>
+            <p>First.</p>
+            <ul><li>Outer<ul><li>Inner</li></ul></li></ul>
+            <table class="wikitable"><tr><th>A</th></tr><tr><td>1</td></tr></table>
+        </main>"#;
+
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let root = document.select(&selector).next().unwrap();
+
+        let flat = process_content_with(root, "Test", &ParseOptions::default());
+        assert!(flat.blocks.is_empty());
+
+        let options = ParseOptions {
+            structured: true,
+            ..Default::default()
+        };
+        let page = process_content_with(root, "Test", &options);
+
+        assert_eq!(page.blocks.len(), 6);
+        assert_eq!(
+            page.blocks[0],
+            Block::Paragraph("Lead paragraph.".to_string())
+        );
+        assert_eq!(
+            page.blocks[1],
+            Block::Heading {
+                level: 2,
+                text: "History".to_string()
+            }
+        );
+        assert!(matches!(&page.blocks[3], Block::ListItem { text, .. } if text == "Outer"));
+        assert!(matches!(&page.blocks[4], Block::ListItem { text, .. } if text == "Inner"));
+        assert!(matches!(&page.blocks[5], Block::Table(table) if table.headers == ["A"]));
+        assert_eq!(page.content, flat.content);
+    }
+
+    #[test]
+    fn test_links_filtered_by_namespace() {
+        use crate::wiki_info::{process_content_with, Block, Namespace, ParseOptions};
+
+        assert_eq!(Namespace::from_title("File:Paris.jpg"), Namespace::File);
+        assert_eq!(Namespace::from_title("User_talk:Example"), Namespace::Talk);
+        assert_eq!(
+            Namespace::from_title("Star Wars: Episode IV"),
+            Namespace::Article
+        );
+
+        let html = r#"<main id="content"><p>
+            <a href="/wiki/Paris">Paris</a>
+            <a href="/wiki/File:Paris.jpg">image</a>
+            <a href="/wiki/Help:Contents">help</a>
+            <a href="/wiki/Category:Capitals_in_Europe">Capitals in Europe</a>
+        </p></main>"#;
+
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let root = document.select(&selector).next().unwrap();
+
+        let page = process_content_with(root, "Test", &ParseOptions::default());
+        assert_eq!(page.links.len(), 1);
+        assert_eq!(page.links[0].namespace, Namespace::Article);
+        assert_eq!(page.categories, vec!["Capitals in Europe"]);
+
+        let options = ParseOptions {
+            namespaces: vec![Namespace::Article, Namespace::File],
+            ..Default::default()
+        };
+        let page = process_content_with(root, "Test", &options);
+        assert_eq!(page.links.len(), 2);
+        assert_eq!(page.links[1].namespace, Namespace::File);
+
+        // list item links of structured pages follow the same filter
+        let html = r#"<main id="content"><ul><li>
+            <a href="/wiki/Paris">Paris</a> and <a href="/wiki/File:Paris.jpg">image</a>
+        </li></ul></main>"#;
+        let document = Html::parse_document(html);
+        let root = document.select(&selector).next().unwrap();
+
+        for (namespaces, expected) in [(vec![Namespace::Article], 1), (options.namespaces, 2)] {
+            let options = ParseOptions {
+                structured: true,
+                namespaces,
+                ..Default::default()
+            };
+            let page = process_content_with(root, "Test", &options);
+            let Block::ListItem { links, .. } = &page.blocks[0] else {
+                panic!("expected a list item, got {:?}", page.blocks);
+            };
+            assert_eq!(links.len(), expected);
+            assert_eq!(links.len(), page.links.len());
+        }
+    }
+
+    #[cfg(feature = "tantivy")]
+    #[test]
+    fn test_index_pages_tantivy() {
+        use crate::wiki_info::fulltext::index_pages_tantivy;
+
+        let dir = std::env::temp_dir().join(format!("wiki-info-tantivy-{}", std::process::id()));
+        let pages = vec![
+            page(
+                "Gunslinger",
+                "Roland crosses the desert after the man in black",
+            ),
+            page("Desert", "A desert is a barren area of landscape"),
+            page("Castle", "A fortified structure built in Europe"),
+        ];
+
+        let index = index_pages_tantivy(&pages, &dir).unwrap();
+        let results = index.search("desert", 10).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, "Desert");
+
+        // indexing again replaces pages instead of duplicating them
+        let index = index_pages_tantivy(&pages[..1], &dir).unwrap();
+        assert_eq!(index.searcher().num_docs(), 3);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_links_deduplicated_with_counts() {
+        use crate::wiki_info::{process_content, wikitext::parse_wikitext};
+
+        let html = r#"<main id="content"><p>
+            <a href="/wiki/Paris">Paris</a>
+            <a href="/wiki/France">France</a>
+            <a href="/wiki/Paris">the capital</a>
+            <a href="/wiki/Paris">Paris</a>
+        </p></main>"#;
+
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let root = document.select(&selector).next().unwrap();
+
+        let page = process_content(root, "Test");
+        assert_eq!(page.links.len(), 2);
+        assert_eq!(page.links[0].title, "Paris");
+        assert_eq!((page.links[0].count, page.links[0].position), (3, 0));
+        assert_eq!((page.links[1].count, page.links[1].position), (1, 1));
+
+        let page = parse_wikitext("Test", "[[Lyon]] and [[Paris]]\n\n[[Paris|the capital]]");
+        assert_eq!(page.links.len(), 2);
+        assert_eq!((page.links[1].count, page.links[1].position), (2, 1));
+    }
+
+    #[test]
+    fn test_section_anchor_links() {
+        use crate::wiki_info::{process_content, url_utils, wikitext::parse_wikitext};
+
+        let html = r#"<main id="content"><p>
+            <a href="/wiki/France#History">history of France</a>
+            <a href="/wiki/France">France</a>
+            <a href="/wiki/Caf%C3%A9_culture">cafés</a>
+        </p></main>"#;
+
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let root = document.select(&selector).next().unwrap();
+
+        let page = process_content(root, "Test");
+        assert_eq!(page.links.len(), 2);
+        assert_eq!(
+            page.links[0].outlink,
+            "https://en.wikipedia.org/wiki/France"
+        );
+        assert_eq!(page.links[0].target, "France");
+        assert_eq!(page.links[0].fragment.as_deref(), Some("History"));
+        assert_eq!(page.links[0].count, 2);
+        assert_eq!(page.links[1].target, "Café culture");
+
+        let page = parse_wikitext("Test", "[[France#Early history|early France]]");
+        assert_eq!(page.links[0].target, "France");
+        assert_eq!(page.links[0].fragment.as_deref(), Some("Early history"));
+
+        let url = "https://en.wikipedia.org/wiki/France#History";
+        assert_eq!(
+            url_utils::split_fragment(url),
+            ("https://en.wikipedia.org/wiki/France", Some("History"))
+        );
+        assert_eq!(url_utils::title_from_url(url).trim(), "France");
+    }
+
+    #[test]
+    fn test_read_titles() {
+        let input = "Rust (programming language)\n\n  Mozilla  \nhttps://en.wikipedia.org/wiki/Cargo_(software)#History\nhttps://en.wikipedia.org/wiki/Caf%C3%A9\n";
+
+        let titles = crate::wiki_info::read_titles(input.as_bytes()).unwrap();
+        assert_eq!(
+            titles,
+            vec![
+                "Rust (programming language)",
+                "Mozilla",
+                "Cargo (software)",
+                "Café"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_link_context() {
+        use crate::wiki_info::{process_content_with, LinkContext, ParseOptions};
+
+        let html = r#"<main id="content">
+            <p>Paris is large. It is the capital of <a href="/wiki/France">France</a>, a country in Europe.<sup class="reference">[1]</sup> It sits on a river.</p>
+        </main>"#;
+
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let root = document.select(&selector).next().unwrap();
+
+        let page = process_content_with(root, "Paris", &ParseOptions::default());
+        assert_eq!(page.links[0].context, None);
+
+        let options = ParseOptions {
+            link_context: Some(LinkContext::Sentence),
+            ..Default::default()
+        };
+        let page = process_content_with(root, "Paris", &options);
+        assert_eq!(
+            page.links[0].context.as_deref(),
+            Some("It is the capital of France, a country in Europe.")
+        );
+
+        let options = ParseOptions {
+            link_context: Some(LinkContext::Words(2)),
+            ..Default::default()
+        };
+        let page = process_content_with(root, "Paris", &options);
+        assert_eq!(
+            page.links[0].context.as_deref(),
+            Some("capital of France, a")
+        );
+    }
+
+    #[test]
+    fn test_red_links() {
+        let html = r#"<main id="content"><p>
+            <a href="/wiki/Paris">Paris</a>
+            <a href="/w/index.php?title=Unwritten_Page&amp;action=edit&amp;redlink=1" class="new">Unwritten Page</a>
+        </p></main>"#;
+
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let root = document.select(&selector).next().unwrap();
+
+        let page = crate::wiki_info::process_content(root, "Test");
+        assert_eq!(page.links.len(), 2);
+        assert!(page.links[0].exists);
+        assert!(!page.links[1].exists);
+        assert_eq!(page.links[1].target, "Unwritten Page");
+        assert_eq!(
+            page.links[1].outlink,
+            "https://en.wikipedia.org/wiki/Unwritten_Page"
+        );
+
+        // links serialized before red link detection exist
+        let link: crate::wiki_info::HyperLink =
+            serde_json::from_str(r#"{"title":"Paris","outlink":"x"}"#).unwrap();
+        assert!(link.exists);
+    }
+
+    #[test]
+    fn test_interwiki_links() {
+        use crate::wiki_info::wikitext::parse_wikitext;
+
+        let html = r#"<main id="content">
+            <div id="p-lang-btn"><ul>
+                <li class="interlanguage-link interwiki-fr"><a href="https://fr.wikipedia.org/wiki/Paris" title="Paris – French" lang="fr" hreflang="fr" class="interlanguage-link-target">Français</a></li>
+                <li class="interlanguage-link interwiki-de"><a href="https://de.wikipedia.org/wiki/K%C3%B6ln_(Stadt)" title="Köln – German" lang="de" hreflang="de" class="interlanguage-link-target">Deutsch</a></li>
+            </ul></div>
+            <p>Text.</p>
+        </main>"#;
+
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let root = document.select(&selector).next().unwrap();
+
+        let page = crate::wiki_info::process_content(root, "Test");
+        assert_eq!(
+            page.interwiki,
+            vec![
+                ("fr".to_string(), "Paris".to_string()),
+                ("de".to_string(), "Köln (Stadt)".to_string())
+            ]
+        );
+        assert!(page.links.is_empty());
+
+        let page = parse_wikitext("Paris", "Paris is a city.\n[[fr:Paris]]\n[[de:Paris]]");
+        assert_eq!(page.interwiki.len(), 2);
+        assert_eq!(page.content, "Paris is a city.");
+    }
+
+    #[test]
+    fn test_fold_diacritics() {
+        use crate::wiki_info::{get_page_similarity_with, text::fold_diacritics, VectorOptions};
+
+        assert_eq!(fold_diacritics("Besançon"), "Besancon");
+        assert_eq!(
+            fold_diacritics("Ærøskøbing, Straße"),
+            "AEroskobing, Strasse"
+        );
+
+        let accented = Page {
+            title: "A".to_string(),
+            content: "Besançon citadelle".to_string(),
+            ..Default::default()
+        };
+        let plain = Page {
+            title: "B".to_string(),
+            content: "Besancon".to_string(),
+            ..Default::default()
+        };
+
+        // accented words are kept apart by default
+        assert_eq!(
+            get_page_similarity_with(&accented, &plain, &VectorOptions::default()),
+            0.0
+        );
+
+        let options = VectorOptions {
+            clean: crate::wiki_info::CleanOptions {
+                fold_diacritics: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(get_page_similarity_with(&accented, &plain, &options) > 0.5);
+    }
+
+    #[test]
+    fn test_parse_page_meta() {
+        use crate::wiki_info::meta::parse_page_meta;
+
+        let html = r#"<html><head>
+            <script>RLCONF={"wgArticleId":22989,"wgRevisionId":1250000000,"wgRestrictionEdit":["autoconfirmed"],"wgRestrictionMove":[]};</script>
+        </head><body>
+            <main id="content"><p>Text.</p></main>
+            <footer><li id="footer-info-lastmod"> This page was last edited on 2 October 2024, at 10:05<span class="anonymous-show">&#160;(UTC)</span>.</li></footer>
+        </body></html>"#;
+
+        let meta = parse_page_meta(&Html::parse_document(html));
+        assert_eq!(meta.page_id, Some(22989));
+        assert_eq!(meta.revision_id, Some(1250000000));
+        assert_eq!(meta.last_modified.as_deref(), Some("2024-10-02T10:05:00Z"));
+        assert_eq!(meta.edit_protection.as_deref(), Some("autoconfirmed"));
+        assert_eq!(meta.move_protection, None);
+        assert!(meta.is_protected());
+
+        assert_eq!(
+            parse_page_meta(&Html::parse_document("<p>no meta</p>")),
+            Default::default()
+        );
+    }
+
+    #[test]
+    fn test_importance_sampled_token_cap() {
+        use crate::wiki_info::{export::fit_vocabulary, TokenCap, VectorOptions};
+
+        let page = Page {
+            title: "Long".to_string(),
+            content: "city city city city city city river river river river museum cathedral"
+                .to_string(),
+            ..Default::default()
+        };
+
+        let options = VectorOptions {
+            max_words: Some(4),
+            token_cap: TokenCap::ImportanceSampled,
+            ..Default::default()
+        };
+
+        let terms = |options: &VectorOptions| -> Vec<String> {
+            fit_vocabulary(std::slice::from_ref(&page), options)
+                .into_iter()
+                .map(|entry| entry.term)
+                .collect()
+        };
+
+        let sampled = terms(&options);
+        assert!(
+            sampled.len() > 1,
+            "terms past the head should survive: {:?}",
+            sampled
+        );
+        assert_eq!(sampled, terms(&options), "sampling must be deterministic");
+
+        let head = VectorOptions {
+            max_words: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(terms(&head), vec!["city"]);
+    }
+
+    #[test]
+    fn test_page_signature() {
+        use crate::wiki_info::signature::Signature;
+
+        let text = "Paris is the capital and largest city of France, with an estimated population of two million residents";
+        let original = page("Paris", text);
+        let edited = page("Paris", &text.replace("two", "2.1"));
+        let other = page(
+            "Berlin",
+            "Berlin ist die Hauptstadt und ein Land der Bundesrepublik Deutschland",
+        );
+
+        let signature = original.signature();
+        assert_eq!(signature, page("paris", text).signature());
+        assert_eq!(signature.length, 17);
+        assert!(
+            signature.hamming_distance(&edited.signature())
+                < signature.hamming_distance(&other.signature())
+        );
+
+        let exported = signature.to_string();
+        assert_eq!(exported.parse::<Signature>().unwrap(), signature);
+        assert!("not-a-signature".parse::<Signature>().is_err());
+    }
+
+    #[test]
+    fn test_page_stats() {
+        let html = r#"<main id="content">
+            <p>The cat sat on the mat. The <a href="/wiki/Cat">cat</a> was happy.<sup class="reference">[1]</sup></p>
+            <p>See <a href="/wiki/Cat">cats</a> and <a href="/wiki/Mat">mats</a>.</p>
+            <ol class="references"><li>First source</li><li>Second source</li></ol>
+        </main>"#;
+
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let root = document.select(&selector).next().unwrap();
+
+        let page = crate::wiki_info::process_content(root, "Cat");
+        let stats = page.stats();
+
+        assert_eq!(stats.word_count, page.content.split_whitespace().count());
+        assert_eq!(stats.outlink_count, 2);
+        assert_eq!(stats.reference_count, 2);
+        assert!((stats.link_density - 3.0 / stats.word_count as f64).abs() < 1e-9);
+        assert!(stats.unique_terms < stats.word_count);
+        assert!(stats.reading_time > std::time::Duration::ZERO);
+
+        let wikitext = crate::wiki_info::wikitext::parse_wikitext(
+            "Cat",
+            r#"Cats.<ref>Source</ref> More.<ref name="a">Other</ref> Again.<ref name="a" />"#,
+        );
+        assert_eq!(wikitext.stats().reference_count, 2);
+    }
+
+    #[test]
+    fn test_detect_language() {
+        use crate::wiki_info::language::detect_language;
+
+        let english = detect_language(&page("", 
+            "Paris is the capital and largest city of France, with an estimated population of two million residents.",
+        ));
+        assert_eq!(english.code, "eng");
+        assert!(english.is_determined());
+
+        let german = detect_language(&page("", 
+            "Berlin ist die Hauptstadt und ein Land der Bundesrepublik Deutschland und die bevölkerungsreichste Stadt.",
+        ));
+        assert_eq!(german.code, "deu");
+
+        assert!(!detect_language(&page("", "")).is_determined());
+    }
+
+    #[test]
+    fn test_prelude() {
+        use crate::wiki_info::prelude::*;
+
+        let page = Page {
+            title: "Prelude".to_string(),
+            content: "everything from one import".to_string(),
+            ..Default::default()
+        };
+
+        let mut corpus = Corpus::with_options(VectorOptions::default());
+        corpus.add_page(&page);
+        assert_eq!(corpus.len(), 1);
+        assert!(get_page_similarity(&page, &page) > 0.99);
+    }
+
+    #[test]
+    fn test_page_paragraphs() {
+        use crate::wiki_info::{process_content_with, ParseOptions};
+
+        let html = r#"<main id="content">
+            <p>First paragraph.</p>
+            <h2 id="History">History</h2>
+            <p>Second <b>paragraph</b>.</p>
+            <ul><li>Not a paragraph</li></ul>
+        </main>"#;
+
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let root = document.select(&selector).next().unwrap();
+
+        let options = ParseOptions {
+            structured: true,
+            ..Default::default()
+        };
+        let page = process_content_with(root, "Test", &options);
+        assert_eq!(
+            page.paragraphs(),
+            vec!["First paragraph.", "Second paragraph."]
+        );
+
+        let flat = process_content_with(root, "Test", &ParseOptions::default());
+        assert_eq!(flat.paragraphs(), vec![flat.content.clone()]);
+        assert!(Page::default().paragraphs().is_empty());
+    }
+
+    #[test]
+    fn test_split_sentences() {
+        use crate::wiki_info::text::split_sentences;
+
+        let text = "Dr. Smith lives on Baker St. near the park. He wrote about J. R. R. Tolkien, e.g. his maps. \
+                    It sold c. 500 copies (roughly.) Did it sell well? \"Yes.\" The end";
+
+        assert_eq!(
+            split_sentences(text),
+            vec![
+                "Dr. Smith lives on Baker St. near the park.",
+                "He wrote about J. R. R. Tolkien, e.g. his maps.",
+                "It sold c. 500 copies (roughly.)",
+                "Did it sell well?",
+                "\"Yes.\"",
+                "The end",
+            ]
+        );
+        assert_eq!(
+            split_sentences("Pi is 3.14 exactly."),
+            vec!["Pi is 3.14 exactly."]
+        );
+    }
+
+    #[test]
+    fn test_select_content_fallbacks() {
+        use crate::wiki_info::select_content;
+
+        let vector =
+            r#"<div class="mw-content-container"><main id="content"><p>New</p></main></div>"#;
+        let legacy = r#"<div id="content"><div id="bodyContent"><p>Legacy</p></div></div>"#;
+        let minerva = r#"<div id="mw-content-text"><p>Mobile</p></div>"#;
+
+        let selected = |html: &str| {
+            let document = Html::parse_document(html);
+            select_content(&document)
+                .map(|(content, selector)| (content.text().collect::<String>(), selector))
+        };
+
+        assert_eq!(
+            selected(vector),
+            Some(("New".to_string(), "div.mw-content-container main#content"))
+        );
+        assert_eq!(selected(legacy), Some(("Legacy".to_string(), "#content")));
+        assert_eq!(
+            selected(minerva),
+            Some(("Mobile".to_string(), "#mw-content-text"))
+        );
+        assert_eq!(selected("<p>nothing</p>"), None);
+    }
+
+    #[test]
+    fn test_mobile_urls() {
+        use crate::wiki_info::{select_content_with, MOBILE_CONTENT_SELECTORS};
+
+        let mobile = "https://en.m.wikipedia.org/wiki/Paris#History";
+        assert!(url_utils::is_mobile_url(mobile));
+        assert!(!url_utils::is_mobile_url(
+            "https://en.wikipedia.org/wiki/Paris"
+        ));
+        assert_eq!(
+            url_utils::canonical_url(mobile),
+            "https://en.wikipedia.org/wiki/Paris"
+        );
+        assert_eq!(url_utils::title_from_url(mobile).trim(), "Paris");
+
+        let html = r#"<main id="content"><div class="page-actions-menu">Edit Watch</div>
+            <div id="bodyContent"><div id="mw-content-text"><p>Paris is a city.</p></div></div></main>"#;
+        let document = Html::parse_document(html);
+        let (content, selector) =
+            select_content_with(&document, &MOBILE_CONTENT_SELECTORS).unwrap();
+        assert_eq!(selector, "#mw-content-text");
+        assert_eq!(content.text().collect::<String>(), "Paris is a city.");
+    }
+
+    #[test]
+    fn test_plain_text_from_title() {
+        let text = crate::wiki_info::plain_text_from_title("Paris").unwrap();
+
+        assert!(text.contains("Paris"));
+        assert!(!text.contains('<'), "Plain text should not contain markup");
+    }
+
+    #[test]
+    fn test_extract_list_items() {
+        use crate::wiki_info::{lists::extract_list_items, process_content_with, ParseOptions};
+
+        let html = r#"<main id="content">
+            <p>This is a list of <a href="/wiki/City">cities</a>.</p>
+            <h2 id="Europe">Europe</h2>
+            <ul>
+                <li><a href="/wiki/Paris">Paris</a>, capital of <a href="/wiki/France">France</a></li>
+                <li><a href="/wiki/Berlin">Berlin</a></li>
+                <li>Unlinked city</li>
+            </ul>
+            <table class="wikitable">
+                <tr><th>Rank</th><th>City</th></tr>
+                <tr><td>1</td><td><a href="/wiki/Tokyo">Tokyo</a></td></tr>
+                <tr><td>2</td><td><a href="/wiki/Paris">Paris</a></td></tr>
+            </table>
+            <h2 id="See_also">See also</h2>
+            <ul><li><a href="/wiki/List_of_towns">List of towns</a></li></ul>
+        </main>"#;
+
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let root = document.select(&selector).next().unwrap();
+
+        let options = ParseOptions {
+            structured: true,
+            ..Default::default()
+        };
+        let page = process_content_with(root, "List of cities", &options);
+
+        let entries: Vec<String> = extract_list_items(&page)
+            .into_iter()
+            .map(|link| link.target)
+            .collect();
+        assert_eq!(entries, vec!["Paris", "Berlin", "Tokyo"]);
+    }
+
+    #[test]
+    fn test_stop_words() {
+        use crate::wiki_info::{clean_document_with, stop_words::StopWords, CleanOptions};
+
+        let english = StopWords::default();
+        assert!(english.contains("The"));
+        assert_eq!(StopWords::for_language("eng"), Some(english.clone()));
+        assert!(StopWords::for_language("deu").unwrap().contains("und"));
+        assert!(StopWords::for_language("fr").unwrap().contains("avec"));
+        assert_eq!(StopWords::for_language("xyz"), None);
+
+        let custom = english.without_words(["who"]).with_words(["Band"]);
+        assert!(!custom.contains("who"));
+        assert!(custom.contains("band"));
+
+        let path = std::env::temp_dir().join("wiki_info_test_stop_words.txt");
+        std::fs::write(&path, "# bands\nband\n\n  Album \n").unwrap();
+        let from_file = StopWords::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(from_file, StopWords::from_words(["band", "album"]));
+        assert!(StopWords::from_file(&path).is_err());
+
+        let page = Page {
+            title: "The Who".to_string(),
+            content: "The Who is a band".to_string(),
+            ..Default::default()
+        };
+        let options = CleanOptions {
+            stop_words: custom,
+            ..Default::default()
+        };
+        assert_eq!(clean_document_with(&page, &options).content.trim(), "who");
+
+        let options = CleanOptions {
+            stop_words: StopWords::none(),
+            ..Default::default()
+        };
+        assert_eq!(
+            clean_document_with(&page, &options).content.trim(),
+            "the who is a band"
+        );
+    }
+
+    #[test]
+    fn test_stemming() {
+        use crate::wiki_info::{clean_document_with, stem::Stemmer, CleanOptions};
+
+        let page = Page {
+            title: "Government".to_string(),
+            content: "The government and governments of Europe".to_string(),
+            ..Default::default()
+        };
+
+        let options = CleanOptions {
+            stemmer: Some(Stemmer::English),
+            ..Default::default()
+        };
+        assert_eq!(
+            clean_document_with(&page, &options).content.trim(),
+            "govern govern europ"
+        );
+        assert_eq!(
+            clean_document(&page).content.trim(),
+            "government governments europe"
+        );
+
+        assert_eq!(Stemmer::for_language("deu"), Some(Stemmer::German));
+        assert_eq!(Stemmer::for_language("xyz"), None);
+    }
+
+    #[cfg(feature = "lemmatizer")]
+    #[test]
+    fn test_lemmatizer() {
+        use crate::wiki_info::{
+            clean_document_with, lemma::Lemmatizer, stem::Stemmer, CleanOptions,
+        };
+
+        let english = Lemmatizer::english();
+        assert_eq!(english.lemmatize("went"), "go");
+        assert_eq!(english.lemmatize("cities"), "city");
+        assert_eq!(english.lemmatize("churches"), "church");
+        assert_eq!(english.lemmatize("series"), "series");
+        assert_eq!(english.lemmatize("university"), "university");
+
+        let page = Page {
+            title: "Government".to_string(),
+            content: "Governments and universities were founded".to_string(),
+            ..Default::default()
+        };
+        let options = CleanOptions {
+            stemmer: Some(Stemmer::English),
+            lemmatizer: Some(english),
+            ..Default::default()
+        };
+        assert_eq!(
+            clean_document_with(&page, &options).content.trim(),
+            "government university founded"
+        );
+
+        let path = std::env::temp_dir().join("wiki_info_test_lemmas.tsv");
+        std::fs::write(&path, "# forms\nmice\tmouse\n").unwrap();
+        let from_file = Lemmatizer::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(from_file, Lemmatizer::from_pairs([("mice", "mouse")]));
+        assert_eq!(from_file.lemmatize("cities"), "cities");
+    }
+
+    #[test]
+    fn test_clean_document_unicode() {
+        use crate::wiki_info::{clean_document_with, CleanOptions};
+
+        let page = Page {
+            title: "Škoda".to_string(),
+            content: "Škoda Auto, based in München. 東京都".to_string(),
+            ..Default::default()
+        };
+
+        let cleaned = clean_document(&page).content;
+        let words: Vec<&str> = cleaned.split_whitespace().collect();
+        assert!(words.contains(&"škoda"));
+        assert!(words.contains(&"auto"));
+        assert!(words.contains(&"münchen"));
+        assert!(words.contains(&"東"));
+
+        let options = CleanOptions {
+            ascii_only: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            clean_document_with(&page, &options).content.trim(),
+            "auto based"
+        );
+    }
+
+    #[test]
+    fn test_custom_tokenizer() {
+        use crate::wiki_info::{
+            clean_document_with,
+            tokenize::{SharedTokenizer, Token, Tokenizer, UnicodeTokenizer, WhitespaceTokenizer},
+            CleanOptions,
+        };
+
+        let text = "Glucose (C6H12O6) is a sugar.";
+        let words: Vec<String> = UnicodeTokenizer
+            .tokenize(text)
+            .into_iter()
+            .map(|token| token.text)
+            .collect();
+        assert_eq!(words, ["Glucose", "C6H12O6", "is", "a", "sugar"]);
+        assert_eq!(
+            WhitespaceTokenizer.tokenize(text)[1],
+            Token {
+                text: "C6H12O6".to_string(),
+                offset: 9
+            }
+        );
+
+        let page = Page {
+            title: "Glucose".to_string(),
+            content: text.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(clean_document(&page).content.trim(), "glucose sugar");
+
+        let options = CleanOptions {
+            tokenizer: SharedTokenizer::new(WhitespaceTokenizer),
+            keep_numbers: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            clean_document_with(&page, &options).content.trim(),
+            "glucose c6h12o6 sugar"
+        );
+
+        // closures are tokenizers too
+        let options = CleanOptions {
+            tokenizer: SharedTokenizer::new(|text: &str| {
+                vec![Token {
+                    text: text.to_string(),
+                    offset: 0,
+                }]
+            }),
+            ..Default::default()
+        };
+        assert_ne!(options, CleanOptions::default());
+        assert_eq!(CleanOptions::default(), CleanOptions::default());
+    }
+
+    #[test]
+    fn test_clean_options() {
+        use crate::wiki_info::{clean_document_with, CleanOptions, PunctuationPolicy};
+
+        let page = Page {
+            title: "Battle of Hastings".to_string(),
+            content: "The Battle of Hastings in 1066 wasn't a well-known siege".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            clean_document(&page).content.trim(),
+            "battle hastings well known siege"
+        );
+
+        let options = CleanOptions {
+            keep_numbers: true,
+            punctuation: PunctuationPolicy::Strip,
+            min_token_length: 4,
+            lowercase: false,
+            remove_stop_words: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            clean_document_with(&page, &options).content.trim(),
+            "Battle Hastings 1066 wasnt well known siege"
+        );
+    }
+
+    #[test]
+    fn test_extract_keywords() {
+        use crate::wiki_info::keywords::extract_keywords;
+
+        let page = Page {
+            title: "Volcano".to_string(),
+            content: "A volcano is a rupture in the crust. Lava erupts from the volcano. \
+                The volcano releases lava, ash and gases. Ash from the volcano falls nearby."
+                .to_string(),
+            ..Default::default()
+        };
+
+        let keywords = extract_keywords(&page, 3);
+        assert_eq!(keywords.len(), 3);
+        assert_eq!(keywords[0].0, "volcano");
+        assert!(keywords.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        assert!(extract_keywords(&Page::default(), 3).is_empty());
+    }
+
+    #[test]
+    fn test_extract_entities() {
+        use crate::wiki_info::{
+            classify::EntityType,
+            entities::{entity_overlap, extract_entities},
+        };
+
+        let page = Page {
+            title: "Pound sterling".to_string(),
+            content: "The pound is issued by the Bank of England. Sir Isaac Newton \
+                ran the Royal Mint in London. Trade on the River Thames used the pound."
+                .to_string(),
+            links: vec![crate::wiki_info::HyperLink::from_href(
+                "London".to_string(),
+                "/wiki/London",
+            )],
+            ..Default::default()
+        };
+
+        let entities = extract_entities(&page);
+        let find = |name: &str| entities.iter().find(|entity| entity.name == name);
+
+        assert_eq!(
+            find("Bank of England").unwrap().entity_type,
+            Some(EntityType::Organization)
+        );
+        assert_eq!(
+            find("Isaac Newton").unwrap().entity_type,
+            Some(EntityType::Person)
+        );
+        assert_eq!(
+            find("River Thames").unwrap().entity_type,
+            Some(EntityType::Place)
+        );
+        assert!(find("London").unwrap().outlink.is_some());
+        assert!(find("Trade").is_none());
+        assert!(find("The").is_none());
+
+        assert_eq!(entity_overlap(&page, &page), 1.0);
+        assert_eq!(entity_overlap(&page, &Page::default()), 0.0);
+    }
+
+    #[test]
+    fn test_summarize() {
+        use crate::wiki_info::summarize::summarize;
+
+        let page = Page {
+            title: "Volcano".to_string(),
+            content: "A volcano is a rupture in the crust of a planet. \
+                Lava and ash erupt from a volcano. \
+                The museum shop sells postcards. \
+                Volcano eruptions release lava, ash and gases from the crust."
+                .to_string(),
+            ..Default::default()
+        };
+
+        let summary = summarize(&page, 2);
+        assert!(!summary.contains("postcards"));
+        assert_eq!(crate::wiki_info::text::split_sentences(&summary).len(), 2);
+        assert_eq!(summarize(&page, 10), page.content);
+        assert_eq!(summarize(&page, 0), "");
+    }
+
+    #[test]
+    fn test_term_frequencies() {
+        use crate::wiki_info::CleanOptions;
+
+        let page = Page {
+            title: "Lava".to_string(),
+            content: "Lava flows. The lava cools into basalt, and basalt erodes. Lava!".to_string(),
+            ..Default::default()
+        };
+
+        let frequencies = page.term_frequencies(&CleanOptions::default());
+        assert_eq!(frequencies["lava"], 3);
+        assert_eq!(frequencies["basalt"], 2);
+        assert!(!frequencies.contains_key("the"));
+
+        assert_eq!(
+            page.top_terms(2),
+            vec![("lava".to_string(), 3), ("basalt".to_string(), 2)]
+        );
+        assert_eq!(page.top_terms(100).len(), frequencies.len());
+    }
+
+    #[test]
+    fn test_process_content_drops_styles() {
+        let html = r#"<main id="content">
+            <style data-mw-deduplicate="TemplateStyles:r1">.mw-parser-output .hatnote{font-style:italic}</style>
+            <p>Lava is molten rock.<script>var wgPageName = "Lava";</script></p>
+            <ul><li><style>.mw-parser-output .plainlist ul{margin:0}</style>Basalt</li></ul>
+            <noscript><img src="pixel.png"></noscript>
+        </main>"#;
+        let document = Html::parse_document(html);
+        let selector = scraper::Selector::parse("main#content").unwrap();
+        let element = document.select(&selector).next().unwrap();
+
+        let page = crate::wiki_info::process_content(element, "Lava");
+        assert_eq!(page.content, "Lava is molten rock. Basalt");
+        assert!(!page.summary.contains("wgPageName"));
+    }
+
+    #[test]
+    fn test_content_clean() {
+        use crate::wiki_info::vector_cache::CleanCache;
+
+        let mut page = page("Lava", "The lava cools into basalt.");
+        let mut cache = CleanCache::default();
+
+        assert_eq!(page.content_raw(), "The lava cools into basalt.");
+        assert_eq!(&*cache.content_clean(&page), clean_document(&page).content);
+        cache.content_clean(&page);
+        assert_eq!(cache.len(), 1);
+
+        // an edited page is cleaned again
+        page.content = "Obsidian shatters.".to_string();
+        assert_eq!(cache.content_clean(&page).trim(), "obsidian shatters");
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_clean_fold_diacritics() {
+        use crate::wiki_info::{clean_document_with, CleanOptions};
+
+        let page = Page {
+            title: "Zürich".to_string(),
+            content: "Zürich and Genève, not Zurich".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(clean_document(&page).content.trim(), "zürich genève zurich");
+
+        let options = CleanOptions {
+            fold_diacritics: true,
+            ascii_only: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            clean_document_with(&page, &options).content.trim(),
+            "zurich geneve zurich"
+        );
+    }
+
+    #[test]
+    fn test_collocations() {
+        use crate::wiki_info::{
+            collocations::{collocations, merge_collocations},
+            corpus::Corpus,
+        };
+
+        let pages = [
+            "The European Union signed a treaty with Norway.",
+            "Trade rules of the European Union cover fishing boats.",
+            "Norway is not in the European Union but trades with it.",
+            "Fishing boats from Norway sell fish in markets.",
+        ];
+
+        let mut corpus = Corpus::new();
+        for (i, content) in pages.iter().enumerate() {
+            corpus.add_page(&Page {
+                title: format!("Page {}", i),
+                content: content.to_string(),
+                ..Default::default()
+            });
+        }
+
+        let found = collocations(&corpus, 2);
+        assert_eq!(
+            (found[0].0.as_str(), found[0].1.as_str()),
+            ("european", "union")
+        );
+        assert!(found.iter().all(|(_, _, score)| *score > 0.0));
+        assert!(collocations(&corpus, 10).is_empty());
+
+        assert_eq!(
+            merge_collocations("european union trade", &found[..1]),
+            "european_union trade"
+        );
+    }
+
+    #[test]
+    fn test_readability() {
+        use crate::wiki_info::readability::readability;
+
+        let simple = Page {
+            title: "Cat".to_string(),
+            content: "The cat sat on the mat. It was a big cat. The dog ran.".to_string(),
+            ..Default::default()
+        };
+        let complex = Page {
+            title: "Epistemology".to_string(),
+            content: "Epistemological investigations characteristically interrogate \
+                the justificatory foundations underlying propositional knowledge, \
+                differentiating inferential understanding from observational acquaintance."
+                .to_string(),
+            ..Default::default()
+        };
+
+        let simple = readability(&simple);
+        let complex = readability(&complex);
+
+        assert_eq!(simple.sentences, 3);
+        assert_eq!(simple.words, 14);
+        assert_eq!(simple.syllables, 14);
+        assert!(simple.flesch_reading_ease > complex.flesch_reading_ease);
+        assert!(simple.flesch_kincaid_grade < complex.flesch_kincaid_grade);
+        assert!(simple.smog_grade < complex.smog_grade);
+
+        assert_eq!(readability(&Page::default()).words, 0);
+    }
+
+    #[test]
+    fn test_concordance() {
+        use crate::wiki_info::concordance::concordance;
+
+        let page = Page {
+            title: "Lava".to_string(),
+            content: "Lava is molten rock. When lava cools, it forms basalt.".to_string(),
+            ..Default::default()
+        };
+
+        let snippets = concordance(&page, "LAVA", 2);
+        assert_eq!(snippets.len(), 2);
+
+        assert_eq!(snippets[0].left, "");
+        assert_eq!(snippets[0].right, "is molten");
+
+        assert_eq!(snippets[1].left, "rock. When");
+        assert_eq!(snippets[1].keyword, "lava");
+        assert_eq!(snippets[1].right, "cools, it");
+        assert_eq!(&page.content[snippets[1].start..snippets[1].end], "lava");
+
+        assert!(concordance(&page, "magma", 2).is_empty());
+    }
+
+    #[test]
+    fn test_extract_acronyms() {
+        use crate::wiki_info::{acronyms::extract_acronyms, clean_document_with, CleanOptions};
+
+        let page = Page {
+            title: "NATO".to_string(),
+            content: "The North Atlantic Treaty Organization (NATO) was founded in 1949. \
+                It works with the US Department of Defense (DoD). NATO has 32 members. \
+                Plain words (like these) are not acronyms."
+                .to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            extract_acronyms(&page),
+            vec![
+                (
+                    "NATO".to_string(),
+                    "North Atlantic Treaty Organization".to_string()
+                ),
+                ("DoD".to_string(), "Department of Defense".to_string()),
+            ]
+        );
+
+        let options = CleanOptions {
+            expand_acronyms: true,
+            ..Default::default()
+        };
+        let cleaned = clean_document_with(&page, &options).content;
+        assert!(!cleaned.contains("nato"));
+        assert_eq!(cleaned.matches("organization").count(), 2);
+    }
+
+    #[test]
+    fn test_extract_dates() {
+        use crate::wiki_info::dates::{extract_dates, time_overlap, time_span};
+
+        let page = Page {
+            title: "French Revolution".to_string(),
+            content: "The Bastille fell on 14 July 1789. The monarchy ended in September 1792. \
+                The Directory ruled until November 9, 1799. Rome was older, from 753 BC. \
+                Around 2500 people were there."
+                .to_string(),
+            ..Default::default()
+        };
+
+        let dates = extract_dates(&page);
+        let summary: Vec<(i32, Option<u8>, Option<u8>)> = dates
+            .iter()
+            .map(|mention| (mention.year, mention.month, mention.day))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1789, Some(7), Some(14)),
+                (1792, Some(9), None),
+                (1799, Some(11), Some(9)),
+                (-753, None, None),
+            ]
+        );
+        assert_eq!(dates[0].text, "14 July 1789");
+        assert_eq!(dates[0].context, "The Bastille fell on 14 July 1789.");
+        assert_eq!(&page.content[dates[1].offset..][..14], "September 1792");
+
+        assert_eq!(time_span(&page), Some((-753, 1799)));
+        assert_eq!(time_span(&Page::default()), None);
+
+        let napoleon = Page {
+            title: "Napoleon".to_string(),
+            content: "Napoleon lived from 1769 to 1821.".to_string(),
+            ..Default::default()
+        };
+        let revolution = Page {
+            title: "Revolution".to_string(),
+            content: "It lasted from 1789 to 1799.".to_string(),
+            ..Default::default()
+        };
+        assert!((time_overlap(&napoleon, &revolution) - 11.0 / 53.0).abs() < 1e-9);
+        assert_eq!(time_overlap(&napoleon, &Page::default()), 0.0);
+    }
+
+    #[test]
+    fn test_corpus_tf_idf() {
+        use crate::wiki_info::corpus::Corpus;
+
+        let volcano = page("Volcano", "Volcano lava erupts, history of the volcano");
+        let basalt = page("Basalt", "Basalt forms when lava cools, history of basalt");
+        let rome = page("Rome", "Rome is a city with a long history");
+
+        let mut corpus = Corpus::new();
+        for page in [&volcano, &basalt, &rome] {
+            corpus.add_page(page);
+        }
+
+        let history = corpus.term_id("history").unwrap();
+        let lava = corpus.term_id("lava").unwrap();
+        assert_eq!(corpus.document_frequency(history), 3);
+        assert_eq!(corpus.document_frequency(lava), 2);
+        assert!(corpus.idf(lava) > corpus.idf(history));
+        assert_eq!(corpus.idf(history), 1.0);
+
+        let vector = corpus.vectorize(&volcano);
+        assert_eq!(vector.len(), corpus.terms().len());
+        assert!(vector[corpus.term_id("volcano").unwrap()] > vector[history]);
+
+        assert!(corpus.similarity(&volcano, &basalt) > corpus.similarity(&volcano, &rome));
+        assert_eq!(corpus.similarity(&volcano, &Page::default()), 0.0);
+    }
+
+    #[test]
+    fn test_bm25_score() {
+        use crate::wiki_info::bm25::{bm25_score, CorpusStats};
+        use crate::wiki_info::corpus::Corpus;
+
+        let volcano = page("Volcano", "Volcano lava erupts from the volcano crater");
+        let basalt = page(
+            "Basalt",
+            "Basalt forms when lava cools quickly near the surface",
+        );
+        let rome = page("Rome", "Rome is a city with a long history of emperors");
+
+        let mut corpus = Corpus::new();
+        for page in [&volcano, &basalt, &rome] {
+            corpus.add_page(page);
+        }
+        let stats = CorpusStats::from_corpus(&corpus);
+        assert_eq!(stats.documents(), 3);
+
+        let query = ["Volcano", "lava"];
+        let volcano_score = bm25_score(&query, &volcano, &stats);
+        let basalt_score = bm25_score(&query, &basalt, &stats);
+        assert!(volcano_score > basalt_score);
+        assert!(basalt_score > 0.0);
+        assert_eq!(bm25_score(&query, &rome, &stats), 0.0);
+
+        // rarer terms weigh more
+        assert!(stats.idf("crater") > stats.idf("lava"));
+
+        // without saturation or length normalization repeated terms count fully
+        let flat = stats.clone().with_params(0.0, 0.0);
+        assert_eq!(
+            bm25_score(&["volcano"], &volcano, &flat),
+            stats.idf("volcano")
+        );
+
+        assert!(bm25_score(&basalt, &volcano, &stats) > bm25_score(&basalt, &rome, &stats));
+    }
+
+    #[test]
+    fn test_set_similarity() {
+        use crate::wiki_info::{jaccard_sim, link_jaccard_sim, link_overlap_sim, overlap_sim};
+
+        let with_links = |content: &str, links: &[&str]| Page {
+            links: links
+                .iter()
+                .map(|href| crate::wiki_info::HyperLink::from_href(href.to_string(), href))
+                .collect(),
+            ..page("", content)
+        };
+        let stub = with_links("volcano lava", &["/wiki/Lava"]);
+        let long = with_links(
+            "volcano lava crater magma eruption",
+            &["/wiki/Lava", "/wiki/Magma", "/wiki/Crater#Rim"],
+        );
+        let other = with_links("city emperor", &["/wiki/Crater"]);
+
+        assert!((jaccard_sim(&stub, &long) - 0.4).abs() < 1e-9);
+        assert_eq!(overlap_sim(&stub, &long), 1.0);
+        assert_eq!(jaccard_sim(&stub, &other), 0.0);
+        assert_eq!(overlap_sim(&stub, &Page::default()), 0.0);
+
+        assert!((link_jaccard_sim(&stub, &long) - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(link_overlap_sim(&stub, &long), 1.0);
+        // section anchors point at the same page
+        assert_eq!(link_overlap_sim(&other, &long), 1.0);
+        assert_eq!(link_jaccard_sim(&Page::default(), &Page::default()), 0.0);
+    }
+
+    #[test]
+    fn test_get_top_k_similar() {
+        use crate::wiki_info::get_top_k_similar;
+
+        let primary = page("", "volcano lava eruption magma");
+        let pages = [
+            page("", "city emperor senate"),
+            page("", "volcano lava eruption magma"),
+            Page::default(),
+            page("", "lava magma basalt"),
+        ];
+
+        let top = get_top_k_similar(&primary, &pages, 3);
+        assert_eq!(top.len(), 3);
+        assert_eq!(top[0].0, 1);
+        assert!((top[0].1 - 1.0).abs() < 1e-9);
+        assert_eq!(top[1].0, 3);
+        assert!(top[1].1 > 0.0 && top[1].1 < 1.0);
+        // nothing else is similar, the empty page scores 0 rather than NaN
+        assert_eq!(top[2], (0, 0.0));
+
+        assert_eq!(get_top_k_similar(&primary, &pages, 10).len(), 4);
+        assert!(get_top_k_similar(&primary, &[], 3).is_empty());
+    }
+
+    #[test]
+    fn test_similarity_matrix() {
+        use crate::wiki_info::{
+            get_page_similarity, similarity_matrix, similarity_matrix_with, LengthNormalization,
+            VectorOptions,
+        };
+
+        let pages = [
+            page("", "volcano lava eruption magma"),
+            page("", "lava magma basalt"),
+            page("", "city emperor senate"),
+            Page::default(),
+        ];
+
+        let matrix = similarity_matrix(&pages);
+        assert_eq!((matrix.rows(), matrix.cols()), (4, 4));
+        for i in 0..3 {
+            assert!((matrix[(i, i)] - 1.0).abs() < 1e-9);
+            for j in 0..4 {
+                assert_eq!(matrix[(i, j)], matrix[(j, i)]);
+            }
+        }
+        assert!((matrix[(0, 1)] - get_page_similarity(&pages[0], &pages[1])).abs() < 1e-9);
+        assert_eq!(matrix[(0, 2)], 0.0);
+        assert_eq!(matrix.row(3), &[0.0; 4]);
+        assert_eq!(matrix.get(4, 0), None);
+
+        let pivoted = VectorOptions {
+            length_normalization: LengthNormalization::Pivoted {
+                slope: 0.3,
+                pivot: None,
+            },
+            ..Default::default()
+        };
+        let matrix = similarity_matrix_with(&pages, &pivoted);
+        for i in 0..4 {
+            for j in 0..4 {
+                assert!((matrix[(i, j)] - matrix[(j, i)]).abs() < 1e-12);
+            }
+        }
+
+        assert_eq!(similarity_matrix(&[]).rows(), 0);
+    }
+
+    #[test]
+    fn test_minhash_lsh() {
+        use crate::wiki_info::minhash::{LshIndex, MinHasher};
+
+        let volcano = page(
+            "",
+            "volcano lava eruption magma crater ash basalt plume vent caldera",
+        );
+        let mirror = page(
+            "",
+            "volcano lava eruption magma crater ash basalt plume vent caldera",
+        );
+        let similar = page(
+            "",
+            "volcano lava eruption magma crater ash basalt plume vent geyser",
+        );
+        let rome = page(
+            "",
+            "city emperor senate forum legion consul republic aqueduct",
+        );
+
+        let hasher = MinHasher::new(128);
+        let signature = hasher.signature(&volcano);
+        assert_eq!(signature.0.len(), 128);
+        assert_eq!(signature.jaccard(&hasher.signature(&mirror)), 1.0);
+        let estimate = signature.jaccard(&hasher.signature(&similar));
+        assert!((estimate - 9.0 / 11.0).abs() < 0.15, "{}", estimate);
+        assert!(signature.jaccard(&hasher.signature(&rome)) < 0.1);
+        assert!(hasher.signature(&Page::default()).is_empty());
+
+        let mut index = LshIndex::new(hasher, 32);
+        assert_eq!(index.add_pages(&[rome, similar, mirror]), vec![0, 1, 2]);
+        assert_eq!(index.add_page(&Page::default()), 3);
+
+        let candidates = index.candidates(&volcano);
+        assert!(candidates.contains(&1) && candidates.contains(&2));
+        assert!(!candidates.contains(&0) && !candidates.contains(&3));
+
+        let matches = index.query(&volcano, 0.95);
+        assert_eq!(matches, vec![(2, 1.0)]);
+        assert!(index.query(&Page::default(), 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_simhash_near_duplicate() {
+        use crate::wiki_info::signature::{is_near_duplicate, simhash};
+
+        let text = "Paris is the capital and largest city of France, with an estimated \
+            population of two million residents in an area of more than one hundred \
+            square kilometres, and one of the major centres of finance, diplomacy, \
+            commerce, culture, fashion and gastronomy in the world";
+        let original = page("Paris", text);
+        let mirror = page("Paris (mirror)", text);
+        let edited = page("Paris", &text.replace("two million", "2.1 million"));
+        let other = page("Basalt", "Basalt is a fine grained volcanic rock formed from the rapid cooling of lava rich in magnesium and iron");
+
+        assert_eq!(simhash(&original), original.signature().simhash);
+        assert!(is_near_duplicate(&original, &mirror, 0));
+        assert!(is_near_duplicate(&original, &edited, 10));
+        assert!(!is_near_duplicate(&original, &other, 10));
+    }
+
+    #[test]
+    fn test_word_embeddings() {
+        use crate::wiki_info::corpus::Corpus;
+        use crate::wiki_info::embeddings::WordEmbeddings;
+
+        let vectors = "3 3\n\
+            car 1.0 0.1 0.0\n\
+            automobile 0.9 0.2 0.0\n\
+            Engine 0.8 0.3 0.1\n\
+            banana 0.0 0.1 1.0\n\
+            fruit 0.1 0.0 0.9\n";
+        let embeddings = WordEmbeddings::from_reader(vectors.as_bytes()).unwrap();
+        assert_eq!(embeddings.dimensions(), 3);
+        assert_eq!(embeddings.len(), 5);
+        assert!(embeddings.get("engine").is_some());
+
+        let car = page("", "The car has an engine");
+        let automobile = page("", "An automobile");
+        let banana = page("", "A banana is a fruit");
+
+        // no shared words, but similar meaning
+        assert_eq!(
+            crate::wiki_info::get_page_similarity(&car, &automobile),
+            0.0
+        );
+        assert!(embeddings.similarity(&car, &automobile) > 0.9);
+        assert!(embeddings.similarity(&car, &banana) < 0.3);
+        assert_eq!(embeddings.similarity(&car, &page("", "unknown words")), 0.0);
+
+        let mut corpus = Corpus::new();
+        for page in [&car, &automobile, &banana] {
+            corpus.add_page(page);
+        }
+        assert!(embeddings.similarity_weighted(&car, &automobile, &corpus) > 0.9);
+
+        assert!(WordEmbeddings::from_reader("car 1.0 0.1\nfruit 0.1\n".as_bytes()).is_err());
+        assert!(WordEmbeddings::from_reader("car 1.0 x\n".as_bytes()).is_err());
+    }
+
+    #[cfg(feature = "onnx")]
+    #[test]
+    fn test_sentence_encoder_missing_files() {
+        use crate::wiki_info::{onnx::SentenceEncoder, WikiError};
+
+        let dir = std::env::temp_dir().join("wiki_info_onnx_missing");
+        let result =
+            SentenceEncoder::from_files(dir.join("model.onnx"), dir.join("tokenizer.json"));
+
+        assert!(matches!(result, Err(WikiError::IOError(_))));
+    }
+
+    #[test]
+    fn test_soft_cosine_sim() {
+        use std::collections::HashMap;
+
+        use crate::wiki_info::embeddings::WordEmbeddings;
+        use crate::wiki_info::get_page_similarity;
+        use crate::wiki_info::soft_cosine::{soft_cosine_sim, CharNgramSimilarity};
+
+        let car = page("", "car engine");
+        let automobile = page("", "automobile motor");
+        let banana = page("", "banana fruit");
+
+        let vectors: HashMap<String, Vec<f32>> = [
+            ("car", [1.0, 0.1, 0.0]),
+            ("automobile", [0.95, 0.15, 0.0]),
+            ("engine", [0.7, 0.6, 0.0]),
+            ("motor", [0.75, 0.55, 0.05]),
+            ("banana", [0.0, 0.1, 1.0]),
+            ("fruit", [0.1, 0.0, 0.9]),
+        ]
+        .into_iter()
+        .map(|(word, vector)| (word.to_owned(), vector.to_vec()))
+        .collect();
+        let embeddings = WordEmbeddings::from_vectors(vectors, 3);
+
+        assert_eq!(get_page_similarity(&car, &automobile), 0.0);
+        assert!(soft_cosine_sim(&car, &automobile, &embeddings) > 0.9);
+        assert_eq!(soft_cosine_sim(&car, &banana, &embeddings), 0.0);
+        assert!((soft_cosine_sim(&car, &car, &embeddings) - 1.0).abs() < 1e-9);
+        assert_eq!(soft_cosine_sim(&car, &Page::default(), &embeddings), 0.0);
+
+        let ngrams = CharNgramSimilarity::default();
+        let volcanic = page("", "volcanic eruptions");
+        let volcano = page("", "volcano eruption");
+        assert_eq!(get_page_similarity(&volcanic, &volcano), 0.0);
+        assert!(soft_cosine_sim(&volcanic, &volcano, &ngrams) > 0.5);
+        assert_eq!(soft_cosine_sim(&volcanic, &banana, &ngrams), 0.0);
+    }
+
+    #[test]
+    fn test_corpus_lsa() {
+        use crate::wiki_info::corpus::Corpus;
+
+        let pages = [
+            page("", "volcano lava magma eruption"),
+            page("", "lava magma crater basalt"),
+            page("", "crater basalt eruption ash"),
+            page("", "emperor senate legion forum"),
+            page("", "senate consul legion republic"),
+            page("", "consul republic forum emperor"),
+        ];
+
+        let mut corpus = Corpus::new();
+        for page in &pages {
+            corpus.add_page(page);
+        }
+
+        let lsa = corpus.lsa(2);
+        assert_eq!(lsa.dimensions(), 2);
+        assert!(lsa.singular_values()[0] >= lsa.singular_values()[1]);
+        assert_eq!(lsa.document_vector(0).len(), 2);
+
+        // no shared terms, but linked through the rest of their topic
+        let volcano = page("", "volcano ash");
+        let basalt = page("", "basalt");
+        assert_eq!(corpus.similarity(&volcano, &basalt), 0.0);
+        assert!(lsa.similarity(&volcano, &basalt) > 0.9);
+        assert!(lsa.similarity(&volcano, &pages[4]) < 0.1);
+
+        assert!(lsa.document_similarity(0, 2) > lsa.document_similarity(0, 3));
+        // a page of the corpus folds in onto its own document vector
+        let folded = lsa.vectorize(&pages[1]);
+        for (a, b) in folded.iter().zip(lsa.document_vector(1)) {
+            assert!((a - b).abs() < 1e-6);
+        }
+
+        assert_eq!(Corpus::new().lsa(10).dimensions(), 0);
+    }
+
+    #[test]
+    fn test_lda_topics() {
+        use crate::wiki_info::corpus::Corpus;
+        use crate::wiki_info::topics::{lda, topic_similarity};
+
+        let pages = [
+            page("", "volcano lava magma eruption lava magma volcano"),
+            page("", "lava magma crater basalt eruption volcano"),
+            page("", "crater basalt eruption lava volcano magma"),
+            page("", "emperor senate legion forum senate emperor"),
+            page("", "senate consul legion republic emperor forum"),
+            page("", "consul republic forum emperor senate legion"),
+        ];
+
+        let mut corpus = Corpus::new();
+        for page in &pages {
+            corpus.add_page(page);
+        }
+
+        let model = lda(&corpus, 2, 200);
+        assert_eq!(model.topics(), 2);
+        assert_eq!(model, lda(&corpus, 2, 200));
+
+        let volcano = model.dominant_topic(0);
+        let rome = model.dominant_topic(3);
+        assert_ne!(volcano, rome);
+        assert!((1..3).all(|doc| model.dominant_topic(doc) == volcano));
+        assert!((4..6).all(|doc| model.dominant_topic(doc) == rome));
+
+        let words: Vec<String> = model
+            .top_words(volcano, 3)
+            .into_iter()
+            .map(|(word, _)| word)
+            .collect();
+        assert!(words.contains(&"lava".to_string()));
+
+        let mixture: f64 = model.document_mixture(0).iter().sum();
+        assert!((mixture - 1.0).abs() < 1e-9);
+        assert!(model.document_similarity(0, 1) > model.document_similarity(0, 4));
+        assert!((topic_similarity(&[0.5, 0.5], &[0.5, 0.5]) - 1.0).abs() < 1e-9);
+        assert!(topic_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_kmeans_clustering() {
+        use crate::wiki_info::cluster::{kmeans, kmeans_with, silhouette_score, KMeansOptions};
+        use crate::wiki_info::corpus::Corpus;
+
+        let pages = [
+            page("Volcano", "volcano lava magma eruption"),
+            page("Basalt", "lava magma crater basalt"),
+            page("Crater", "crater basalt eruption lava"),
+            page("Emperor", "emperor senate legion forum"),
+            page("Senate", "senate consul legion republic"),
+            page("Consul", "consul republic forum emperor"),
+        ];
+
+        let mut corpus = Corpus::new();
+        for page in &pages {
+            corpus.add_page(page);
+        }
+
+        let assignments = kmeans(&corpus, 2);
+        assert_eq!(assignments.len(), 6);
+        assert_eq!(assignments[3].title, "Emperor");
+        let volcano = assignments[0].cluster;
+        assert!(assignments[..3].iter().all(|a| a.cluster == volcano));
+        assert!(assignments[3..].iter().all(|a| a.cluster != volcano));
+
+        let model = kmeans_with(
+            &corpus,
+            &KMeansOptions {
+                k: 2,
+                ..Default::default()
+            },
+        );
+        assert_eq!(model.centroid(volcano).len(), corpus.terms().len());
+        assert_eq!(model.members(volcano).len(), 3);
+        let labels: Vec<String> = model
+            .top_terms(&corpus, volcano, 2)
+            .into_iter()
+            .map(|(term, _)| term)
+            .collect();
+        assert!(
+            labels
+                .iter()
+                .all(|term| ["lava", "magma", "crater", "basalt", "eruption"]
+                    .contains(&term.as_str()))
+        );
+
+        let good = silhouette_score(&corpus, &assignments);
+        let mut shuffled = assignments.clone();
+        shuffled[0].cluster = 1 - shuffled[0].cluster;
+        shuffled[3].cluster = 1 - shuffled[3].cluster;
+        assert!(good > 0.0);
+        assert!(good > silhouette_score(&corpus, &shuffled));
+
+        // more clusters than documents
+        assert_eq!(
+            kmeans_with(
+                &corpus,
+                &KMeansOptions {
+                    k: 10,
+                    ..Default::default()
+                }
+            )
+            .centroids
+            .len(),
+            6
+        );
+    }
+
+    #[test]
+    fn test_hierarchical_clustering() {
+        use crate::wiki_info::cluster::{hierarchical, Linkage};
+        use crate::wiki_info::corpus::Corpus;
+
+        let pages = [
+            page("Volcano", "volcano lava magma eruption"),
+            page("Basalt", "lava magma crater basalt"),
+            page("Emperor", "emperor senate legion forum"),
+            page("Senate", "senate consul legion republic"),
+            page("Rome's Forum", "forum emperor senate"),
+        ];
+
+        let mut corpus = Corpus::new();
+        for page in &pages {
+            corpus.add_page(page);
+        }
+
+        for linkage in [Linkage::Single, Linkage::Complete, Linkage::Average] {
+            let dendrogram = hierarchical(&corpus, linkage);
+            assert_eq!(dendrogram.merges.len(), 4);
+            assert_eq!(dendrogram.merges.last().unwrap().size, 5);
+            assert!(dendrogram
+                .merges
+                .windows(2)
+                .all(|pair| pair[0].distance <= pair[1].distance + 1e-12));
+
+            assert_eq!(dendrogram.cut_into(2), vec![0, 0, 1, 1, 1]);
+            assert_eq!(dendrogram.cut(-1.0), vec![0, 1, 2, 3, 4]);
+            assert_eq!(dendrogram.cut(2.0), vec![0; 5]);
+        }
+
+        let newick = hierarchical(&corpus, Linkage::Average).to_newick();
+        assert!(newick.starts_with('(') && newick.ends_with(");"));
+        assert!(newick.contains("'Rome''s Forum'"));
+
+        assert_eq!(
+            hierarchical(&Corpus::new(), Linkage::Average).to_newick(),
+            "();"
+        );
+    }
+
+    #[test]
+    fn test_section_similarity() {
+        use crate::wiki_info::sections::{best_matching_sections, get_section_similarity};
+        use crate::wiki_info::Block;
+
+        let heading = |text: &str| Block::Heading {
+            level: 2,
+            text: text.to_string(),
+        };
+        let paragraph = |text: &str| Block::Paragraph(text.to_string());
+        let page = |blocks: Vec<Block>| Page {
+            blocks,
+            ..Default::default()
+        };
+
+        let paris = page(vec![
+            paragraph("Paris is the capital of France"),
+            heading("History"),
+            paragraph("Romans founded the settlement, later kings built walls"),
+            Block::Heading {
+                level: 3,
+                text: "Middle Ages".to_string(),
+            },
+            paragraph("Medieval kings ruled the city"),
+            heading("Economy"),
+            paragraph("Finance tourism fashion industry"),
+        ]);
+        let rome = page(vec![
+            heading("Economy"),
+            paragraph("Tourism finance services industry"),
+            heading("History"),
+            paragraph("Romans founded an empire, kings then emperors ruled"),
+        ]);
+
+        let sections = paris.sections();
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections[0].title, None);
+        assert!(sections[1].text.contains("Medieval kings"));
+        assert_eq!(
+            paris.section("history").unwrap().title.as_deref(),
+            Some("History")
+        );
+
+        let history = get_section_similarity(&paris, "History", &rome, "history").unwrap();
+        let cross = get_section_similarity(&paris, "History", &rome, "Economy").unwrap();
+        assert!(history > cross);
+        assert_eq!(
+            get_section_similarity(&paris, "Climate", &rome, "History"),
+            None
+        );
+
+        let matches = best_matching_sections(&paris, &rome);
+        assert!(matches.contains(&(1, 1, history)));
+        assert!(matches.iter().any(|&(a, b, _)| (a, b) == (2, 0)));
+        assert!(matches.windows(2).all(|pair| pair[0].2 >= pair[1].2));
+
+        // unstructured pages are a single section
+        let plain = Page {
+            content: "Romans founded".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(plain.sections().len(), 1);
+    }
+
+    #[test]
+    fn test_graph_simrank() {
+        use crate::wiki_info::graph::WikiGraph;
+
+        let page = |title: &str, links: &[&str]| Page {
+            title: title.to_string(),
+            links: links
+                .iter()
+                .map(|link| {
+                    crate::wiki_info::HyperLink::from_href(
+                        link.to_string(),
+                        &format!("/wiki/{}", link),
+                    )
+                })
+                .collect(),
+            ..Default::default()
+        };
+        // Lava and Magma are both linked from Volcano and Basalt, Senate only from Rome
+        let graph = WikiGraph::from_pages(&[
+            page("Volcano", &["Lava", "Magma"]),
+            page("Basalt", &["Lava", "Magma"]),
+            page("Rome", &["Senate", "Lava"]),
+        ]);
+        let id = |title: &str| graph.node_id(title).unwrap();
+
+        let scores = graph.simrank(0.8, 10);
+        assert_eq!(scores.rows(), graph.node_count());
+        assert_eq!(scores[(id("Lava"), id("Lava"))], 1.0);
+        assert_eq!(
+            scores[(id("Lava"), id("Magma"))],
+            scores[(id("Magma"), id("Lava"))]
+        );
+        assert!(scores[(id("Lava"), id("Magma"))] > scores[(id("Magma"), id("Senate"))]);
+        // no common linking page, only linked from pages that are not similar
+        assert_eq!(scores[(id("Magma"), id("Senate"))], 0.0);
+        assert!(scores[(id("Lava"), id("Senate"))] > 0.0);
+        // no inlinks at all
+        assert_eq!(scores[(id("Volcano"), id("Basalt"))], 0.0);
+
+        let first = graph.simrank(0.8, 1);
+        assert!((first[(id("Lava"), id("Magma"))] - 0.8 * 2.0 / 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_similarity_metrics() {
+        use crate::wiki_info::metric::{
+            get_most_similar_page_by, get_page_similarity_by, get_top_k_similar_by,
+            vectorize_pages, Bm25, Cosine, Euclidean, Jaccard, PageVector, SimilarityMetric,
+        };
+        use crate::wiki_info::{get_page_similarity, VectorOptions};
+
+        let primary = page("", "volcano lava");
+        let pages = [
+            page("", "city emperor senate"),
+            page("", "volcano lava lava eruption magma crater"),
+            page("", "volcano lava"),
+        ];
+        let options = VectorOptions::default();
+
+        let cosine = get_page_similarity_by(&primary, &pages[1], &Cosine, &options);
+        assert!((cosine - get_page_similarity(&primary, &pages[1])).abs() < 1e-9);
+        assert!(
+            (get_page_similarity_by(&primary, &pages[1], &Jaccard, &options) - 0.4).abs() < 1e-9
+        );
+        assert_eq!(
+            get_page_similarity_by(&primary, &pages[2], &Euclidean, &options),
+            1.0
+        );
+
+        for metric in [
+            &Cosine as &dyn SimilarityMetric,
+            &Jaccard,
+            &Euclidean,
+            &Bm25::default(),
+        ] {
+            assert_eq!(
+                get_most_similar_page_by(&primary, &pages, metric, &options),
+                2
+            );
+            let top = get_top_k_similar_by(&primary, &pages, 3, metric, &options);
+            assert_eq!(
+                top.iter().map(|&(i, _)| i).collect::<Vec<_>>(),
+                vec![2, 1, 0]
+            );
+        }
+
+        let vectors = vectorize_pages(&[&primary, &pages[0]], &options);
+        assert_eq!(vectors[0].collection.documents, 2);
+        assert_eq!(vectors[0].term_frequencies().iter().sum::<f64>(), 1.0);
+
+        // any metric can be plugged in
+        struct SharedTerms;
+        impl SimilarityMetric for SharedTerms {
+            fn score(&self, a: &PageVector, b: &PageVector) -> f64 {
+                a.counts
+                    .iter()
+                    .zip(&b.counts)
+                    .filter(|(x, y)| **x > 0.0 && **y > 0.0)
+                    .count() as f64
+            }
+        }
+        assert_eq!(
+            get_page_similarity_by(&primary, &pages[1], &SharedTerms, &options),
+            2.0
+        );
+    }
+
+    #[test]
+    fn test_find_most_similar_page() {
+        use crate::wiki_info::{find_most_similar_page, WikiError};
+
+        let primary = page("", "volcano lava eruption");
+        let pages = [
+            page("", "city emperor senate"),
+            page("", "volcano lava magma"),
+            Page::default(),
+        ];
+
+        let result = find_most_similar_page(&primary, &pages).unwrap();
+        assert_eq!(result.index, 1);
+        assert!(result.score > 0.0 && result.score < 1.0);
+        assert_eq!(result.ranking.len(), 3);
+        assert_eq!(result.ranking[0], (1, result.score));
+        assert_eq!(&result.ranking[1..], &[(0, 0.0), (2, 0.0)]);
+
+        // nothing is similar, but it can still be told apart from an error
+        let unrelated = find_most_similar_page(&primary, &pages[..1]).unwrap();
+        assert_eq!((unrelated.index, unrelated.score), (0, 0.0));
+
+        assert!(matches!(
+            find_most_similar_page(&primary, &[]),
+            Err(WikiError::InputError(_))
+        ));
+        assert!(matches!(
+            find_most_similar_page(&Page::default(), &pages),
+            Err(WikiError::InputError(_))
+        ));
+    }
+
+    #[test]
+    fn test_vocabulary_stable_ids() {
+        use crate::wiki_info::vocabulary::Vocabulary;
+        use crate::wiki_info::{cosine_sim, get_page_similarity, VectorOptions};
+
+        let options = VectorOptions::default();
+        let volcano = page("", "volcano lava eruption");
+        let basalt = page("", "lava basalt");
+
+        let mut vocabulary = Vocabulary::from_pages([&volcano], &options);
+        assert_eq!(vocabulary.len(), 3);
+        assert_eq!(vocabulary.id("volcano"), Some(0));
+        assert_eq!(vocabulary.term(1), Some("lava"));
+        let early = vocabulary.vectorize(&volcano, &options);
+
+        vocabulary.extend([&basalt], &options);
+        assert_eq!(vocabulary.len(), 4);
+        assert_eq!(vocabulary.id("volcano"), Some(0));
+        let late = vocabulary.vectorize(&basalt, &options);
+
+        // vectors from before the vocabulary grew still compare correctly
+        let sim = cosine_sim(&early, &late);
+        assert!((sim - get_page_similarity(&volcano, &basalt)).abs() < 1e-9);
+
+        vocabulary.freeze();
+        assert_eq!(vocabulary.add_term("magma"), None);
+        assert_eq!(vocabulary.add_term("lava"), Some(1));
+        vocabulary.extend([&page("", "magma crater")], &options);
+        assert_eq!(vocabulary.len(), 4);
+        vocabulary.unfreeze();
+        assert_eq!(vocabulary.add_term("magma"), Some(4));
+    }
+
+    #[test]
+    fn test_vector_cache() {
+        use crate::wiki_info::vector_cache::VectorCache;
+        use crate::wiki_info::vocabulary::Vocabulary;
+        use crate::wiki_info::{get_top_k_similar_with, VectorOptions};
+
+        let options = VectorOptions::default();
+        let query = page("Volcano", "volcano lava eruption magma");
+        let pool = vec![
+            page("Lava", "lava flows from a volcano eruption"),
+            page("Senate", "the senate passed a law"),
+            page("Magma", "magma chamber below the volcano"),
+        ];
+
+        let mut vocabulary = Vocabulary::from_pages(std::iter::once(&query).chain(&pool), &options);
+        vocabulary.freeze();
+
+        let mut cache = VectorCache::new(options.clone());
+        let scores = cache.similarities(&query, &pool, &vocabulary);
+        assert_eq!((cache.hits(), cache.misses()), (0, 4));
+
+        let expected = get_top_k_similar_with(&query, &pool, pool.len(), &options);
+        for (index, sim) in expected {
+            assert!((scores[index] - sim).abs() < 1e-9);
+        }
+
+        // the second query is served entirely from the cache
+        assert_eq!(cache.similarities(&query, &pool, &vocabulary), scores);
+        assert_eq!((cache.hits(), cache.misses()), (4, 4));
+
+        // an edited page is vectorized again
+        let edited = page("Senate", "the senate debated the volcano");
+        cache.vector(&edited, &vocabulary);
+        assert_eq!(cache.misses(), 5);
+
+        // a grown vocabulary is a new version
+        let old_version = vocabulary.version();
+        vocabulary.unfreeze();
+        vocabulary.add_term("basalt");
+        assert_ne!(vocabulary.version(), old_version);
+        assert_eq!(cache.vector(&query, &vocabulary).len(), vocabulary.len());
+        cache.retain_version(vocabulary.version());
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_f32_vectors() {
+        use crate::wiki_info::corpus::Corpus;
+        use crate::wiki_info::{
+            cosine_sim, cosine_sim_f32, get_page_similarity, page_to_vec_f32,
+            vocabulary::Vocabulary, VectorOptions,
+        };
+
+        let volcano = page("", "volcano lava eruption magma lava");
+        let basalt = page("", "lava basalt magma");
+
+        let vocabulary = Vocabulary::from_pages([&volcano, &basalt], &VectorOptions::default());
+        let a = page_to_vec_f32(&volcano, vocabulary.as_map());
+        let b = page_to_vec_f32(&basalt, vocabulary.as_map());
+        let sim = cosine_sim_f32(&a, &b);
+        assert!((sim - get_page_similarity(&volcano, &basalt)).abs() < 1e-6);
+        assert_eq!(cosine_sim_f32(&a, &vec![0.0; a.len()]), 0.0);
+
+        let mut corpus = Corpus::new();
+        corpus.add_page(&volcano);
+        corpus.add_page(&basalt);
+        let sim = cosine_sim_f32(
+            &corpus.vectorize_f32(&volcano),
+            &corpus.vectorize_f32(&basalt),
+        );
+        let expected = cosine_sim(&corpus.vectorize(&volcano), &corpus.vectorize(&basalt));
+        assert!((sim - expected).abs() < 1e-6);
+        assert_eq!(corpus.tf_idf_row_f32(0).len(), corpus.tf_idf_row(0).len());
+    }
+
+    #[test]
+    fn test_fielded_similarity() {
+        use crate::wiki_info::fields::{get_fielded_similarity, FieldWeights};
+        use crate::wiki_info::get_page_similarity;
+
+        let with_summary = |title: &str, summary: &str, content: &str| Page {
+            summary: summary.to_string(),
+            ..page(title, content)
+        };
+        let body = "city river bridge market harbor cathedral museum";
+        let query = with_summary("Volcano", "", "volcano");
+        let titled = with_summary("Volcano", "a town", body);
+        let untitled = with_summary("Harbor", "a town", &format!("{} volcano", body));
+
+        // on content alone the page mentioning the volcano wins
+        assert!(get_page_similarity(&query, &untitled) > get_page_similarity(&query, &titled));
+
+        let weights = FieldWeights::default();
+        assert!(
+            get_fielded_similarity(&query, &titled, &weights)
+                > get_fielded_similarity(&query, &untitled, &weights)
+        );
+
+        // body only weights ignore the title
+        let body_only = FieldWeights {
+            title: 0.0,
+            lead: 0.0,
+            body: 1.0,
+        };
+        let sim = get_fielded_similarity(&query, &untitled, &body_only);
+        assert!((sim - get_page_similarity(&query, &untitled)).abs() < 1e-9);
+        assert_eq!(
+            get_fielded_similarity(&query, &with_summary("", "", ""), &weights),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_title_matching() {
+        use crate::wiki_info::titles::{closest_title, jaro_winkler, title_distance};
+
+        assert_eq!(title_distance("Besançon", "besancon"), 0.0);
+        assert_eq!(title_distance("New_York  City", "new york city"), 0.0);
+        assert!((title_distance("kitten", "sitting") - 3.0 / 7.0).abs() < 1e-9);
+        assert_eq!(title_distance("", ""), 0.0);
+        assert_eq!(title_distance("abc", ""), 1.0);
+
+        assert!((jaro_winkler("MARTHA", "MARHTA") - 0.9611).abs() < 1e-4);
+        assert_eq!(jaro_winkler("Paris", "paris"), 1.0);
+        assert_eq!(jaro_winkler("abc", "xyz"), 0.0);
+
+        let titles = vec![
+            "Albert Einstein".to_string(),
+            "Albert Camus".to_string(),
+            "Isaac Newton".to_string(),
+        ];
+        assert_eq!(closest_title("albert einstien", &titles).unwrap().0, 0);
+        assert_eq!(closest_title("isaac newton", &titles), Some((2, 0.0)));
+        assert_eq!(closest_title("anything", &[]), None);
+    }
+
+    #[test]
+    fn test_query_similarity() {
+        use crate::wiki_info::{query_similarity, rank_pages_by_query};
+
+        let pages = vec![
+            page("", "the senate passed a budget law"),
+            page("", "lava flows from the volcano during an eruption"),
+            page("", "a volcano is a rupture in the crust"),
+        ];
+
+        assert!(query_similarity("volcano eruption", &pages[1]) > 0.0);
+        assert_eq!(query_similarity("volcano eruption", &pages[0]), 0.0);
+        // nothing is left of a query of stop words
+        assert_eq!(query_similarity("the of a", &pages[1]), 0.0);
+
+        let ranking = rank_pages_by_query("Volcano eruption of lava", &pages);
+        assert_eq!(ranking.len(), pages.len());
+        assert_eq!(ranking[0].0, 1);
+        assert_eq!(ranking[2], (0, 0.0));
+    }
+
+    #[test]
+    fn test_inverted_index() {
+        use crate::wiki_info::bm25::{bm25_score, CorpusStats};
+        use crate::wiki_info::corpus::Corpus;
+        use crate::wiki_info::index::{IndexScoring, InvertedIndex};
+
+        let pages = vec![
+            page("Senate", "the senate passed a budget law"),
+            page(
+                "Lava",
+                "lava flows from the volcano during an eruption, lava cools",
+            ),
+            page("Volcano", "a volcano is a rupture in the crust"),
+        ];
+
+        let mut corpus = Corpus::new();
+        for page in &pages {
+            corpus.add_page(page);
+        }
+        let index = InvertedIndex::build(&corpus);
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.title(1), Some("Lava"));
+        assert_eq!(index.postings("lava").len(), 1);
+        assert_eq!(index.postings("lava")[0].count, 2);
+        assert!(index.postings("unknown").is_empty());
+
+        // the same scores as scoring every page with bm25
+        let results = index.search("Volcano lava");
+        assert_eq!(results.iter().map(|r| r.0).collect::<Vec<_>>(), vec![1, 2]);
+        let stats = CorpusStats::from_corpus(&corpus);
+        for (doc, score) in results {
+            let expected = bm25_score(&["volcano", "lava"], &pages[doc], &stats);
+            assert!((score - expected).abs() < 1e-9);
+        }
+
+        let index = index.with_scoring(IndexScoring::TfIdf);
+        assert_eq!(index.search("senate budget")[0].0, 0);
+        assert!(index.search("the of").is_empty());
+    }
+
+    #[test]
+    fn test_boolean_query() {
+        use crate::wiki_info::corpus::Corpus;
+        use crate::wiki_info::index::InvertedIndex;
+        use crate::wiki_info::query::BooleanQuery;
+
+        let term = |t: &str| Box::new(BooleanQuery::Term(t.to_string()));
+        assert_eq!(
+            BooleanQuery::parse("rust AND (compiler OR borrow) NOT game").unwrap(),
+            BooleanQuery::And(
+                Box::new(BooleanQuery::And(
+                    term("rust"),
+                    Box::new(BooleanQuery::Or(term("compiler"), term("borrow")))
+                )),
+                Box::new(BooleanQuery::Not(term("game")))
+            )
+        );
+        assert!(BooleanQuery::parse("").is_err());
+        assert!(BooleanQuery::parse("(rust OR go").is_err());
+        assert!(BooleanQuery::parse("rust AND").is_err());
+        assert!(BooleanQuery::parse("rust)").is_err());
+
+        let mut corpus = Corpus::new();
+        for content in [
+            "rust compiler internals",
+            "the rust borrow checker",
+            "rust the survival game with a compiler",
+            "go compiler",
+        ] {
+            corpus.add_page(&page("", content));
+        }
+        let index = InvertedIndex::build(&corpus);
+
+        let search = |query: &str| index.search_boolean(query).unwrap();
+        assert_eq!(search("rust AND (compiler OR borrow) NOT game"), vec![0, 1]);
+        assert_eq!(search("compiler"), vec![0, 2, 3]);
+        assert_eq!(search("compiler NOT rust"), vec![3]);
+        assert_eq!(search("borrow OR go"), vec![1, 3]);
+        assert_eq!(search("NOT compiler"), vec![1]);
+        // stop words do not constrain the match
+        assert_eq!(search("rust AND the"), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_phrase_queries() {
+        use crate::wiki_info::corpus::Corpus;
+        use crate::wiki_info::index::InvertedIndex;
+
+        let mut corpus = Corpus::new();
+        for content in [
+            "the prime minister of france met the president",
+            "france has a minister for every prime number",
+            "prime minister of france, later prime minister of france again",
+        ] {
+            corpus.add_page(&page("", content));
+        }
+        let index = InvertedIndex::build(&corpus);
+        assert_eq!(index.postings("prime")[2].positions, vec![0, 4]);
+
+        assert_eq!(
+            index.phrase_matches("Prime Minister of France"),
+            vec![(0, 1), (2, 2)]
+        );
+        assert!(index.phrase_matches("france prime").is_empty());
+        assert!(index.phrase_matches("of the").is_empty());
+
+        // bag of words matches all three pages, the phrase only two
+        assert_eq!(index.search("prime minister france").len(), 3);
+        let results = index.search("\"prime minister of france\" president");
+        assert_eq!(results.iter().map(|r| r.0).collect::<Vec<_>>(), vec![0, 2]);
+
+        assert_eq!(
+            index
+                .search_boolean("\"prime minister\" NOT president")
+                .unwrap(),
+            vec![2]
+        );
+        assert!(index.search_boolean("\"prime minister").is_err());
+    }
+
+    #[test]
+    fn test_fielded_search() {
+        use crate::wiki_info::index::{FieldBoosts, FieldedIndex, SearchField};
+        use crate::wiki_info::{Block, VectorOptions};
+
+        let with_heading = |title: &str, heading: &str, content: &str| Page {
+            blocks: vec![Block::Heading {
+                level: 2,
+                text: heading.to_string(),
+            }],
+            ..page(title, content)
+        };
+        let pages = vec![
+            with_heading(
+                "Rust",
+                "Ownership",
+                "a systems language with a borrow checker",
+            ),
+            with_heading(
+                "Iron",
+                "Corrosion",
+                "iron oxide, known as rust, forms on iron",
+            ),
+            with_heading(
+                "Borrowing",
+                "History",
+                "ownership and borrowing in rust code",
+            ),
+        ];
+        let index = FieldedIndex::build(&pages, &VectorOptions::default());
+        assert_eq!(index.len(), 3);
+        assert_eq!(SearchField::from_name("Title"), Some(SearchField::Title));
+
+        let docs = |results: Vec<(usize, f64)>| results.iter().map(|r| r.0).collect::<Vec<_>>();
+        assert_eq!(docs(index.search("title:rust")), vec![0]);
+        assert_eq!(docs(index.search("headings:ownership")), vec![0]);
+        assert_eq!(docs(index.search("body:rust")), vec![2, 1]);
+        assert_eq!(docs(index.search("title:rust body:ownership")), vec![0, 2]);
+        assert_eq!(docs(index.search("body:\"borrow checker\"")), vec![0]);
+
+        // the title boost puts the page titled rust first
+        assert_eq!(docs(index.search("rust"))[0], 0);
+        let index = index.with_boosts(FieldBoosts {
+            title: 0.0,
+            headings: 0.0,
+            body: 1.0,
+        });
+        assert_eq!(index.search("rust")[0].1, index.search("body:rust")[0].1);
+    }
+
+    #[test]
+    fn test_alias_expansion() {
+        use crate::wiki_info::aliases::AliasTable;
+        use crate::wiki_info::HyperLink;
+
+        let link = |anchor: &str, href: &str| HyperLink::from_href(anchor.to_string(), href);
+        let pages = vec![
+            Page {
+                links: vec![
+                    link("USA", "/wiki/United_States"),
+                    link("UK", "/wiki/United_Kingdom"),
+                ],
+                ..Default::default()
+            },
+            Page {
+                links: vec![link("USA", "/wiki/United_States"), link("U.K.", "/wiki/UK")],
+                ..Default::default()
+            },
+        ];
+
+        let mut aliases = AliasTable::from_pages(&pages);
+        assert_eq!(aliases.resolve("usa"), Some("United States"));
+        assert_eq!(aliases.resolve("United States"), None);
+        assert_eq!(aliases.expand("USA economy"), "USA economy United States");
+
+        // explicit aliases win, and longer aliases win over the words inside them
+        aliases.add_alias("Big Apple", "New York City");
+        aliases.add_alias("apple", "Apple Inc.");
+        assert_eq!(
+            aliases.expand("the big apple"),
+            "the big apple New York City"
+        );
+        aliases.add_alias("UK", "Britain");
+        assert_eq!(aliases.resolve("uk"), Some("Britain"));
+
+        let united = Page {
+            content: "the united states is a country in north america".to_string(),
+            ..Default::default()
+        };
+        let options = crate::wiki_info::VectorOptions::default();
+        assert_eq!(crate::wiki_info::query_similarity("USA", &united), 0.0);
+        assert!(aliases.query_similarity("USA", &united, &options) > 0.0);
+    }
+
+    #[test]
+    fn test_rerank_mmr() {
+        use crate::wiki_info::{get_top_k_similar, rerank_mmr};
+
+        let query = page("", "volcano eruption lava magma crater");
+        let pages = vec![
+            page("", "volcano eruption lava flows"),
+            page("", "volcano eruption lava flows again"),
+            page("", "magma crater formation"),
+            page("", "senate budget law"),
+        ];
+
+        let results = get_top_k_similar(&query, &pages, 3);
+        assert_eq!(
+            results.iter().map(|r| r.0).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+
+        // the near duplicate drops below the page about something else
+        let reranked = rerank_mmr(&results, &pages, 0.5);
+        assert_eq!(
+            reranked.iter().map(|r| r.0).collect::<Vec<_>>(),
+            vec![0, 2, 1]
+        );
+        assert_eq!(reranked[1], results[2]);
+
+        // all relevance keeps the order
+        assert_eq!(rerank_mmr(&results, &pages, 1.0), results);
+        assert!(rerank_mmr(&[], &pages, 0.5).is_empty());
+    }
+
+    #[test]
+    fn test_explain_similarity() {
+        use crate::wiki_info::{explain_similarity, get_page_similarity};
+
+        let a = page("", "volcano volcano lava eruption senate");
+        let b = page("", "volcano lava lava crater");
+
+        let all = explain_similarity(&a, &b, 10);
+        assert_eq!(
+            all.iter().map(|c| c.term.as_str()).collect::<Vec<_>>(),
+            vec!["lava", "volcano"]
+        );
+        assert!((all[1].weight_a - 0.4).abs() < 1e-9);
+        assert!((all[1].weight_b - 0.25).abs() < 1e-9);
+
+        // the contributions add up to the cosine
+        let total: f64 = all.iter().map(|c| c.contribution).sum();
+        assert!((total - get_page_similarity(&a, &b)).abs() < 1e-9);
+
+        assert_eq!(explain_similarity(&a, &b, 1).len(), 1);
+        assert!(explain_similarity(&a, &page("", ""), 5).is_empty());
+    }
+
+    #[test]
+    fn test_similarity_significance() {
+        use crate::wiki_info::significance::similarity_significance;
+
+        let words = |prefix: &str, n: usize| {
+            (0..n)
+                .map(|i| {
+                    format!(
+                        "{}{}{}",
+                        prefix,
+                        (b'a' + (i / 26) as u8) as char,
+                        (b'a' + (i % 26) as u8) as char
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        // articles repeating the same few key terms among many terms of their own
+        let repeat = |word: &str, n: usize| vec![word; n].join(" ");
+        let a = page(
+            "",
+            &format!(
+                "{} {} {}",
+                repeat("volcano", 20),
+                repeat("lava", 15),
+                words("alpha", 40)
+            ),
+        );
+        let b = page(
+            "",
+            &format!(
+                "{} {} {}",
+                repeat("volcano", 18),
+                repeat("lava", 12),
+                words("beta", 40)
+            ),
+        );
+        let significance = similarity_significance(&a, &b, 200).unwrap();
+        assert!(significance.similarity > 0.7);
+        assert!(significance.z_score > 3.0);
+        assert!(significance.p_value < 0.01);
+        assert!(significance.null_mean < significance.similarity);
+
+        // the same result on every run
+        assert_eq!(similarity_significance(&a, &b, 200).unwrap(), significance);
+
+        // unrelated pages are not significant
+        let c = page("", &words("gamma", 40));
+        let unrelated = similarity_significance(&a, &c, 50).unwrap();
+        assert_eq!(unrelated.similarity, 0.0);
+        assert!(unrelated.p_value > 0.5);
+
+        assert!(similarity_significance(&a, &b, 0).is_err());
+    }
+
+    #[test]
+    fn test_divergence_metrics() {
+        use crate::wiki_info::metric::{
+            get_page_similarity_by, get_top_k_similar_by, DirichletKl, JensenShannon,
+        };
+        use crate::wiki_info::VectorOptions;
+
+        let options = VectorOptions::default();
+        let a = page("", "volcano lava eruption magma");
+        let same = page("", "magma eruption lava volcano");
+        let unrelated = page("", "senate budget law");
+
+        let js = |x: &Page, y: &Page| get_page_similarity_by(x, y, &JensenShannon, &options);
+        assert!((js(&a, &same) - 1.0).abs() < 1e-9);
+        assert!(js(&a, &unrelated) < 1e-9);
+        assert_eq!(js(&a, &page("", "")), 0.0);
+        let partial = page("", "volcano lava crater");
+        assert!(js(&a, &partial) > 0.0 && js(&a, &partial) < 1.0);
+        assert!((js(&a, &partial) - js(&partial, &a)).abs() < 1e-12);
+
+        // a long page on the topic beats a short unrelated one
+        let long = page(
+            "",
+            &format!(
+                "volcano lava eruption magma crater ash {}",
+                "rock ".repeat(10)
+            ),
+        );
+        let candidates = vec![unrelated.clone(), long, same.clone()];
+        let ranking = get_top_k_similar_by(&a, &candidates, 3, &DirichletKl { mu: 1.0 }, &options);
+        assert_eq!(
+            ranking.iter().map(|r| r.0).collect::<Vec<_>>(),
+            vec![2, 1, 0]
+        );
+        assert!(ranking.iter().all(|r| r.1 > 0.0 && r.1 <= 1.0));
+    }
+
+    #[test]
+    fn test_rocchio() {
+        use crate::wiki_info::corpus::Corpus;
+        use crate::wiki_info::cosine_sim;
+
+        let mut corpus = Corpus::new();
+        for content in [
+            "volcano lava eruption",
+            "volcano magma crater",
+            "volcano tourism hotel",
+        ] {
+            corpus.add_page(&page("", content));
+        }
+
+        let query = corpus.vectorize(&page("", "volcano"));
+        let refined = corpus
+            .rocchio(&query, &[0, 1], &[2], 1.0, 0.75, 0.5)
+            .unwrap();
+        assert_eq!(refined.len(), corpus.terms().len());
+
+        let weight = |vector: &[f64], term: &str| vector[corpus.term_id(term).unwrap()];
+        assert!(weight(&refined, "lava") > 0.0);
+        assert!(weight(&refined, "magma") > 0.0);
+        // pushed below zero and clipped
+        assert_eq!(weight(&refined, "hotel"), 0.0);
+
+        let score =
+            |vector: &[f64], doc: &str| cosine_sim(vector, &corpus.vectorize(&page("", doc)));
+        assert!(score(&refined, "lava eruption") > score(&query, "lava eruption"));
+
+        // no feedback only scales the query
+        let same = corpus.rocchio(&query, &[], &[], 2.0, 0.75, 0.5).unwrap();
+        assert!(same
+            .iter()
+            .zip(&query)
+            .all(|(a, b)| (a - 2.0 * b).abs() < 1e-12));
+
+        assert!(corpus.rocchio(&query, &[3], &[], 1.0, 0.75, 0.5).is_err());
+    }
+
+    #[test]
+    fn test_wikitext_request_encodes_title() {
+        use crate::wiki_info::wikitext::wikitext_request;
+
+        let client = reqwest::blocking::Client::new();
+        let request = wikitext_request(&client, "AT&T C++ Who?#Bar")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            request.url().as_str(),
+            "https://en.wikipedia.org/w/index.php?title=AT%26T_C%2B%2B_Who%3F%23Bar&action=raw"
+        );
+        let title = request
+            .url()
+            .query_pairs()
+            .find(|(key, _)| key == "title")
+            .unwrap()
+            .1;
+        assert_eq!(title, "AT&T_C++_Who?#Bar");
+    }
+
+    #[test]
+    fn test_red_links_not_fetched() {
+        use crate::wiki_info::graph::WikiGraph;
+        use crate::wiki_info::{fetchable_outlinks, HyperLink};
+
+        let red = HyperLink {
+            exists: false,
+            ..HyperLink::from_href("Missing".to_string(), "/wiki/Missing")
+        };
+        let page = Page {
+            title: "Source".to_string(),
+            links: vec![
+                HyperLink::from_href("Lava".to_string(), "/wiki/Lava"),
+                red,
+                HyperLink::from_href("lava".to_string(), "/wiki/Lava"),
+            ],
+            ..Default::default()
+        };
+
+        let fetched: Vec<&str> = fetchable_outlinks(&page)
+            .iter()
+            .map(|link| link.target.as_str())
+            .collect();
+        assert_eq!(fetched, vec!["Lava"]);
+
+        let graph = WikiGraph::from_pages(&[page]);
+        assert_eq!(graph.edge_count(), 1);
+        assert_eq!(graph.node_id("Missing"), None);
+    }
 }
@@ -1,8 +1,66 @@
-use std::{collections::HashMap, fmt::format};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::format,
+};
 
 use scraper::{Html, Selector};
-
-mod stop_words;
+use serde::{Deserialize, Serialize};
+
+pub mod acronyms;
+pub mod aliases;
+pub mod bm25;
+pub mod categories;
+pub mod classify;
+pub mod cluster;
+pub mod collocations;
+pub mod concordance;
+pub mod corpus;
+pub mod dates;
+pub mod embeddings;
+pub mod entities;
+pub mod export;
+pub mod fields;
+#[cfg(feature = "tantivy")]
+pub mod fulltext;
+pub mod graph;
+pub mod hashing;
+pub mod index;
+pub mod keywords;
+pub mod language;
+#[cfg(feature = "lemmatizer")]
+pub mod lemma;
+pub mod lists;
+pub mod lsa;
+pub mod matrix;
+pub mod meta;
+pub mod metric;
+pub mod minhash;
+#[cfg(feature = "onnx")]
+pub mod onnx;
+pub mod overlap;
+pub mod prelude;
+pub mod query;
+pub mod readability;
+pub mod router;
+pub mod sections;
+pub mod signature;
+pub mod significance;
+pub mod sink;
+pub mod soft_cosine;
+pub mod stats;
+pub mod stem;
+pub mod stop_words;
+pub mod summarize;
+pub mod tables;
+pub mod term_set;
+pub mod text;
+pub mod tiled;
+pub mod titles;
+pub mod tokenize;
+pub mod topics;
+pub mod vector_cache;
+pub mod vocabulary;
+pub mod wikitext;
 
 /// Singleton module for networking clients. 
 /// This is a *blocking* library, should never have race condition on networking side 
@@ -58,6 +116,9 @@ pub enum WikiError {
     NetworkingError(String),
     ParseError(String),
     URLError(String),
+    IOError(String),
+    /// The arguments can not produce a meaningful result, ie nothing to compare against
+    InputError(String),
 }
 
 impl std::error::Error for WikiError {}
@@ -69,6 +130,8 @@ impl std::fmt::Display for WikiError {
             Self::NetworkingError(msg) => write!(f, "Networking error: {}", msg),
             Self::ParseError(msg) => write!(f, "Parse error: {}", msg),
             Self::URLError(msg) => write!(f, "URL error {}", msg),
+            Self::IOError(msg) => write!(f, "IO error: {}", msg),
+            Self::InputError(msg) => write!(f, "Input error: {}", msg),
         }
     }
 }
@@ -83,11 +146,106 @@ impl std::fmt::Display for WikiError {
 /// Ok(Page) - the new wiki page struct
 /// Err(WikiError) - error if wiki parsing/fetching fails
 pub fn page_from_title(title: &str) -> Result<Page, WikiError> {
+    page_from_title_with(title, &ParseOptions::default())
+}
+
+/// Gets a Page from a title &str, parsed with the given options
+///
+/// # Arguments
+/// * `title` - The title of the page
+/// * `options` - How the page content is parsed
+///
+/// # Returns
+///
+/// Ok(Page) - the new wiki page struct
+/// Err(WikiError) - error if wiki parsing/fetching fails
+pub fn page_from_title_with(title: &str, options: &ParseOptions) -> Result<Page, WikiError> {
     debug!("parse_parse_from_title called...");
 
     let url = url_utils::resolve_wiki_url(title)?;
 
-    return page_from_url(&url);
+    page_from_url_with(&url, options)
+}
+
+/// Selectors for the content root of a page, in order of preference.
+/// The first is the current Vector skin, the rest cover legacy Vector,
+/// Minerva (mobile), Timeless and other skins
+pub const CONTENT_SELECTORS: [&str; 5] = [
+    "div.mw-content-container main#content",
+    "main#content",
+    "#content",
+    "#bodyContent",
+    "#mw-content-text",
+];
+
+/// Finds the content root of a fetched document, trying `CONTENT_SELECTORS` in order
+///
+/// # Arguments
+/// * `document` - The whole fetched page
+///
+/// # Returns
+///
+/// Some((content root, the selector that matched)), None if no selector matched
+pub fn select_content(document: &Html) -> Option<(scraper::ElementRef<'_>, &'static str)> {
+    select_content_with(document, &CONTENT_SELECTORS)
+}
+
+/// Selectors for the content root of mobile (Minerva) pages, in order of preference.
+/// The article text sits below the page action menus, so its root comes first
+pub const MOBILE_CONTENT_SELECTORS: [&str; 3] = ["#mw-content-text", "main#content", "#content"];
+
+/// Finds the content root of a fetched document, trying the given selectors in order
+///
+/// # Arguments
+/// * `document` - The whole fetched page
+/// * `selectors` - The selectors to try, ie `MOBILE_CONTENT_SELECTORS`
+///
+/// # Returns
+///
+/// Some((content root, the selector that matched)), None if no selector matched
+pub fn select_content_with<'a>(
+    document: &'a Html,
+    selectors: &[&'static str],
+) -> Option<(scraper::ElementRef<'a>, &'static str)> {
+    selectors.iter().find_map(|&selector| {
+        let parsed = Selector::parse(selector).unwrap();
+
+        document
+            .select(&parsed)
+            .next()
+            .map(|content| (content, selector))
+    })
+}
+
+/// Reads page titles from a reader, one per line, ie titles piped into stdin.
+/// Blank lines are skipped and full wiki urls are turned into their titles
+///
+/// # Arguments
+/// * `reader` - The source of titles, ie `std::io::stdin().lock()`
+///
+/// # Returns
+///
+/// Ok(Vec<String>) - the titles, in input order
+/// Err(WikiError::IOError) - error if reading fails
+pub fn read_titles<R: std::io::BufRead>(reader: R) -> Result<Vec<String>, WikiError> {
+    let mut titles = Vec::new();
+
+    for line in reader.lines() {
+        let line = line.map_err(|err| WikiError::IOError(err.to_string()))?;
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with("http://") || line.starts_with("https://") {
+            titles.push(url_utils::title_from_url(line).trim().to_owned());
+        } else {
+            titles.push(line.to_owned());
+        }
+    }
+
+    Ok(titles)
 }
 
 /// Gets a Page from a url
@@ -101,7 +259,29 @@ pub fn page_from_title(title: &str) -> Result<Page, WikiError> {
 /// Ok(Page) - the new wiki page struct
 /// Err(WikiError) - error if wiki parsing/fetching fails
 pub fn page_from_url(url: &str) -> Result<Page, WikiError> {
+    page_from_url_with(url, &ParseOptions::default())
+}
+
+/// Gets a Page from a url, parsed with the given options
+///
+/// # Arguments
+///
+/// * `url` the url of the wiki page
+/// * `options` - How the page content is parsed
+///
+/// # Returns
+///
+/// Ok(Page) - the new wiki page struct
+/// Err(WikiError) - error if wiki parsing/fetching fails
+pub fn page_from_url_with(url: &str, options: &ParseOptions) -> Result<Page, WikiError> {
     debug!("parse_page_from_url called with url: {}", url);
+    // section links fetch the whole page
+    let (url, _) = url_utils::split_fragment(url);
+    let selectors: &[&str] = if url_utils::is_mobile_url(url) {
+        &MOBILE_CONTENT_SELECTORS
+    } else {
+        &CONTENT_SELECTORS
+    };
     let client = client::get_client();
 
     debug!("Sending request to URL: {}", url);
@@ -123,22 +303,34 @@ pub fn page_from_url(url: &str) -> Result<Page, WikiError> {
     debug!("Parsing HTML content...");
     let document = Html::parse_document(&html_content);
 
-    // this wierd selector is what gets the actual body from a page
-    let selector = Selector::parse("div.mw-content-container main#content").unwrap(); // TODO FIX UNWRAP
-
-    match document.select(&selector).next() {
-        Some(content) => {
-            debug!("Content successfully selected. Processing content...");
+    match select_content_with(&document, selectors) {
+        Some((content, content_selector)) => {
+            debug!(
+                "Content selected with {:?}. Processing content...",
+                content_selector
+            );
 
             let title = url_utils::title_from_url(url);
             // process starting at root elem
-            Ok(process_content(content, &title))
+            let mut page = process_content_with(content, &title, options);
+
+            // older skins keep the language menu in a sidebar outside the content
+            if page.interwiki.is_empty() {
+                page.interwiki = interwiki_links(document.root_element());
+            }
+            page.meta = meta::PageMeta {
+                content_selector: Some(content_selector.to_owned()),
+                ..meta::parse_page_meta(&document)
+            };
+
+            Ok(page)
         }
         None => {
             debug!("Failed to select content from document.");
-            Err(WikiError::ParseError(
-                "Failed to select content from document.".to_owned(),
-            ))
+            Err(WikiError::ParseError(format!(
+                "Failed to select content from document, tried {:?}",
+                selectors
+            )))
         }
     }
 }
@@ -150,7 +342,7 @@ pub mod url_utils {
 
     use super::{client::get_client, WikiError};
 
-    /// Extract a title slug from a url &srt
+    /// Extract a title slug from a url &srt, percent decoded like link targets
     ///
     /// # Arguments
     /// * `url` - the url to pull the title from
@@ -158,20 +350,66 @@ pub mod url_utils {
     /// # Returns
     /// owned string for the new title
     pub fn title_from_url(url: &str) -> String {
-        let title = extract_slug(url)
+        let title = percent_encoding::percent_decode_str(extract_slug(url))
+            .decode_utf8_lossy()
             .split("_")
             .fold(String::new(), |a, b| a + b + " ");
 
-        return title;
+        title
     }
 
     // util for title extraction
-    fn extract_slug(url: &str) -> &str {
-        // last elem
-        match url.rsplit('/').next() {
-            Some(slug) => slug,
-            None => "",
+    pub(crate) fn extract_slug(url: &str) -> &str {
+        // last elem, without any section anchor
+        let (url, _) = split_fragment(url);
+        url.rsplit('/').next().unwrap_or_default()
+    }
+
+    /// Splits the section anchor off a url
+    ///
+    /// # Arguments
+    /// * `url` - the url (or slug), ie `https://en.wikipedia.org/wiki/France#History`
+    ///
+    /// # Returns
+    /// (the url without the anchor, the anchor if there was one)
+    pub fn split_fragment(url: &str) -> (&str, Option<&str>) {
+        match url.split_once('#') {
+            Some((url, fragment)) => (url, Some(fragment).filter(|f| !f.is_empty())),
+            None => (url, None),
+        }
+    }
+
+    /// Whether a url points at a mobile site, ie `https://en.m.wikipedia.org/wiki/Paris`
+    ///
+    /// # Arguments
+    /// * `url` - the url to check
+    pub fn is_mobile_url(url: &str) -> bool {
+        host(url).split('.').nth(1) == Some("m")
+    }
+
+    /// Normalizes a url to the canonical desktop url of its page, for caching and dedup.
+    /// Mobile hosts become desktop hosts and any section anchor is dropped
+    ///
+    /// # Arguments
+    /// * `url` - the url, ie `https://en.m.wikipedia.org/wiki/Paris#History`
+    ///
+    /// # Returns
+    /// owned canonical url, ie `https://en.wikipedia.org/wiki/Paris`
+    pub fn canonical_url(url: &str) -> String {
+        let (url, _) = split_fragment(url);
+        if !is_mobile_url(url) {
+            return url.to_owned();
         }
+
+        let host = host(url);
+        let desktop_host = host.replacen(".m.", ".", 1);
+        url.replacen(host, &desktop_host, 1)
+    }
+
+    // host part of a url, the whole input if it has no scheme
+    fn host(url: &str) -> &str {
+        let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+        rest.split(['/', '?', '#']).next().unwrap_or_default()
     }
 
     /// Resolves a wiki title to its full url
@@ -196,9 +434,10 @@ pub mod url_utils {
                 if response.status().is_success() {
                     Ok(url)
                 } else {
-                    Err(WikiError::NetworkingError(
-                        format!("URL returned status: {}", response.status()).into(),
-                    ))
+                    Err(WikiError::NetworkingError(format!(
+                        "URL returned status: {}",
+                        response.status()
+                    )))
                 }
             }
             Err(e) => Err(WikiError::NetworkingError(
@@ -208,6 +447,121 @@ pub mod url_utils {
     }
 }
 
+/// Fetches only the lead extract of a page from the REST summary endpoint.
+/// The returned page has no links, its content is the plain text lead
+fn summary_from_url(url: &str) -> Result<Page, WikiError> {
+    debug!("summary_from_url called with url: {}", url);
+    let client = client::get_client();
+
+    let slug = url_utils::extract_slug(url);
+    let summary_url = "https://en.wikipedia.org/api/rest_v1/page/summary/".to_owned() + slug;
+
+    let response = client.get(&summary_url).send().map_err(|err| {
+        WikiError::NetworkingError(format!("Request error with status {:?}", err.status()))
+    })?;
+
+    let body = handle_response(response)?;
+    let json: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|err| WikiError::ParseError(format!("Invalid summary response: {}", err)))?;
+
+    let extract = json["extract"].as_str().ok_or_else(|| {
+        WikiError::ParseError("Summary response did not contain an extract".to_owned())
+    })?;
+
+    let extract = clean_meta_content(extract);
+
+    Ok(Page {
+        title: url_utils::title_from_url(url).trim().to_owned(),
+        links: vec![],
+        content: extract.clone(),
+        summary: extract,
+        ..Default::default()
+    })
+}
+
+/// Gets only the lead section of a page from a title &str.
+/// Much cheaper than fetching and parsing the full page
+///
+/// # Arguments
+/// * `title` - The title of the page
+///
+/// # Returns
+///
+/// Ok(String) - the plain text lead of the page
+/// Err(WikiError) - error if fetching fails
+pub fn page_summary_from_title(title: &str) -> Result<String, WikiError> {
+    debug!("page_summary_from_title called...");
+
+    let url = "https://en.wikipedia.org/wiki/".to_owned() + &title.replace(' ', "_");
+
+    Ok(summary_from_url(&url)?.summary)
+}
+
+/// Fetches the whole text of a page as plain prose from the TextExtracts API.
+/// The returned page has no links, its content is the extract
+fn plain_text_from_url(url: &str) -> Result<Page, WikiError> {
+    debug!("plain_text_from_url called with url: {}", url);
+    let client = client::get_client();
+
+    let response = client
+        .get("https://en.wikipedia.org/w/api.php")
+        .query(&[
+            ("action", "query"),
+            ("prop", "extracts"),
+            ("explaintext", "1"),
+            ("exsectionformat", "plain"),
+            ("redirects", "1"),
+            ("format", "json"),
+            ("formatversion", "2"),
+            ("titles", &url_utils::title_from_url(url)),
+        ])
+        .send()
+        .map_err(|err| {
+            WikiError::NetworkingError(format!("Request error with status {:?}", err.status()))
+        })?;
+
+    let body = handle_response(response)?;
+    let json: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|err| WikiError::ParseError(format!("Invalid extracts response: {}", err)))?;
+
+    let page = &json["query"]["pages"][0];
+    if page["missing"].as_bool().unwrap_or(false) {
+        return Err(WikiError::ParseError(format!(
+            "Page does not exist: {}",
+            url_utils::title_from_url(url).trim()
+        )));
+    }
+
+    let extract = page["extract"].as_str().ok_or_else(|| {
+        WikiError::ParseError("Extracts response did not contain an extract".to_owned())
+    })?;
+
+    Ok(Page {
+        title: page["title"].as_str().unwrap_or_default().to_owned(),
+        content: clean_meta_content(extract),
+        ..Default::default()
+    })
+}
+
+/// Gets the whole text of a page as plain prose, from a title &str.
+/// No HTML is fetched or cleaned, so there are no markup artifacts,
+/// but also no links, tables or other structure
+///
+/// # Arguments
+/// * `title` - The title of the page
+///
+/// # Returns
+///
+/// Ok(String) - the plain text of the page
+/// Err(WikiError) - error if fetching fails or the page does not exist
+pub fn plain_text_from_title(title: &str) -> Result<String, WikiError> {
+    debug!("plain_text_from_title called...");
+
+    let url = "https://en.wikipedia.org/wiki/".to_owned() + &title.replace(' ', "_");
+
+    Ok(plain_text_from_url(&url)?.content)
+}
+
 fn handle_response(response: reqwest::blocking::Response) -> Result<String, WikiError> {
     debug!("Handling response...");
     if response.status().is_success() {
@@ -229,192 +583,1278 @@ fn handle_response(response: reqwest::blocking::Response) -> Result<String, Wiki
 /// From an IR standpoint, this represents a graph node of a semantic network
 /// It's outlinks are the `links` field. This does not contain backlinks, as this
 /// library is built for dynamic traversal
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
 pub struct Page {
     pub title: String,
     pub links: Vec<HyperLink>,
     pub content: String,
+    /// Names of the categories the page belongs to, without the `Category:` prefix
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// Type classes of the page's infobox (ie `"ib-settlement vcard"`), if it has one
+    #[serde(default)]
+    pub infobox_type: Option<String>,
+    /// Every `wikitable` on the page, see `ParseOptions::include_tables`
+    #[serde(default)]
+    pub tables: Vec<tables::Table>,
+    /// Editor curated links from the "See also" section.
+    /// These are also part of `links`
+    #[serde(default)]
+    pub see_also: Vec<HyperLink>,
+    /// Text of the lead paragraphs, everything before the first section heading
+    #[serde(default)]
+    pub summary: String,
+    /// Geographic coordinates of the page subject, from the geo microformat
+    #[serde(default)]
+    pub coordinates: Option<Coordinates>,
+    /// The content as a sequence of blocks, in document order.
+    /// Only populated when parsed with `ParseOptions::structured`
+    #[serde(default)]
+    pub blocks: Vec<Block>,
+    /// The same article in other languages, as (language code, title) pairs
+    #[serde(default)]
+    pub interwiki: Vec<(String, String)>,
+    /// Page id, revision and freshness information.
+    /// Only populated by fetches, it lives outside the content element
+    #[serde(default)]
+    pub meta: meta::PageMeta,
+    /// Number of distinct citations in the page's reference lists
+    #[serde(default)]
+    pub reference_count: usize,
 }
 
-/// A struct representing a hyperlink out of a wiki page, to another.
-/// From an IR standpoint, this represents a graph edge of a semantic network
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct HyperLink {
-    pub title: String,
-    pub outlink: String,
+/// A structural piece of page content
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Block {
+    /// A section heading, `level` is 2 for `h2` through 6 for `h6`
+    Heading {
+        level: u8,
+        text: String,
+    },
+    Paragraph(String),
+    /// A single list item, without the text or links of any nested list.
+    /// `links` are the links in the item into `ParseOptions::namespaces`
+    ListItem {
+        text: String,
+        links: Vec<HyperLink>,
+    },
+    Table(tables::Table),
 }
 
-fn process_content_recursive(
-    element: scraper::ElementRef,
-    raw_content: &mut String,
-    links: &mut Vec<HyperLink>,
-) {
-    for node in element.children() {
-        if let Some(text) = node.value().as_text() {
-            raw_content.push_str(text);
-        } else if let Some(elem) = scraper::ElementRef::wrap(node) {
-            if elem.value().name() == "a" {
-                if let Some(href) = elem.value().attr("href") {
-                    let cur_outline = href.to_string();
-
-                    if !cur_outline.starts_with("/wiki/") {
-                        continue;
-                    }
+/// A latitude/longitude pair in decimal degrees.
+/// Compares by total float ordering so pages stay `Eq` and `Ord`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
 
-                    let link = HyperLink {
-                        title: elem.text().collect::<String>(),
-                        outlink: "https://en.wikipedia.org".to_string() + &cur_outline,
-                    };
-                    links.push(link);
-                }
-            } else {
-                // process children elements
-                process_content_recursive(elem, raw_content, links);
-            }
-        }
+impl From<Coordinates> for (f64, f64) {
+    fn from(coordinates: Coordinates) -> Self {
+        (coordinates.latitude, coordinates.longitude)
     }
 }
 
-///Processes a raw wikipedia fetch into a Page
-///
-/// # Arguments
-///
-/// * `element` - root element of wikipedia DOM
-/// * 'page_title` - title of wikipedia page being processed
-///
-/// # Returns
-///
-/// Page struct representing the given wiki page
-pub fn process_content(element: scraper::ElementRef, page_title: &str) -> Page {
-    debug!("Processing content element...");
-    let mut raw_content = String::new();
-    let mut links = Vec::new();
+impl PartialEq for Coordinates {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
 
-    process_content_recursive(element, &mut raw_content, &mut links);
+impl Eq for Coordinates {}
 
-    // clean meta content is actually not a cheap function,
-    // only wanna call it once here vs inside the recursive one
-    let cleaned_content = clean_meta_content(&raw_content);
+impl PartialOrd for Coordinates {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-    Page {
-        title: page_title.trim().to_owned(),
-        content: cleaned_content,
-        links: links,
+impl Ord for Coordinates {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.latitude
+            .total_cmp(&other.latitude)
+            .then_with(|| self.longitude.total_cmp(&other.longitude))
     }
 }
 
-use regex::Regex;
+impl Page {
+    /// The readable content, as parsed. Same as the `content` field,
+    /// see `vector_cache::CleanCache` for the cleaned form
+    pub fn content_raw(&self) -> &str {
+        &self.content
+    }
 
-/// Cleans the wikipedia meta content from a string
-///
-/// # Arguments
-///
-/// * `input` - Input content to clean
-///
-/// # Returns
-///
-/// String cleaned of wikipedia meta content
-pub fn clean_meta_content(input: &str) -> String {
-    debug!("Cleaning meta content...");
-    let re_whitespace = Regex::new(r"\s+").unwrap();
-    let cleaned_text = re_whitespace.replace_all(input, " ").to_string();
+    /// Gets the text of every `<p>` of the page, in document order.
+    /// Paragraph boundaries are only known for pages parsed with
+    /// `ParseOptions::structured`, any other page is returned as a single paragraph
+    ///
+    /// # Returns
+    ///
+    /// The paragraphs, empty if the page has no content
+    pub fn paragraphs(&self) -> Vec<String> {
+        let paragraphs: Vec<String> = self
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                Block::Paragraph(text) => Some(text.clone()),
+                _ => None,
+            })
+            .collect();
+
+        if !paragraphs.is_empty() || !self.blocks.is_empty() || self.content.trim().is_empty() {
+            return paragraphs;
+        }
+
+        vec![self.content.clone()]
+    }
 
-    let re_css = Regex::new(r"\.mw-.*?\{.*?\}").unwrap();
-    let cleaned_text_no_css = re_css.replace_all(&cleaned_text, "").to_string();
+    /// Cuts the page content down to at most `max_words` words, ending on a
+    /// sentence boundary. A first sentence longer than `max_words` is cut mid sentence
+    ///
+    /// # Arguments
+    ///
+    /// * `max_words` - The max number of whitespace separated words to keep
+    ///
+    /// # Returns
+    ///
+    /// A new, owned page with the truncated content, and whether anything was cut
+    pub fn truncated(&self, max_words: usize) -> (Page, bool) {
+        let mut content = String::new();
+        let mut word_count = 0;
+        let mut was_truncated = false;
+
+        for sentence in text::split_sentences(&self.content) {
+            let sentence_words = sentence.split_whitespace().count();
+
+            if word_count + sentence_words > max_words {
+                // never return an empty page when a single sentence is too long
+                if word_count == 0 {
+                    content = sentence
+                        .split_whitespace()
+                        .take(max_words)
+                        .collect::<Vec<&str>>()
+                        .join(" ");
+                }
+                was_truncated = true;
+                break;
+            }
 
-    let clean_text_no_symbols = cleaned_text_no_css.replace("()", "").replace("[]", "");
+            if !content.is_empty() {
+                content.push(' ');
+            }
+            content.push_str(sentence);
+            word_count += sentence_words;
+        }
 
-    let re_trim = Regex::new(r"^\s+|\s+$").unwrap();
-    let final_text = re_trim.replace_all(&clean_text_no_symbols, "").to_string();
+        if !was_truncated {
+            return (self.clone(), false);
+        }
 
-    debug!("Meta content cleaned.");
-    final_text
+        let page = Page {
+            content,
+            ..self.clone()
+        };
+
+        (page, true)
+    }
 }
 
-/// Removes non-semantic indicators from document
-///
-/// # Arguments
-///
-/// * `page` - page to clean
-///
-/// # Returns
-///
-/// A new, owned clean page with no non-semantic indicators
-pub fn clean_document(page: &Page) -> Page {
-    let stop_words: Vec<String> = STOP_WORDS.to_vec();
+/// A struct representing a hyperlink out of a wiki page, to another.
+/// From an IR standpoint, this represents a graph edge of a semantic network
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct HyperLink {
+    pub title: String,
+    /// Url of the linked page, without any section anchor
+    pub outlink: String,
+    /// Title of the linked page, ie `"France"` for `/wiki/France#History`
+    #[serde(default)]
+    pub target: String,
+    /// Section of the linked page, ie `Some("History")` for `/wiki/France#History`
+    #[serde(default)]
+    pub fragment: Option<String>,
+    /// Namespace of the link target, `Namespace::Article` for regular pages
+    #[serde(default)]
+    pub namespace: Namespace,
+    /// How many times the page links to this target
+    #[serde(default)]
+    pub count: usize,
+    /// Index of the first link to this target among all links on the page,
+    /// before duplicates were collapsed
+    #[serde(default)]
+    pub position: usize,
+    /// Text around the first link to this target.
+    /// Only populated when parsed with `ParseOptions::link_context`
+    #[serde(default)]
+    pub context: Option<String>,
+    /// Whether the target page exists. Red links, to pages nobody has written
+    /// yet, are false and are not worth fetching
+    #[serde(default = "default_exists")]
+    pub exists: bool,
+}
 
-    debug!("Cleaning document...");
-    let mut results: String = String::new();
+fn default_exists() -> bool {
+    true
+}
 
-    page.content
-        .split_whitespace()
-        .map(|word| word.trim())
-        .filter(|word| word.is_ascii())
-        .filter(|word| word.chars().all(|c| c.is_alphabetic()))
-        .map(|word| word.to_ascii_lowercase())
-        .inspect(|word| debug!("current word: {:?}", word))
-        .filter(|word| !stop_words.contains(&word.to_string()))
-        .map(|word| word.to_ascii_lowercase())
-        .for_each(|word| {
-            results.push_str(&word);
-            results.push_str(" ");
-        });
+impl Default for HyperLink {
+    fn default() -> Self {
+        HyperLink {
+            title: String::new(),
+            outlink: String::new(),
+            target: String::new(),
+            fragment: None,
+            namespace: Namespace::Article,
+            count: 0,
+            position: 0,
+            context: None,
+            exists: true,
+        }
+    }
+}
 
-    debug!("Document cleaned.");
+impl HyperLink {
+    /// Builds a link from a wiki href, ie `/wiki/France#History`.
+    /// The count is 1 and the position 0, callers collecting several links set it
+    pub(crate) fn from_href(title: String, href: &str) -> HyperLink {
+        let (path, fragment) = url_utils::split_fragment(href);
+        let slug = path.strip_prefix("/wiki/").unwrap_or(path);
+
+        let decode = |text: &str| {
+            percent_encoding::percent_decode_str(text)
+                .decode_utf8_lossy()
+                .replace('_', " ")
+        };
 
-    Page {
-        title: page.title.clone(),
-        links: page.links.clone(),
-        content: results,
+        HyperLink {
+            title,
+            outlink: "https://en.wikipedia.org".to_string() + path,
+            target: decode(slug),
+            fragment: fragment.map(decode),
+            namespace: Namespace::from_title(slug),
+            count: 1,
+            position: 0,
+            context: None,
+            exists: true,
+        }
     }
 }
 
-/// Convert a Page into its vector representation in a word embeddding vector space
-///
-/// # Arguments
-///
-/// * `page` - The page to convert
-/// * `vocab` - shared vocabulary that you want to use
-///
-/// # Returns
-///
-/// An owned vector of floats containing ONLY the term-frequencies values
-/// This notably does not contain the IDF information
-/// 
-pub fn page_to_vec(page: &Page, vocab: &HashMap<String, usize>) -> Vec<f64> {
-    let content = clean_document(page).content;
-    let words: Vec<&str> = content.split_whitespace().collect();
+/// Gets the `/wiki/` path an anchor element points to, and whether its target exists.
+/// Red links point at the editor (`/w/index.php?title=X&action=edit&redlink=1`),
+/// they are turned back into `/wiki/X`. Returns None for links out of the wiki
+pub(crate) fn wiki_href(anchor: scraper::ElementRef) -> Option<(String, bool)> {
+    let href = anchor.value().attr("href")?;
+
+    if href.starts_with("/wiki/") {
+        let red = anchor
+            .value()
+            .has_class("new", scraper::CaseSensitivity::AsciiCaseInsensitive);
+        return Some((href.to_owned(), !red));
+    }
 
-    let mut word_count = HashMap::new();
-    for &word in &words {
-        *word_count.entry(word.to_string()).or_insert(0) += 1;
+    let (_, query) = href.split_once('?')?;
+    let mut params = query.split('&');
+    if !params.clone().any(|param| param == "redlink=1") {
+        return None;
     }
 
-    let total_words = words.len() as f64;
-    let mut vector = vec![0.0; vocab.len()];
+    let title = params.find_map(|param| param.strip_prefix("title="))?;
+    Some((format!("/wiki/{}", title), false))
+}
 
-    for (word, &count) in &word_count {
-        if let Some(&index) = vocab.get(word) {
-            vector[index] = count as f64 / total_words;
+/// Collapses links to the same target into one, counting occurrences.
+/// The first occurrence's title and position are kept, links stay in first occurrence order
+pub(crate) fn dedup_links(links: Vec<HyperLink>) -> Vec<HyperLink> {
+    let mut deduped: Vec<HyperLink> = Vec::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    for link in links {
+        match seen.get(&link.outlink) {
+            Some(&i) => deduped[i].count += link.count,
+            None => {
+                seen.insert(link.outlink.clone(), deduped.len());
+                deduped.push(link);
+            }
         }
     }
 
-    vector
+    deduped
 }
-use rayon::prelude::*;
-use stop_words::STOP_WORDS;
 
-/// The cosine similarity between two vectors
-///
-/// # Arguments
-///
-/// * `vec1` - The first vector
-/// * `vec2` - The second vector
-///
-/// # Returns
-///
-/// the cosine of the angle between the vectors -> [0-1)
-pub fn cosine_sim(vec1: &Vec<f64>, vec2: &Vec<f64>) -> f64 {
+/// The namespace of a wiki page, from the prefix of its title (`File:`, `Help:`, etc).
+/// Every talk namespace (`Talk:`, `User talk:`, ...) is `Talk`
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Default,
+)]
+pub enum Namespace {
+    #[default]
+    Article,
+    Talk,
+    User,
+    /// `Wikipedia:` project pages
+    Project,
+    File,
+    MediaWiki,
+    Template,
+    Help,
+    Category,
+    Portal,
+    Draft,
+    Module,
+    Special,
+}
+
+impl Namespace {
+    /// Gets the namespace of a page title or url slug, ie `"File:Paris.jpg"`.
+    /// Titles with an unknown prefix, like `"Star Wars: Episode IV"`, are articles
+    pub fn from_title(title: &str) -> Namespace {
+        let Some((prefix, _)) = title.split_once(':') else {
+            return Namespace::Article;
+        };
+
+        let prefix = prefix.trim().replace('_', " ").to_lowercase();
+        if prefix == "talk" || prefix.ends_with(" talk") {
+            return Namespace::Talk;
+        }
+
+        match prefix.as_str() {
+            "user" => Namespace::User,
+            "wikipedia" | "wp" | "project" => Namespace::Project,
+            "file" | "image" | "media" => Namespace::File,
+            "mediawiki" => Namespace::MediaWiki,
+            "template" => Namespace::Template,
+            "help" => Namespace::Help,
+            "category" => Namespace::Category,
+            "portal" => Namespace::Portal,
+            "draft" => Namespace::Draft,
+            "module" => Namespace::Module,
+            "special" => Namespace::Special,
+            _ => Namespace::Article,
+        }
+    }
+}
+
+/// Everything gathered while walking the content DOM
+#[derive(Default)]
+struct ContentCollector {
+    raw_content: String,
+    links: Vec<HyperLink>,
+    categories: Vec<String>,
+    infobox_type: Option<String>,
+    tables: Vec<tables::Table>,
+    see_also: Vec<HyperLink>,
+    raw_summary: String,
+    coordinates: Option<Coordinates>,
+    blocks: Vec<Block>,
+    /// Title of the `h2` section currently being walked, None while in the lead
+    current_section: Option<String>,
+}
+
+/// Options controlling how a page's DOM is turned into a Page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Keep the text of `wikitable`s in `Page.content`.
+    /// Tables are parsed into `Page.tables` either way
+    pub include_tables: bool,
+    /// Elements with any of these classes are skipped entirely, text and links.
+    /// Defaults to the boilerplate blocks repeated across many pages:
+    /// `navbox`, `sidebar`, `hatnote` and `metadata`
+    pub skip_classes: Vec<String>,
+    /// Also record the content as `Page.blocks`, keeping paragraph and
+    /// section boundaries that the flat content string loses
+    pub structured: bool,
+    /// Only links into these namespaces are kept in `Page.links`.
+    /// Defaults to articles only, so link graphs only contain real article edges.
+    /// Categories are recorded in `Page.categories` regardless
+    pub namespaces: Vec<Namespace>,
+    /// Record the text around every link in `HyperLink.context`.
+    /// Off by default, it costs a walk of the enclosing paragraph per link
+    pub link_context: Option<LinkContext>,
+}
+
+/// How much of the text around a link is kept as its context
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkContext {
+    /// The sentence the link is in
+    Sentence,
+    /// Up to this many words on either side of the link
+    Words(usize),
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            include_tables: true,
+            skip_classes: ["navbox", "sidebar", "hatnote", "metadata"]
+                .iter()
+                .map(|class| class.to_string())
+                .collect(),
+            structured: false,
+            namespaces: vec![Namespace::Article],
+            link_context: None,
+        }
+    }
+}
+
+fn process_content_recursive(
+    element: scraper::ElementRef,
+    options: &ParseOptions,
+    collector: &mut ContentCollector,
+) {
+    for node in element.children() {
+        if let Some(text) = node.value().as_text() {
+            collector.raw_content.push_str(text);
+        } else if let Some(elem) = scraper::ElementRef::wrap(node) {
+            if elem.value().name() == "a" {
+                if let Some((cur_outline, exists)) = wiki_href(elem) {
+                    let mut link = HyperLink {
+                        position: collector.links.len(),
+                        exists,
+                        ..HyperLink::from_href(
+                            text::normalize_text(&elem.text().collect::<String>()),
+                            &cur_outline,
+                        )
+                    };
+                    if let Some(category) = link.target.strip_prefix("Category:") {
+                        collector.categories.push(category.to_string());
+                    }
+                    if !options.namespaces.contains(&link.namespace) {
+                        continue;
+                    }
+
+                    if let Some(mode) = options.link_context {
+                        link.context = link_context(elem, mode);
+                    }
+
+                    if collector.current_section.as_deref() == Some("See also") {
+                        collector.see_also.push(link.clone());
+                    }
+                    collector.links.push(link);
+                }
+            } else {
+                if is_artifact(elem) {
+                    continue;
+                }
+
+                let skipped = options.skip_classes.iter().any(|class| {
+                    elem.value()
+                        .has_class(class, scraper::CaseSensitivity::AsciiCaseInsensitive)
+                });
+                if skipped {
+                    continue;
+                }
+
+                // first infobox on the page describes the subject, keep its type classes
+                if collector.infobox_type.is_none()
+                    && elem
+                        .value()
+                        .has_class("infobox", scraper::CaseSensitivity::AsciiCaseInsensitive)
+                {
+                    let infobox_type = elem
+                        .value()
+                        .classes()
+                        .filter(|class| !class.eq_ignore_ascii_case("infobox"))
+                        .collect::<Vec<&str>>()
+                        .join(" ");
+                    collector.infobox_type = Some(infobox_type);
+                }
+
+                if elem.value().name() == "h2" {
+                    collector.current_section = Some(heading_title(elem));
+                }
+
+                if options.structured {
+                    if let Some(block) = text_block(elem, options) {
+                        collector.blocks.push(block);
+                    }
+                }
+
+                if collector.coordinates.is_none() {
+                    collector.coordinates = parse_coordinates(elem);
+                }
+
+                // the lead is every paragraph before the first section heading
+                if elem.value().name() == "p" && collector.current_section.is_none() {
+                    collector.raw_summary.push_str(&visible_text(elem));
+                    collector.raw_summary.push(' ');
+                }
+
+                if elem.value().name() == "table"
+                    && elem
+                        .value()
+                        .has_class("wikitable", scraper::CaseSensitivity::AsciiCaseInsensitive)
+                {
+                    let table = tables::parse_table(elem);
+                    if options.structured {
+                        collector.blocks.push(Block::Table(table.clone()));
+                    }
+                    collector.tables.push(table);
+
+                    if !options.include_tables {
+                        continue;
+                    }
+                }
+
+                // process children elements
+                process_content_recursive(elem, options, collector);
+            }
+        }
+    }
+}
+
+/// Turns a heading, paragraph or list item element into a Block.
+/// Returns None for any other element, or if the element has no text.
+/// List item links are filtered by `options.namespaces`, like `Page.links`
+fn text_block(element: scraper::ElementRef, options: &ParseOptions) -> Option<Block> {
+    let block = match element.value().name() {
+        "h2" | "h3" | "h4" | "h5" | "h6" => Block::Heading {
+            level: element.value().name()[1..].parse().ok()?,
+            text: heading_title(element),
+        },
+        "p" => Block::Paragraph(clean_meta_content(&visible_text(element))),
+        "li" => {
+            // reference lists are citations, not content
+            let in_references = element
+                .parent()
+                .and_then(scraper::ElementRef::wrap)
+                .is_some_and(|list| {
+                    list.value()
+                        .has_class("references", scraper::CaseSensitivity::AsciiCaseInsensitive)
+                });
+            if in_references {
+                return None;
+            }
+
+            let anchor_selector = Selector::parse("a[href]").unwrap();
+            let mut text = String::new();
+            let mut links = Vec::new();
+
+            // nested lists become their own items
+            for node in element.children() {
+                if let Some(node_text) = node.value().as_text() {
+                    text.push_str(node_text);
+                } else if let Some(child) = scraper::ElementRef::wrap(node) {
+                    if matches!(child.value().name(), "ul" | "ol") || is_artifact(child) {
+                        continue;
+                    }
+                    text.push_str(&visible_text(child));
+
+                    let anchors = std::iter::once(child)
+                        .filter(|child| child.value().name() == "a")
+                        .chain(child.select(&anchor_selector));
+                    for anchor in anchors {
+                        let Some((href, exists)) = wiki_href(anchor) else {
+                            continue;
+                        };
+
+                        let link = HyperLink {
+                            position: links.len(),
+                            exists,
+                            ..HyperLink::from_href(
+                                text::normalize_text(&anchor.text().collect::<String>()),
+                                &href,
+                            )
+                        };
+                        if options.namespaces.contains(&link.namespace) {
+                            links.push(link);
+                        }
+                    }
+                }
+            }
+
+            Block::ListItem {
+                text: clean_meta_content(&text),
+                links,
+            }
+        }
+        _ => return None,
+    };
+
+    let is_empty = match &block {
+        Block::Heading { text, .. } | Block::Paragraph(text) | Block::ListItem { text, .. } => {
+            text.trim().is_empty()
+        }
+        Block::Table(_) => false,
+    };
+
+    (!is_empty).then_some(block)
+}
+
+/// Gets the text around a link from its enclosing paragraph, list item or cell
+fn link_context(anchor: scraper::ElementRef, mode: LinkContext) -> Option<String> {
+    let block = anchor
+        .ancestors()
+        .filter_map(scraper::ElementRef::wrap)
+        .find(|element| {
+            matches!(
+                element.value().name(),
+                "p" | "li" | "td" | "th" | "dd" | "dt" | "blockquote" | "figcaption" | "caption"
+            )
+        })?;
+
+    let mut block_text = String::new();
+    let mut span = None;
+    collect_around(block, anchor, &mut block_text, &mut span);
+    let (start, end) = span?;
+
+    // sentences and words are slices of block_text, this gets their byte offset
+    let base = block_text.as_ptr() as usize;
+    let offset = |slice: &str| slice.as_ptr() as usize - base;
+
+    let context = match mode {
+        LinkContext::Sentence => text::split_sentences(&block_text)
+            .into_iter()
+            .find(|sentence| offset(sentence) + sentence.len() > start)?,
+        LinkContext::Words(n) => {
+            let from = block_text[..start]
+                .split_whitespace()
+                .rev()
+                .take(n)
+                .last()
+                .map_or(start, offset);
+            let to = block_text[end..]
+                .split_whitespace()
+                .take(n)
+                .last()
+                .map_or(end, |word| offset(word) + word.len());
+
+            &block_text[from..to]
+        }
+    };
+
+    let context = clean_meta_content(context);
+    (!context.trim().is_empty()).then_some(context)
+}
+
+// visible text of an element, recording where the given anchor's text lands
+fn collect_around(
+    element: scraper::ElementRef,
+    anchor: scraper::ElementRef,
+    out: &mut String,
+    span: &mut Option<(usize, usize)>,
+) {
+    for node in element.children() {
+        if let Some(node_text) = node.value().as_text() {
+            out.push_str(node_text);
+        } else if let Some(child) = scraper::ElementRef::wrap(node) {
+            if child.id() == anchor.id() {
+                let start = out.len();
+                out.push_str(&visible_text(child));
+                *span = Some((start, out.len()));
+            } else if !is_artifact(child) {
+                collect_around(child, anchor, out, span);
+            }
+        }
+    }
+}
+
+/// Whether an element is an editing artifact rather than article text:
+/// citation markers (`[1]`), `[citation needed]` style notices, `[edit]` links
+/// and embedded `<style>`/`<script>` blocks
+fn is_artifact(element: scraper::ElementRef) -> bool {
+    let value = element.value();
+    let has_class = |class| value.has_class(class, scraper::CaseSensitivity::AsciiCaseInsensitive);
+
+    // inline TemplateStyles css and scripts are text nodes, not rendered text
+    matches!(value.name(), "style" | "script" | "noscript")
+        || (value.name() == "sup" && (has_class("reference") || has_class("noprint")))
+        || has_class("mw-editsection")
+}
+
+/// Collects the text below an element, leaving out editing artifacts
+pub(crate) fn visible_text(element: scraper::ElementRef) -> String {
+    let mut text = String::new();
+
+    for node in element.children() {
+        if let Some(node_text) = node.value().as_text() {
+            text.push_str(node_text);
+        } else if let Some(child) = scraper::ElementRef::wrap(node) {
+            if !is_artifact(child) {
+                text.push_str(&visible_text(child));
+            }
+        }
+    }
+
+    text
+}
+
+/// Parses a `span.geo` microformat element (`"48.8567; 2.3508"`) into coordinates.
+/// Returns None for any other element
+fn parse_coordinates(element: scraper::ElementRef) -> Option<Coordinates> {
+    if !element
+        .value()
+        .has_class("geo", scraper::CaseSensitivity::AsciiCaseInsensitive)
+    {
+        return None;
+    }
+
+    let text = element.text().collect::<String>();
+    let (latitude, longitude) = text.split_once(';')?;
+
+    let coordinates = Coordinates {
+        latitude: latitude.trim().parse().ok()?,
+        longitude: longitude.trim().parse().ok()?,
+    };
+
+    let in_range = coordinates.latitude.abs() <= 90.0 && coordinates.longitude.abs() <= 180.0;
+    in_range.then_some(coordinates)
+}
+
+/// Gets the title of a section heading element.
+/// Prefers the anchor id, which does not include any `[edit]` link text
+fn heading_title(heading: scraper::ElementRef) -> String {
+    let headline_selector = Selector::parse("span.mw-headline[id]").unwrap();
+
+    let id = heading.value().attr("id").or_else(|| {
+        heading
+            .select(&headline_selector)
+            .next()
+            .and_then(|headline| headline.value().attr("id"))
+    });
+
+    match id {
+        Some(id) => id.replace('_', " "),
+        None => clean_meta_content(&visible_text(heading)),
+    }
+}
+
+///Processes a raw wikipedia fetch into a Page
+///
+/// # Arguments
+///
+/// * `element` - root element of wikipedia DOM
+/// * 'page_title` - title of wikipedia page being processed
+///
+/// # Returns
+///
+/// Page struct representing the given wiki page
+pub fn process_content(element: scraper::ElementRef, page_title: &str) -> Page {
+    process_content_with(element, page_title, &ParseOptions::default())
+}
+
+///Processes a raw wikipedia fetch into a Page, with the given options
+///
+/// # Arguments
+///
+/// * `element` - root element of wikipedia DOM
+/// * 'page_title` - title of wikipedia page being processed
+/// * `options` - How the content is parsed
+///
+/// # Returns
+///
+/// Page struct representing the given wiki page
+pub fn process_content_with(
+    element: scraper::ElementRef,
+    page_title: &str,
+    options: &ParseOptions,
+) -> Page {
+    debug!("Processing content element...");
+    let mut collector = ContentCollector::default();
+    let references_selector = Selector::parse("ol.references > li").unwrap();
+
+    process_content_recursive(element, options, &mut collector);
+
+    // clean meta content is actually not a cheap function,
+    // only wanna call it once here vs inside the recursive one
+    let cleaned_content = clean_meta_content(&collector.raw_content);
+
+    Page {
+        title: page_title.trim().to_owned(),
+        content: cleaned_content,
+        links: dedup_links(collector.links),
+        categories: collector.categories,
+        infobox_type: collector.infobox_type,
+        tables: collector.tables,
+        see_also: collector.see_also,
+        summary: clean_meta_content(&collector.raw_summary),
+        coordinates: collector.coordinates,
+        blocks: collector.blocks,
+        interwiki: interwiki_links(element),
+        meta: meta::PageMeta::default(),
+        reference_count: element.select(&references_selector).count(),
+    }
+}
+
+/// Gets the interlanguage links below an element, as (language code, title) pairs.
+/// These are the entries of the language menu, so no extra API request is needed
+///
+/// # Arguments
+///
+/// * `element` - root element to search, ie the content root or the whole document
+///
+/// # Returns
+///
+/// The pairs in menu order
+pub fn interwiki_links(element: scraper::ElementRef) -> Vec<(String, String)> {
+    let selector = Selector::parse("a.interlanguage-link-target[hreflang][href]").unwrap();
+
+    element
+        .select(&selector)
+        .filter_map(|link| {
+            let lang = link.value().attr("hreflang")?;
+            let href = link.value().attr("href")?;
+            let (_, slug) = href.split_once("/wiki/")?;
+            let (slug, _) = url_utils::split_fragment(slug);
+
+            let title = percent_encoding::percent_decode_str(slug)
+                .decode_utf8_lossy()
+                .replace('_', " ");
+
+            Some((lang.to_owned(), title))
+        })
+        .collect()
+}
+
+use regex::Regex;
+
+/// Cleans the wikipedia meta content from a string
+///
+/// # Arguments
+///
+/// * `input` - Input content to clean
+///
+/// # Returns
+///
+/// String cleaned of wikipedia meta content
+pub fn clean_meta_content(input: &str) -> String {
+    debug!("Cleaning meta content...");
+    let normalized = text::normalize_text(input);
+
+    let re_whitespace = Regex::new(r"\s+").unwrap();
+    let cleaned_text = re_whitespace.replace_all(&normalized, " ").to_string();
+
+    let clean_text_no_symbols = cleaned_text.replace("()", "").replace("[]", "");
+
+    let re_trim = Regex::new(r"^\s+|\s+$").unwrap();
+    let final_text = re_trim.replace_all(&clean_text_no_symbols, "").to_string();
+
+    debug!("Meta content cleaned.");
+    final_text
+}
+
+/// Removes non-semantic indicators from document
+///
+/// # Arguments
+///
+/// * `page` - page to clean
+///
+/// # Returns
+///
+/// A new, owned clean page with no non-semantic indicators
+pub fn clean_document(page: &Page) -> Page {
+    clean_document_with(page, &CleanOptions::default())
+}
+
+/// Removes non-semantic indicators from document, with the given options
+///
+/// # Arguments
+///
+/// * `page` - page to clean
+/// * `options` - stop words to remove and how to normalize the remaining words
+///
+/// # Returns
+///
+/// A new, owned clean page with no non-semantic indicators
+pub fn clean_document_with(page: &Page, options: &CleanOptions) -> Page {
+    debug!("Cleaning document...");
+    let mut results: String = String::new();
+
+    let content = if options.expand_acronyms {
+        let acronyms = acronyms::extract_acronyms(page);
+        std::borrow::Cow::Owned(acronyms::expand_acronyms(&page.content, &acronyms))
+    } else {
+        std::borrow::Cow::Borrowed(&page.content)
+    };
+
+    options
+        .tokenizer
+        .tokenize(&content)
+        .into_iter()
+        .map(|token| {
+            if options.fold_diacritics && !token.text.is_ascii() {
+                text::fold_diacritics(&token.text)
+            } else {
+                token.text
+            }
+        })
+        .filter(|word| !options.ascii_only || word.is_ascii())
+        .filter_map(|word| options.punctuation.apply(word))
+        .filter(|word| options.keep_numbers || !word.chars().any(|c| c.is_numeric()))
+        .filter(|word| !word.is_empty() && word.chars().count() >= options.min_token_length)
+        .map(|word| {
+            // most words are already lowercase, skip allocating a copy of them
+            if options.lowercase && word.chars().any(char::is_uppercase) {
+                word.to_lowercase()
+            } else {
+                word
+            }
+        })
+        .inspect(|word| debug!("current word: {:?}", word))
+        .filter(|word| !options.remove_stop_words || !options.stop_words.contains(word))
+        .map(|word| options.normalize(word))
+        .for_each(|word| {
+            results.push_str(&word);
+            results.push(' ');
+        });
+
+    debug!("Document cleaned.");
+
+    Page {
+        content: results,
+        ..page.clone()
+    }
+}
+
+/// Options controlling how `clean_document_with` cleans a page
+#[derive(Debug, Clone, PartialEq)]
+pub struct CleanOptions {
+    /// Splits the content into words, `tokenize::UnicodeTokenizer` by default
+    pub tokenizer: SharedTokenizer,
+    /// Drop every word with a non-ASCII character (`"Škoda"`, `"München"`, CJK text),
+    /// the behaviour of older versions. Off by default
+    pub ascii_only: bool,
+    /// Fold diacritics into their base letters (`"é"` to `"e"`), see
+    /// `text::fold_diacritics`. Folding happens before `ascii_only`, so folded
+    /// names are kept. Off by default, it merges distinct words in some languages
+    pub fold_diacritics: bool,
+    /// Keep numbers and words with digits (`"1066"`, `"1990s"`). Off by default
+    pub keep_numbers: bool,
+    /// What to do with words containing punctuation, see `PunctuationPolicy`
+    pub punctuation: PunctuationPolicy,
+    /// Drop words shorter than this many characters, 1 by default
+    pub min_token_length: usize,
+    /// Lowercase every word, on by default
+    pub lowercase: bool,
+    /// Remove `stop_words`, on by default
+    pub remove_stop_words: bool,
+    /// Words removed from documents, english by default
+    pub stop_words: StopWords,
+    /// Replace acronyms defined in the page (`"North Atlantic Treaty Organization (NATO)"`)
+    /// with their expansion, so both forms count as the same terms.
+    /// See `acronyms::extract_acronyms`, off by default
+    pub expand_acronyms: bool,
+    /// Stem the remaining words so inflections share a term, off by default.
+    /// Stop words are removed before stemming
+    pub stemmer: Option<Stemmer>,
+    /// Lemmatize the remaining words, used instead of `stemmer` when both are set.
+    /// Requires the `lemmatizer` feature
+    #[cfg(feature = "lemmatizer")]
+    pub lemmatizer: Option<lemma::Lemmatizer>,
+}
+
+impl Default for CleanOptions {
+    fn default() -> Self {
+        CleanOptions {
+            tokenizer: SharedTokenizer::default(),
+            ascii_only: false,
+            fold_diacritics: false,
+            keep_numbers: false,
+            punctuation: PunctuationPolicy::default(),
+            min_token_length: 1,
+            lowercase: true,
+            remove_stop_words: true,
+            stop_words: StopWords::default(),
+            expand_acronyms: false,
+            stemmer: None,
+            #[cfg(feature = "lemmatizer")]
+            lemmatizer: None,
+        }
+    }
+}
+
+impl CleanOptions {
+    // reduce a cleaned word to its lemma or stem
+    fn normalize(&self, word: String) -> String {
+        #[cfg(feature = "lemmatizer")]
+        if let Some(lemmatizer) = &self.lemmatizer {
+            return lemmatizer.lemmatize(&word).into_owned();
+        }
+
+        match self.stemmer {
+            Some(stemmer) => stemmer.stem(&word).into_owned(),
+            None => word,
+        }
+    }
+}
+
+/// What `clean_document_with` does with words containing punctuation.
+/// The default tokenizer already splits hyphenated words, use
+/// `tokenize::WhitespaceTokenizer` to see them whole
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PunctuationPolicy {
+    /// Drop the whole word (`"don't"`, `"3.14"`)
+    #[default]
+    Drop,
+    /// Remove the punctuation and keep the rest (`"don't"` to `"dont"`)
+    Strip,
+    /// Keep the word as it is (`"well-known"`)
+    Keep,
+}
+
+impl PunctuationPolicy {
+    fn apply(&self, word: String) -> Option<String> {
+        if word.chars().all(|c| c.is_alphanumeric()) {
+            return Some(word);
+        }
+
+        match self {
+            PunctuationPolicy::Drop => None,
+            PunctuationPolicy::Strip => {
+                Some(word.chars().filter(|c| c.is_alphanumeric()).collect())
+            }
+            PunctuationPolicy::Keep => Some(word),
+        }
+    }
+}
+
+/// Convert a Page into its vector representation in a word embeddding vector space
+///
+/// # Arguments
+///
+/// * `page` - The page to convert
+/// * `vocab` - shared vocabulary that you want to use
+///
+/// # Returns
+///
+/// An owned vector of floats containing ONLY the term-frequencies values
+/// This notably does not contain the IDF information, see `corpus::Corpus::vectorize`
+/// 
+pub fn page_to_vec(page: &Page, vocab: &HashMap<String, usize>) -> Vec<f64> {
+    page_to_vec_with(page, vocab, &VectorOptions::default())
+}
+
+/// Options controlling how pages are turned into vectors
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct VectorOptions {
+    /// Cap every document at this many words before vectorizing, see `token_cap`.
+    /// Keeps very long list articles from dominating the shared vocabulary
+    pub max_words: Option<usize>,
+    /// How words are chosen when a document is over `max_words`
+    pub token_cap: TokenCap,
+    /// How document length is normalized when scoring vectors against each other
+    pub length_normalization: LengthNormalization,
+    /// How documents are cleaned before vectorizing, see `clean_document_with`.
+    /// Diacritic folding is `CleanOptions::fold_diacritics`
+    pub clean: CleanOptions,
+}
+
+/// Strategy for cutting a document down to `VectorOptions::max_words`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TokenCap {
+    /// Keep the head of the document, see `Page::truncated`
+    #[default]
+    Head,
+    /// Sample cleaned terms from the whole document, weighted by the inverse of
+    /// their frequency in it. Rare, informative terms from late sections survive
+    /// where head truncation would keep only the lead.
+    /// Sampling is seeded by the page title, so a page always yields the same terms
+    ImportanceSampled,
+}
+
+/// Document length normalization used when scoring page vectors
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LengthNormalization {
+    /// Divide by each vector's own magnitude, plain cosine similarity
+    #[default]
+    Cosine,
+    /// Pivoted normalization, divides by `(1 - slope) * pivot + slope * magnitude`.
+    /// A `slope` below 1 penalizes long (low magnitude) documents that would otherwise
+    /// score as similar to everything, `slope = 1` is plain cosine. `pivot` defaults to
+    /// the mean magnitude of the candidate documents, so scores against the same
+    /// candidates are comparable whichever page they are scored for. Pairwise
+    /// similarity pivots over both pages.
+    /// Scores are no longer bounded by 1
+    Pivoted { slope: f64, pivot: Option<f64> },
+}
+
+impl LengthNormalization {
+    // fill in a missing pivot with the mean magnitude of `vectors`
+    pub(crate) fn pivoted_over<V: AsRef<[f64]>>(self, vectors: &[V]) -> Self {
+        match self {
+            LengthNormalization::Pivoted { slope, pivot: None } => {
+                let total: f64 = vectors.iter().map(|vec| magnitude(vec.as_ref())).sum();
+
+                LengthNormalization::Pivoted {
+                    slope,
+                    pivot: Some(total / vectors.len().max(1) as f64),
+                }
+            }
+            other => other,
+        }
+    }
+}
+
+/// Cleans a page for vectorization, applying the length cap of `options`
+pub(crate) fn prepare_document(page: &Page, options: &VectorOptions) -> Page {
+    let page = match (options.max_words, options.token_cap) {
+        (Some(max_words), TokenCap::Head) => std::borrow::Cow::Owned(page.truncated(max_words).0),
+        _ => std::borrow::Cow::Borrowed(page),
+    };
+
+    let mut page = clean_document_with(&page, &options.clean);
+
+    if let (Some(max_words), TokenCap::ImportanceSampled) = (options.max_words, options.token_cap) {
+        let seed = hashing::fnv1a(page.title.as_bytes());
+        page.content = sample_terms(&page.content, max_words, seed);
+    }
+
+    page
+}
+
+/// Weighted sampling of `max_words` terms without replacement (Efraimidis-Spirakis),
+/// a term's weight is the inverse of its count. Kept terms stay in document order
+fn sample_terms(content: &str, max_words: usize, seed: u64) -> String {
+    let terms: Vec<&str> = content.split_whitespace().collect();
+    if terms.len() <= max_words {
+        return content.to_owned();
+    }
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for term in &terms {
+        *counts.entry(term).or_insert(0) += 1;
+    }
+
+    // xorshift64*, only needs to be deterministic, not strong
+    let mut state = seed | 1;
+    let mut next_unit = || {
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        let bits = state.wrapping_mul(0x2545f4914f6cdd1d) >> 11;
+        (bits as f64 + 1.0) / (1u64 << 53) as f64
+    };
+
+    // key = u^(1 / weight), the largest keys win
+    let mut keyed: Vec<(f64, usize)> = terms
+        .iter()
+        .enumerate()
+        .map(|(i, term)| (next_unit().powi(counts[term] as i32), i))
+        .collect();
+    keyed.sort_unstable_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut kept: Vec<usize> = keyed[..max_words].iter().map(|&(_, i)| i).collect();
+    kept.sort_unstable();
+
+    kept.iter()
+        .map(|&i| terms[i])
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Builds a shared vocabulary over a set of pages, see `vocabulary::Vocabulary`
+pub(crate) fn build_vocab<'a>(
+    pages: impl Iterator<Item = &'a Page>,
+    options: &VectorOptions,
+) -> HashMap<String, usize> {
+    vocabulary::Vocabulary::from_pages(pages, options).into_map()
+}
+
+/// Counts every term of a cleaned document
+pub(crate) fn count_terms(content: &str) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for word in content.split_whitespace() {
+        match counts.get_mut(word) {
+            Some(count) => *count += 1,
+            None => {
+                counts.insert(word.to_owned(), 1);
+            }
+        }
+    }
+
+    counts
+}
+
+/// Convert a Page into its vector representation, with the given options
+///
+/// # Arguments
+///
+/// * `page` - The page to convert
+/// * `vocab` - shared vocabulary that you want to use
+/// * `options` - How the page is vectorized
+///
+/// # Returns
+///
+/// An owned vector of floats containing ONLY the term-frequencies values
+pub fn page_to_vec_with(
+    page: &Page,
+    vocab: &HashMap<String, usize>,
+    options: &VectorOptions,
+) -> Vec<f64> {
+    let content = prepare_document(page, options).content;
+
+    term_frequencies(&count_terms(&content), vocab)
+}
+
+/// Projects the term counts of a document onto a vocabulary as term frequencies
+pub(crate) fn term_frequencies(
+    counts: &HashMap<String, usize>,
+    vocab: &HashMap<String, usize>,
+) -> Vec<f64> {
+    let total_words = counts.values().sum::<usize>() as f64;
+    let mut vector = vec![0.0; vocab.len()];
+
+    for (word, &count) in counts {
+        if let Some(&index) = vocab.get(word) {
+            vector[index] = count as f64 / total_words;
+        }
+    }
+
+    vector
+}
+
+/// Convert a Page into its vector representation with single precision floats,
+/// half the memory of `page_to_vec` for large candidate pools
+///
+/// # Arguments
+///
+/// * `page` - The page to convert
+/// * `vocab` - shared vocabulary that you want to use
+///
+/// # Returns
+///
+/// An owned vector of floats containing ONLY the term-frequencies values
+pub fn page_to_vec_f32(page: &Page, vocab: &HashMap<String, usize>) -> Vec<f32> {
+    page_to_vec_f32_with(page, vocab, &VectorOptions::default())
+}
+
+/// Convert a Page into its single precision vector representation, with the given options
+///
+/// # Arguments
+///
+/// * `page` - The page to convert
+/// * `vocab` - shared vocabulary that you want to use
+/// * `options` - How the page is vectorized
+///
+/// # Returns
+///
+/// An owned vector of floats containing ONLY the term-frequencies values
+pub fn page_to_vec_f32_with(
+    page: &Page,
+    vocab: &HashMap<String, usize>,
+    options: &VectorOptions,
+) -> Vec<f32> {
+    to_f32(page_to_vec_with(page, vocab, options))
+}
+
+pub(crate) fn to_f32(vector: Vec<f64>) -> Vec<f32> {
+    vector.into_iter().map(|x| x as f32).collect()
+}
+use rayon::prelude::*;
+use stem::Stemmer;
+use stop_words::StopWords;
+use tokenize::SharedTokenizer;
+
+/// The cosine similarity between two vectors
+///
+/// # Arguments
+///
+/// * `vec1` - The first vector
+/// * `vec2` - The second vector
+///
+/// # Returns
+///
+/// the cosine of the angle between the vectors -> [0-1)
+pub fn cosine_sim(vec1: &[f64], vec2: &[f64]) -> f64 {
     let dot_product: f64 = vec1
         .par_iter()
         .zip(vec2.par_iter())
@@ -424,7 +1864,76 @@ pub fn cosine_sim(vec1: &Vec<f64>, vec2: &Vec<f64>) -> f64 {
     //par iter brrrrrrrrrrrrrrrr
     let magnitude1: f64 = vec1.par_iter().map(|x| x.powi(2)).sum::<f64>().sqrt();
     let magnitude2: f64 = vec2.par_iter().map(|x| x.powi(2)).sum::<f64>().sqrt();
-    return dot_product / (magnitude1 * magnitude2);
+    dot_product / (magnitude1 * magnitude2)
+}
+
+/// The cosine similarity between two single precision vectors, ie `page_to_vec_f32`
+/// vectors or word embeddings
+///
+/// # Arguments
+///
+/// * `vec1` - The first vector
+/// * `vec2` - The second vector
+///
+/// # Returns
+///
+/// the cosine of the angle between the vectors -> [-1, 1], 0 if either vector is all zeros
+pub fn cosine_sim_f32(vec1: &[f32], vec2: &[f32]) -> f64 {
+    // sums are taken in f64, long sparse vectors lose too much precision otherwise
+    let (mut dot_product, mut norm1, mut norm2) = (0.0f64, 0.0f64, 0.0f64);
+
+    for (&a, &b) in vec1.iter().zip(vec2) {
+        let (a, b) = (a as f64, b as f64);
+        dot_product += a * b;
+        norm1 += a * a;
+        norm2 += b * b;
+    }
+
+    if norm1 == 0.0 || norm2 == 0.0 {
+        return 0.0;
+    }
+
+    dot_product / (norm1.sqrt() * norm2.sqrt())
+}
+
+fn magnitude(vec: &[f64]) -> f64 {
+    vec.par_iter().map(|x| x.powi(2)).sum::<f64>().sqrt()
+}
+
+/// Scores a primary vector against candidate vectors under a length normalization.
+/// A missing pivot is the mean magnitude of the candidates
+pub(crate) fn score_vectors<V: AsRef<[f64]>>(
+    primary: &[f64],
+    candidates: &[V],
+    normalization: LengthNormalization,
+) -> Vec<f64> {
+    match normalization.pivoted_over(candidates) {
+        LengthNormalization::Cosine => candidates
+            .iter()
+            .map(|candidate| cosine_sim(primary, candidate.as_ref()))
+            .collect(),
+        LengthNormalization::Pivoted { slope, pivot } => {
+            let primary_magnitude = magnitude(primary);
+            let magnitudes: Vec<f64> = candidates.iter().map(|c| magnitude(c.as_ref())).collect();
+
+            let pivot = pivot.unwrap_or_default();
+            let pivoted = |magnitude: f64| (1.0 - slope) * pivot + slope * magnitude;
+
+            candidates
+                .iter()
+                .zip(magnitudes)
+                .map(|(candidate, candidate_magnitude)| {
+                    let dot_product: f64 = primary
+                        .par_iter()
+                        .zip(candidate.as_ref().par_iter())
+                        .map(|(a, b)| a * b)
+                        .sum();
+
+                    dot_product / (pivoted(primary_magnitude) * pivoted(candidate_magnitude))
+                })
+                .collect()
+        }
+    }
 }
 
 /// Get the similarity of two pages
@@ -438,60 +1947,210 @@ pub fn cosine_sim(vec1: &Vec<f64>, vec2: &Vec<f64>) -> f64 {
 ///
 /// The document similarity [0-1)
 pub fn get_page_similarity(page1: &Page, page2: &Page) -> f64 {
-    let mut vocab = HashMap::new();
+    get_page_similarity_with(page1, page2, &VectorOptions::default())
+}
 
+/// Get the similarity of two pages, vectorized with the given options
+///
+/// # Arguments
+///
+/// * `page1` - The first page to check
+/// * `page2` - The second page to check
+/// * `options` - How the pages are vectorized
+///
+/// # Returns
+///
+/// The document similarity [0-1)
+pub fn get_page_similarity_with(page1: &Page, page2: &Page, options: &VectorOptions) -> f64 {
     // need shared vocab now
-    let mut vocab_len = 0;
-    for page in &[page1, page2] {
-        let content = clean_document(page).content;
+    let vocab = build_vocab([page1, page2].into_iter(), options);
 
-        for word in content.split_whitespace() {
-            vocab_len = vocab.len();
+    let vec1 = page_to_vec_with(page1, &vocab, options);
+    let vec2 = page_to_vec_with(page2, &vocab, options);
+    let normalization = options.length_normalization.pivoted_over(&[&vec1, &vec2]);
 
-            vocab.entry(word.to_string()).or_insert(vocab_len);
-        }
-    }
+    score_vectors(&vec1, &[vec2], normalization)[0]
+}
 
-    let vec1 = page_to_vec(page1, &vocab);
-    let vec2 = page_to_vec(page2, &vocab);
+/// How much a single term adds to the cosine similarity of two pages,
+/// see `overlap::TermOverlap::contribution`
+pub type TermContribution = overlap::TermOverlap;
 
-    cosine_sim(&vec1, &vec2)
+/// Lists the terms that contribute most to the cosine similarity of two pages,
+/// ie to see why two pages score surprisingly high
+///
+/// # Arguments
+///
+/// * `a` - The first page
+/// * `b` - The second page
+/// * `n` - The maximum number of terms
+///
+/// # Returns
+///
+/// Up to `n` shared terms, largest contribution first and by term on ties
+pub fn explain_similarity(a: &Page, b: &Page, n: usize) -> Vec<TermContribution> {
+    explain_similarity_with(a, b, n, &VectorOptions::default())
 }
 
-/// Get the most similar page from a set of pages
+/// Lists the terms that contribute most to the cosine similarity of two pages,
+/// vectorized with the given options, see `explain_similarity`
 ///
 /// # Arguments
 ///
-/// * `primary_page` - The page to check for similarity to
-/// * `pages` - The set of pages to check against
+/// * `a` - The first page
+/// * `b` - The second page
+/// * `n` - The maximum number of terms
+/// * `options` - How the pages are vectorized
 ///
 /// # Returns
 ///
-/// The ARGMAX of the most similar page
-pub fn get_most_similar_page(primary_page: &Page, pages: &Vec<Page>) -> usize {
-    let mut vocab = HashMap::new();
+/// Up to `n` shared terms, largest contribution first and by term on ties
+pub fn explain_similarity_with(
+    a: &Page,
+    b: &Page,
+    n: usize,
+    options: &VectorOptions,
+) -> Vec<TermContribution> {
+    // the overlap report is already ordered by contribution
+    let mut contributions = overlap::term_overlap_report_with(a, b, options);
+    contributions.truncate(n);
+
+    contributions
+}
+
+/// The Jaccard similarity of the term sets of two pages, |A ∩ B| / |A ∪ B|.
+/// Cheaper than `get_page_similarity` and less swayed by a few repeated words,
+/// which makes it more robust for stubs and very short pages
+///
+/// # Arguments
+///
+/// * `page1` - The first page to check
+/// * `page2` - The second page to check
+///
+/// # Returns
+///
+/// The set similarity [0-1], 0 if neither page has any terms
+pub fn jaccard_sim(page1: &Page, page2: &Page) -> f64 {
+    jaccard_sim_with(page1, page2, &VectorOptions::default())
+}
 
+/// The Jaccard similarity of the term sets of two pages, cleaned with the given options
+pub fn jaccard_sim_with(page1: &Page, page2: &Page, options: &VectorOptions) -> f64 {
+    let (set1, set2) = term_set_pair(page1, page2, options);
+    set1.jaccard(&set2)
+}
 
-    let mut vocab_len = 0;
-    // Build shared vocabulary from primary_page and all comparison pages
-    for page in std::iter::once(primary_page).chain(pages.iter()) {
-        let content = clean_document(page).content;
+/// The overlap coefficient of the term sets of two pages, |A ∩ B| / min(|A|, |B|).
+/// A stub whose terms all appear in a longer page scores 1
+///
+/// # Arguments
+///
+/// * `page1` - The first page to check
+/// * `page2` - The second page to check
+///
+/// # Returns
+///
+/// The set similarity [0-1], 0 if either page has no terms
+pub fn overlap_sim(page1: &Page, page2: &Page) -> f64 {
+    overlap_sim_with(page1, page2, &VectorOptions::default())
+}
 
-        for word in content.split_whitespace() {
-            vocab_len = vocab.len();
-            vocab.entry(word.to_string()).or_insert(vocab_len);
-        }
+/// The overlap coefficient of the term sets of two pages, cleaned with the given options
+pub fn overlap_sim_with(page1: &Page, page2: &Page, options: &VectorOptions) -> f64 {
+    let (set1, set2) = term_set_pair(page1, page2, options);
+    set1.overlap(&set2)
+}
+
+/// The Jaccard similarity of the sets of pages two pages link to
+///
+/// # Returns
+///
+/// The set similarity [0-1], 0 if neither page has links
+pub fn link_jaccard_sim(page1: &Page, page2: &Page) -> f64 {
+    let (links1, links2) = (link_set(page1), link_set(page2));
+    let union = links1.union(&links2).count();
+
+    if union == 0 {
+        return 0.0;
+    }
+
+    links1.intersection(&links2).count() as f64 / union as f64
+}
+
+/// The overlap coefficient of the sets of pages two pages link to
+///
+/// # Returns
+///
+/// The set similarity [0-1], 0 if either page has no links
+pub fn link_overlap_sim(page1: &Page, page2: &Page) -> f64 {
+    let (links1, links2) = (link_set(page1), link_set(page2));
+    let smaller = links1.len().min(links2.len());
+
+    if smaller == 0 {
+        return 0.0;
     }
 
-    let primary_vec = page_to_vec(primary_page, &vocab);
+    links1.intersection(&links2).count() as f64 / smaller as f64
+}
+
+fn term_set_pair(
+    page1: &Page,
+    page2: &Page,
+    options: &VectorOptions,
+) -> (term_set::TermSet, term_set::TermSet) {
+    let vocab = build_vocab([page1, page2].into_iter(), options);
+
+    (
+        term_set::TermSet::from_page(page1, &vocab, options),
+        term_set::TermSet::from_page(page2, &vocab, options),
+    )
+}
+
+// links to different sections of a page point to the same page
+fn link_set(page: &Page) -> HashSet<&str> {
+    page.links
+        .iter()
+        .map(|link| link.outlink.as_str())
+        .collect()
+}
+
+/// Get the most similar page from a set of pages
+///
+/// # Arguments
+///
+/// * `primary_page` - The page to check for similarity to
+/// * `pages` - The set of pages to check against
+///
+/// # Returns
+///
+/// The ARGMAX of the most similar page. 0 if `pages` is empty or nothing could be
+/// compared, see `find_most_similar_page` to tell those cases apart
+pub fn get_most_similar_page(primary_page: &Page, pages: &[Page]) -> usize {
+    get_most_similar_page_with(primary_page, pages, &VectorOptions::default())
+}
+
+/// Get the most similar page from a set of pages, vectorized with the given options
+///
+/// # Arguments
+///
+/// * `primary_page` - The page to check for similarity to
+/// * `pages` - The set of pages to check against
+/// * `options` - How the pages are vectorized
+///
+/// # Returns
+///
+/// The ARGMAX of the most similar page
+pub fn get_most_similar_page_with(
+    primary_page: &Page,
+    pages: &[Page],
+    options: &VectorOptions,
+) -> usize {
+    let sims = similarity_scores(primary_page, pages, options);
 
     let mut most_similar_index: usize = 0;
     let mut best_similarity: f64 = -1.0; // start at most dissimilar
 
-    for (page_index, page) in pages.iter().enumerate() {
-        let cur_vec = page_to_vec(page, &vocab);
-        let cur_sim = cosine_sim(&primary_vec, &cur_vec);
-
+    for (page_index, &cur_sim) in sims.iter().enumerate() {
         if cur_sim > best_similarity {
             best_similarity = cur_sim;
             most_similar_index = page_index;
@@ -499,4 +2158,431 @@ pub fn get_most_similar_page(primary_page: &Page, pages: &Vec<Page>) -> usize {
     }
 
     most_similar_index
-}
\ No newline at end of file
+}
+
+/// The most similar page of a set, with its score and the full ranking
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarityResult {
+    /// Index of the most similar page in the candidates
+    pub index: usize,
+    /// Similarity of the most similar page, 0 if nothing is actually similar
+    pub score: f64,
+    /// Every candidate as (index, similarity), most similar first
+    pub ranking: Vec<(usize, f64)>,
+}
+
+/// Get the most similar page from a set of pages, with its score and the ranking
+/// of every candidate. Unlike `get_most_similar_page`, inputs that have no most
+/// similar page are errors rather than index 0
+///
+/// # Arguments
+///
+/// * `primary_page` - The page to check for similarity to
+/// * `pages` - The set of pages to check against
+///
+/// # Returns
+///
+/// Ok(SimilarityResult) - the most similar page and the ranking
+/// Err(WikiError::InputError) - error if `pages` is empty, or no candidate
+/// could be scored because the primary page or every candidate has no terms
+pub fn find_most_similar_page(
+    primary_page: &Page,
+    pages: &[Page],
+) -> Result<SimilarityResult, WikiError> {
+    find_most_similar_page_with(primary_page, pages, &VectorOptions::default())
+}
+
+/// Get the most similar page from a set of pages with its score and the ranking,
+/// vectorized with the given options, see `find_most_similar_page`
+///
+/// # Arguments
+///
+/// * `primary_page` - The page to check for similarity to
+/// * `pages` - The set of pages to check against
+/// * `options` - How the pages are vectorized
+///
+/// # Returns
+///
+/// Ok(SimilarityResult) - the most similar page and the ranking
+/// Err(WikiError::InputError) - error if there is nothing to compare
+pub fn find_most_similar_page_with(
+    primary_page: &Page,
+    pages: &[Page],
+    options: &VectorOptions,
+) -> Result<SimilarityResult, WikiError> {
+    if pages.is_empty() {
+        return Err(WikiError::InputError(
+            "No candidate pages to compare against".to_owned(),
+        ));
+    }
+
+    let sims = similarity_scores(primary_page, pages, options);
+    if sims.iter().all(|sim| sim.is_nan()) {
+        return Err(WikiError::InputError(format!(
+            "No terms to compare between {} and the candidate pages",
+            primary_page.title
+        )));
+    }
+
+    let mut ranking: Vec<(usize, f64)> = sims
+        .into_iter()
+        .map(|sim| if sim.is_nan() { 0.0 } else { sim })
+        .enumerate()
+        .collect();
+    ranking.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let (index, score) = ranking[0];
+    Ok(SimilarityResult {
+        index,
+        score,
+        ranking,
+    })
+}
+
+/// Get the k most similar pages from a set of pages, with their scores
+///
+/// # Arguments
+///
+/// * `primary_page` - The page to check for similarity to
+/// * `pages` - The set of pages to check against
+/// * `k` - The maximum number of pages to return
+///
+/// # Returns
+///
+/// Up to `k` (index into `pages`, similarity) pairs, most similar first.
+/// Pages with no terms score 0
+pub fn get_top_k_similar(primary_page: &Page, pages: &[Page], k: usize) -> Vec<(usize, f64)> {
+    get_top_k_similar_with(primary_page, pages, k, &VectorOptions::default())
+}
+
+/// Get the k most similar pages from a set of pages, vectorized with the given options
+///
+/// # Arguments
+///
+/// * `primary_page` - The page to check for similarity to
+/// * `pages` - The set of pages to check against
+/// * `k` - The maximum number of pages to return
+/// * `options` - How the pages are vectorized
+///
+/// # Returns
+///
+/// Up to `k` (index into `pages`, similarity) pairs, most similar first
+pub fn get_top_k_similar_with(
+    primary_page: &Page,
+    pages: &[Page],
+    k: usize,
+    options: &VectorOptions,
+) -> Vec<(usize, f64)> {
+    let mut ranked: Vec<(usize, f64)> = similarity_scores(primary_page, pages, options)
+        .into_iter()
+        .map(|sim| if sim.is_nan() { 0.0 } else { sim })
+        .enumerate()
+        .collect();
+
+    // stable, so ties keep the order of `pages`
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked.truncate(k);
+
+    ranked
+}
+
+/// Get the similarity of a free text query to a page. The query goes through the
+/// same cleaning and vectorizing as a page, so it can be any text, not just a title
+///
+/// # Arguments
+///
+/// * `query` - The text to score
+/// * `page` - The page to score it against
+///
+/// # Returns
+///
+/// The similarity [0-1], 0 if the query has no terms left after cleaning
+pub fn query_similarity(query: &str, page: &Page) -> f64 {
+    query_similarity_with(query, page, &VectorOptions::default())
+}
+
+/// Get the similarity of a free text query to a page, vectorized with the given options
+///
+/// # Arguments
+///
+/// * `query` - The text to score
+/// * `page` - The page to score it against
+/// * `options` - How the query and the page are vectorized
+///
+/// # Returns
+///
+/// The similarity [0-1], 0 if the query has no terms left after cleaning
+pub fn query_similarity_with(query: &str, page: &Page, options: &VectorOptions) -> f64 {
+    let sim = get_page_similarity_with(&query_page(query), page, options);
+
+    if sim.is_nan() {
+        0.0
+    } else {
+        sim
+    }
+}
+
+/// Ranks pages by their similarity to a free text query, see `query_similarity`
+///
+/// # Arguments
+///
+/// * `query` - The text to rank by
+/// * `pages` - The pages to rank
+///
+/// # Returns
+///
+/// (index into `pages`, similarity) for every page, most similar first
+pub fn rank_pages_by_query(query: &str, pages: &[Page]) -> Vec<(usize, f64)> {
+    rank_pages_by_query_with(query, pages, &VectorOptions::default())
+}
+
+/// Ranks pages by their similarity to a free text query, vectorized with the given options
+///
+/// # Arguments
+///
+/// * `query` - The text to rank by
+/// * `pages` - The pages to rank
+/// * `options` - How the query and the pages are vectorized
+///
+/// # Returns
+///
+/// (index into `pages`, similarity) for every page, most similar first
+pub fn rank_pages_by_query_with(
+    query: &str,
+    pages: &[Page],
+    options: &VectorOptions,
+) -> Vec<(usize, f64)> {
+    get_top_k_similar_with(&query_page(query), pages, pages.len(), options)
+}
+
+// a query is vectorized as a page holding only the query text
+fn query_page(query: &str) -> Page {
+    Page {
+        content: query.to_owned(),
+        ..Default::default()
+    }
+}
+
+/// Computes the similarity of every pair of pages. The vocabulary is built once
+/// and rows are scored in parallel, unlike calling `get_page_similarity` per pair.
+/// For page sets too large to hold the matrix in memory see `tiled::TiledSimilarityMatrix`
+///
+/// # Arguments
+///
+/// * `pages` - The pages to compare, every page against every page
+///
+/// # Returns
+///
+/// A symmetric `pages.len()` square matrix, row `i` column `j` holding the
+/// similarity of pages `i` and `j`. Pages with no terms score 0 against everything
+pub fn similarity_matrix(pages: &[Page]) -> matrix::Matrix<f64> {
+    similarity_matrix_with(pages, &VectorOptions::default())
+}
+
+/// Computes the similarity of every pair of pages, vectorized with the given options
+///
+/// # Arguments
+///
+/// * `pages` - The pages to compare, every page against every page
+/// * `options` - How the pages are vectorized
+///
+/// # Returns
+///
+/// A symmetric `pages.len()` square matrix of similarities
+pub fn similarity_matrix_with(pages: &[Page], options: &VectorOptions) -> matrix::Matrix<f64> {
+    let vocab = build_vocab(pages.iter(), options);
+    let vectors: Vec<Vec<f64>> = pages
+        .par_iter()
+        .map(|page| page_to_vec_with(page, &vocab, options))
+        .collect();
+
+    // one pivot for every row, or the matrix is not symmetric
+    let normalization = options.length_normalization.pivoted_over(&vectors);
+
+    let data: Vec<f64> = vectors
+        .par_iter()
+        .flat_map_iter(|vector| score_vectors(vector, &vectors, normalization))
+        .map(|sim| if sim.is_nan() { 0.0 } else { sim })
+        .collect();
+
+    matrix::Matrix::from_vec(pages.len(), pages.len(), data)
+}
+
+/// Re-ranks similarity results with maximal marginal relevance, so the top results
+/// are not several near identical pages. Each pick is the page with the best
+/// `lambda * relevance - (1 - lambda) * (highest similarity to an earlier pick)`
+///
+/// # Arguments
+///
+/// * `results` - (index into `pages`, relevance) pairs, ie from `get_top_k_similar`
+/// * `pages` - The pages the results index into
+/// * `lambda` - Trade off [0-1] between relevance (1) and diversity (0)
+///
+/// # Returns
+///
+/// The same results with their relevance, in the re-ranked order
+pub fn rerank_mmr(results: &[(usize, f64)], pages: &[Page], lambda: f64) -> Vec<(usize, f64)> {
+    rerank_mmr_with(results, pages, lambda, &VectorOptions::default())
+}
+
+/// Re-ranks similarity results with maximal marginal relevance, comparing pages
+/// vectorized with the given options, see `rerank_mmr`
+///
+/// # Arguments
+///
+/// * `results` - (index into `pages`, relevance) pairs, ie from `get_top_k_similar_with`
+/// * `pages` - The pages the results index into
+/// * `lambda` - Trade off [0-1] between relevance (1) and diversity (0)
+/// * `options` - How the pages are vectorized
+///
+/// # Returns
+///
+/// The same results with their relevance, in the re-ranked order
+pub fn rerank_mmr_with(
+    results: &[(usize, f64)],
+    pages: &[Page],
+    lambda: f64,
+    options: &VectorOptions,
+) -> Vec<(usize, f64)> {
+    assert!(
+        (0.0..=1.0).contains(&lambda),
+        "MMR lambda must be between 0 and 1"
+    );
+
+    let candidates = || results.iter().map(|&(index, _)| &pages[index]);
+    let vocab = build_vocab(candidates(), options);
+    let vectors: Vec<Vec<f64>> = candidates()
+        .map(|page| page_to_vec_with(page, &vocab, options))
+        .collect();
+
+    let mut remaining: Vec<usize> = (0..results.len()).collect();
+    // highest similarity of every remaining candidate to any pick so far
+    let mut redundancy = vec![0.0; results.len()];
+    let mut reranked = Vec::with_capacity(results.len());
+
+    while !remaining.is_empty() {
+        let mmr = |&candidate: &usize| {
+            lambda * results[candidate].1 - (1.0 - lambda) * redundancy[candidate]
+        };
+
+        // the first candidate wins ties, keeping the original order
+        let mut best = 0;
+        for position in 1..remaining.len() {
+            if mmr(&remaining[position]) > mmr(&remaining[best]) {
+                best = position;
+            }
+        }
+
+        let picked = remaining.remove(best);
+        reranked.push(results[picked]);
+
+        let scores = score_vectors(&vectors[picked], &vectors, options.length_normalization);
+        for &candidate in &remaining {
+            let sim = if scores[candidate].is_nan() {
+                0.0
+            } else {
+                scores[candidate]
+            };
+            redundancy[candidate] = f64::max(redundancy[candidate], sim);
+        }
+    }
+
+    reranked
+}
+
+/// Scores every page against the primary page over their shared vocabulary
+fn similarity_scores(primary_page: &Page, pages: &[Page], options: &VectorOptions) -> Vec<f64> {
+    // Build shared vocabulary from primary_page and all comparison pages
+    let vocab = build_vocab(std::iter::once(primary_page).chain(pages.iter()), options);
+
+    let primary_vec = page_to_vec_with(primary_page, &vocab, options);
+    let page_vecs: Vec<Vec<f64>> = pages
+        .iter()
+        .map(|page| page_to_vec_with(page, &vocab, options))
+        .collect();
+
+    score_vectors(&primary_vec, &page_vecs, options.length_normalization)
+}
+
+/// How outlink targets are fetched when ranking them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchMode {
+    /// Only fetch the lead extract of each target. Much faster, less accurate
+    Summary,
+    /// Fetch and parse every target page in full
+    Full,
+    /// Fetch the whole plain text of each target, see `plain_text_from_title`.
+    /// As complete as `Full` without any HTML cleanup
+    PlainText,
+}
+
+/// Ranks the outlinks of a page by how similar their targets are to the page.
+/// Uses `FetchMode::Summary` to fetch targets
+///
+/// # Arguments
+///
+/// * `page` - The page whose outlinks should be ranked
+///
+/// # Returns
+///
+/// Each unique outlink with its similarity score, most similar first
+pub fn rank_outlinks_by_similarity(page: &Page) -> Vec<(HyperLink, f64)> {
+    rank_outlinks_by_similarity_with(page, FetchMode::Summary)
+}
+
+/// The outlinks worth fetching: each target once, red links to missing pages left out
+pub(crate) fn fetchable_outlinks(page: &Page) -> Vec<&HyperLink> {
+    // hub pages link the same target many times, only fetch each once
+    let mut seen = HashSet::new();
+
+    page.links
+        .iter()
+        .filter(|link| link.exists && seen.insert(link.outlink.as_str()))
+        .collect()
+}
+
+/// Ranks the outlinks of a page by how similar their targets are to the page.
+/// Red links are skipped, their pages do not exist
+///
+/// # Arguments
+///
+/// * `page` - The page whose outlinks should be ranked
+/// * `mode` - How each outlink target is fetched
+///
+/// # Returns
+///
+/// Each unique outlink with its similarity score, most similar first.
+/// Targets that fail to fetch are left out of the ranking
+pub fn rank_outlinks_by_similarity_with(page: &Page, mode: FetchMode) -> Vec<(HyperLink, f64)> {
+    debug!("Ranking outlinks of {} with {:?}", page.title, mode);
+
+    let unique_links = fetchable_outlinks(page);
+
+    let mut ranked: Vec<(HyperLink, f64)> = unique_links
+        .par_iter()
+        .filter_map(|link| {
+            let target = match mode {
+                FetchMode::Summary => summary_from_url(&link.outlink),
+                FetchMode::Full => page_from_url(&link.outlink),
+                FetchMode::PlainText => plain_text_from_url(&link.outlink),
+            };
+
+            match target {
+                Ok(target) => {
+                    let sim = get_page_similarity(page, &target);
+                    // empty documents have no direction, treat them as unrelated
+                    let sim = if sim.is_nan() { 0.0 } else { sim };
+                    Some(((*link).clone(), sim))
+                }
+                Err(err) => {
+                    debug!("Skipping outlink {}: {}", link.outlink, err);
+                    None
+                }
+            }
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    ranked
+}
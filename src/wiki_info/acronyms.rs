@@ -0,0 +1,110 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::Page;
+
+lazy_static! {
+    // "North Atlantic Treaty Organization (NATO)"
+    static ref DEFINITION: Regex = Regex::new(r"\(([A-Z][A-Za-z]{0,8}[A-Z])s?\)").unwrap();
+}
+
+// small words an acronym usually skips
+const CONNECTORS: [&str; 9] = ["of", "the", "and", "for", "in", "on", "to", "a", "de"];
+
+// the expansion may contain this many connectors besides the acronym letters
+const MAX_CONNECTORS: usize = 3;
+
+/// Finds acronyms defined in a page, in the `"Expansion (ACRONYM)"` form.
+/// The expansion is the shortest run of words before the parentheses whose
+/// initials spell the capital letters of the acronym, skipping connectors
+/// (`"Department of Defense (DoD)"`)
+///
+/// # Arguments
+///
+/// * `page` - the page to search
+///
+/// # Returns
+///
+/// (acronym, expansion) pairs in order of first definition, one per acronym
+pub fn extract_acronyms(page: &Page) -> Vec<(String, String)> {
+    let mut found: Vec<(String, String)> = Vec::new();
+
+    for captures in DEFINITION.captures_iter(&page.content) {
+        let acronym = &captures[1];
+        if found.iter().any(|(known, _)| known == acronym) {
+            continue;
+        }
+
+        let before = &page.content[..captures.get(0).unwrap().start()];
+        if let Some(expansion) = find_expansion(acronym, before) {
+            found.push((acronym.to_owned(), expansion));
+        }
+    }
+
+    found
+}
+
+/// Replaces acronyms in a text with their expansions, so both forms count as
+/// the same terms. Parenthesized definitions are removed so the expansion is
+/// not counted twice where the acronym is defined
+///
+/// # Arguments
+///
+/// * `text` - the text to rewrite
+/// * `acronyms` - (acronym, expansion) pairs, ie from `extract_acronyms`
+///
+/// # Returns
+///
+/// The text with every whole word acronym expanded
+pub fn expand_acronyms(text: &str, acronyms: &[(String, String)]) -> String {
+    let mut text = text.to_owned();
+
+    for (acronym, expansion) in acronyms {
+        let definition = format!("({})", acronym);
+        text = text.replace(&definition, "");
+
+        let word = Regex::new(&format!(r"\b{}\b", regex::escape(acronym))).unwrap();
+        text = word.replace_all(&text, expansion.as_str()).into_owned();
+    }
+
+    text
+}
+
+fn find_expansion(acronym: &str, before: &str) -> Option<String> {
+    let letters: Vec<char> = acronym
+        .chars()
+        .filter(|c| c.is_uppercase())
+        .flat_map(char::to_lowercase)
+        .collect();
+
+    let words: Vec<&str> = before
+        .split_whitespace()
+        .rev()
+        .take(letters.len() + MAX_CONNECTORS)
+        .collect::<Vec<&str>>()
+        .into_iter()
+        .rev()
+        .collect();
+
+    // shortest run of words first
+    (0..words.len()).rev().find_map(|start| {
+        let candidate = &words[start..];
+        if CONNECTORS.contains(&candidate[0].to_lowercase().as_str()) {
+            return None;
+        }
+
+        let initials: Vec<char> = candidate
+            .iter()
+            .filter(|word| !CONNECTORS.contains(&word.to_lowercase().as_str()))
+            .filter_map(|word| word.chars().find(|c| c.is_alphanumeric()))
+            .flat_map(char::to_lowercase)
+            .collect();
+
+        (initials == letters).then(|| {
+            candidate
+                .join(" ")
+                .trim_matches(|c: char| !c.is_alphanumeric())
+                .to_owned()
+        })
+    })
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+
+use log::debug;
+
+use super::{
+    client, handle_response, query_similarity_with, rank_pages_by_query_with,
+    titles::normalize_title, Namespace, Page, VectorOptions, WikiError,
+};
+
+/// Known alternative names of pages, ie `"USA"` for `"United States"`, gathered
+/// from redirects and from the anchor text of links. Used to expand queries so
+/// an alias matches the page of its title
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AliasTable {
+    /// Normalized redirect title to the title it redirects to
+    redirects: HashMap<String, String>,
+    /// Normalized anchor text to how often it links to each title
+    anchors: HashMap<String, HashMap<String, usize>>,
+    // longest alias in words, bounds the phrases `expand` looks up
+    max_words: usize,
+}
+
+impl AliasTable {
+    /// Creates an empty alias table
+    pub fn new() -> Self {
+        AliasTable::default()
+    }
+
+    /// Gathers aliases from the anchor text of the article links of pages,
+    /// ie a link reading `"USA"` to `United States`
+    pub fn from_pages<'a>(pages: impl IntoIterator<Item = &'a Page>) -> Self {
+        let mut table = AliasTable::new();
+        table.add_pages(pages);
+
+        table
+    }
+
+    /// Adds the link anchors of more pages, see `from_pages`
+    pub fn add_pages<'a>(&mut self, pages: impl IntoIterator<Item = &'a Page>) {
+        for page in pages {
+            for link in &page.links {
+                if link.namespace != Namespace::Article || link.target.is_empty() {
+                    continue;
+                }
+
+                let alias = normalize_title(&link.title);
+                if alias.is_empty() || alias == normalize_title(&link.target) {
+                    continue;
+                }
+
+                self.max_words = self.max_words.max(alias.split(' ').count());
+                *self
+                    .anchors
+                    .entry(alias)
+                    .or_default()
+                    .entry(link.target.clone())
+                    .or_insert(0) += link.count.max(1);
+            }
+        }
+    }
+
+    /// Adds an alias of a title, taking precedence over link anchors
+    pub fn add_alias(&mut self, alias: &str, title: &str) {
+        let alias = normalize_title(alias);
+        if alias.is_empty() {
+            return;
+        }
+
+        self.max_words = self.max_words.max(alias.split(' ').count());
+        self.redirects.insert(alias, title.trim().to_owned());
+    }
+
+    /// Fetches every redirect to a page and adds it as an alias, see `redirects_from_title`
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - The title of the page
+    ///
+    /// # Returns
+    ///
+    /// Ok(usize) - the number of redirects added
+    /// Err(WikiError) - error if fetching fails
+    pub fn add_redirects(&mut self, title: &str) -> Result<usize, WikiError> {
+        let redirects = redirects_from_title(title)?;
+        for redirect in &redirects {
+            self.add_alias(redirect, title);
+        }
+
+        Ok(redirects.len())
+    }
+
+    /// Number of known aliases
+    pub fn len(&self) -> usize {
+        self.redirects.len()
+            + self
+                .anchors
+                .keys()
+                .filter(|alias| !self.redirects.contains_key(*alias))
+                .count()
+    }
+
+    /// Whether there are no aliases
+    pub fn is_empty(&self) -> bool {
+        self.redirects.is_empty() && self.anchors.is_empty()
+    }
+
+    /// Resolves an alias to its title, ignoring case and accents
+    ///
+    /// # Returns
+    ///
+    /// The redirect target, else the title the alias most often links to.
+    /// None if the alias is unknown
+    pub fn resolve(&self, alias: &str) -> Option<&str> {
+        self.resolve_normalized(&normalize_title(alias))
+    }
+
+    fn resolve_normalized(&self, alias: &str) -> Option<&str> {
+        if let Some(title) = self.redirects.get(alias) {
+            return Some(title);
+        }
+
+        // most links first, then by title so ties do not depend on hash order
+        self.anchors
+            .get(alias)?
+            .iter()
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+            .map(|(title, _)| title.as_str())
+    }
+
+    /// Expands a query with the titles of the aliases in it, ie `"USA economy"`
+    /// becomes `"USA economy United States"`. Longer aliases win over the
+    /// shorter aliases inside them
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query or title to expand
+    ///
+    /// # Returns
+    ///
+    /// The query followed by the title of every alias found in it
+    pub fn expand(&self, query: &str) -> String {
+        let normalized = normalize_title(query);
+        let words: Vec<&str> = normalized.split(' ').filter(|w| !w.is_empty()).collect();
+
+        let mut expanded = query.to_owned();
+        let mut start = 0;
+        while start < words.len() {
+            let longest = self.max_words.min(words.len() - start);
+
+            let found = (1..=longest).rev().find_map(|length| {
+                let phrase = words[start..start + length].join(" ");
+                self.resolve_normalized(&phrase)
+                    .map(|title| (length, title))
+            });
+
+            match found {
+                Some((length, title)) => {
+                    debug!("Expanding alias in query to {}", title);
+                    expanded.push(' ');
+                    expanded.push_str(title);
+                    start += length;
+                }
+                None => start += 1,
+            }
+        }
+
+        expanded
+    }
+
+    /// Get the similarity of a free text query to a page after expanding its
+    /// aliases, see `query_similarity_with`
+    pub fn query_similarity(&self, query: &str, page: &Page, options: &VectorOptions) -> f64 {
+        query_similarity_with(&self.expand(query), page, options)
+    }
+
+    /// Ranks pages by their similarity to a free text query after expanding
+    /// its aliases, see `rank_pages_by_query_with`
+    pub fn rank_pages_by_query(
+        &self,
+        query: &str,
+        pages: &[Page],
+        options: &VectorOptions,
+    ) -> Vec<(usize, f64)> {
+        rank_pages_by_query_with(&self.expand(query), pages, options)
+    }
+}
+
+/// Gets every title redirecting to a page
+///
+/// # Arguments
+/// * `title` - The title of the page
+///
+/// # Returns
+///
+/// Ok(Vec<String>) - the redirect titles, ie `"USA"` for `"United States"`
+/// Err(WikiError) - error if fetching fails or the response is malformed
+pub fn redirects_from_title(title: &str) -> Result<Vec<String>, WikiError> {
+    debug!("redirects_from_title called with title: {}", title);
+    let client = client::get_client();
+
+    let response = client
+        .get("https://en.wikipedia.org/w/api.php")
+        .query(&[
+            ("action", "query"),
+            ("prop", "redirects"),
+            ("rdlimit", "max"),
+            ("rdnamespace", "0"),
+            ("format", "json"),
+            ("formatversion", "2"),
+            ("titles", title),
+        ])
+        .send()
+        .map_err(|err| {
+            WikiError::NetworkingError(format!("Request error with status {:?}", err.status()))
+        })?;
+
+    let body = handle_response(response)?;
+    let json: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|err| WikiError::ParseError(format!("Invalid redirects response: {}", err)))?;
+
+    let redirects = json["query"]["pages"][0]["redirects"]
+        .as_array()
+        .map(|redirects| {
+            redirects
+                .iter()
+                .filter_map(|redirect| redirect["title"].as_str().map(str::to_owned))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(redirects)
+}
@@ -0,0 +1,191 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{corpus::Corpus, count_terms, prepare_document, Page, VectorOptions};
+
+/// The usual term frequency saturation `k1`
+pub const DEFAULT_K1: f64 = 1.2;
+/// The usual length normalization `b`
+pub const DEFAULT_B: f64 = 0.75;
+
+/// BM25 inverse document frequency, `ln(1 + (n - df + 0.5) / (df + 0.5))`.
+/// Never negative, even for terms in more than half of the documents
+///
+/// # Arguments
+///
+/// * `documents` - Number of documents in the collection
+/// * `frequency` - Number of documents containing the term
+pub fn bm25_idf(documents: usize, frequency: usize) -> f64 {
+    let (documents, frequency) = (documents as f64, frequency as f64);
+
+    (1.0 + (documents - frequency + 0.5) / (frequency + 0.5)).ln()
+}
+
+/// The BM25 score of a single term in a document
+///
+/// # Arguments
+///
+/// * `tf` - Number of times the term appears in the document
+/// * `idf` - Inverse document frequency of the term, see `bm25_idf`
+/// * `length` - Number of terms in the document
+/// * `average_length` - Average number of terms per document, 0 ignores length
+/// * `k1` - Term frequency saturation
+/// * `b` - Length normalization [0-1]
+///
+/// # Returns
+///
+/// The weight of the term, 0 if it is not in the document
+pub fn bm25_term(tf: f64, idf: f64, length: f64, average_length: f64, k1: f64, b: f64) -> f64 {
+    let length_norm = if average_length > 0.0 {
+        1.0 - b + b * length / average_length
+    } else {
+        1.0
+    };
+
+    idf * tf * (k1 + 1.0) / (tf + k1 * length_norm)
+}
+
+/// The corpus wide statistics BM25 needs: document frequencies and the
+/// average document length, plus the `k1` and `b` parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorpusStats {
+    options: VectorOptions,
+    documents: usize,
+    average_length: f64,
+    document_frequencies: HashMap<String, usize>,
+    /// Term frequency saturation, higher values let repeated terms keep adding score
+    pub k1: f64,
+    /// Length normalization [0-1], 0 ignores document length entirely
+    pub b: f64,
+}
+
+impl CorpusStats {
+    /// Takes the statistics of a corpus, with the usual `k1 = 1.2` and `b = 0.75`.
+    /// Pages are cleaned with the options of the corpus
+    pub fn from_corpus(corpus: &Corpus) -> Self {
+        let documents = corpus.len();
+        let total_length: usize = corpus.documents().iter().map(|doc| doc.length).sum();
+
+        let document_frequencies = corpus
+            .terms()
+            .iter()
+            .enumerate()
+            .map(|(id, term)| (term.clone(), corpus.document_frequency(id)))
+            .collect();
+
+        CorpusStats {
+            options: corpus.options().clone(),
+            documents,
+            average_length: if documents == 0 {
+                0.0
+            } else {
+                total_length as f64 / documents as f64
+            },
+            document_frequencies,
+            k1: DEFAULT_K1,
+            b: DEFAULT_B,
+        }
+    }
+
+    /// Sets the `k1` and `b` parameters
+    pub fn with_params(mut self, k1: f64, b: f64) -> Self {
+        self.k1 = k1;
+        self.b = b;
+        self
+    }
+
+    /// Number of documents the statistics were taken over
+    pub fn documents(&self) -> usize {
+        self.documents
+    }
+
+    /// Average number of cleaned terms per document
+    pub fn average_length(&self) -> f64 {
+        self.average_length
+    }
+
+    /// BM25 inverse document frequency of a cleaned term, see `bm25_idf`
+    pub fn idf(&self, term: &str) -> f64 {
+        let frequency = self.document_frequencies.get(term).copied().unwrap_or(0);
+
+        bm25_idf(self.documents, frequency)
+    }
+}
+
+impl From<&Corpus> for CorpusStats {
+    fn from(corpus: &Corpus) -> Self {
+        CorpusStats::from_corpus(corpus)
+    }
+}
+
+/// A BM25 query, either a whole page or a list of terms.
+/// Terms are cleaned like page content, so `"Volcanoes"` matches `"volcanoes"`
+#[derive(Debug, Clone, Copy)]
+pub enum Bm25Query<'a> {
+    Page(&'a Page),
+    Terms(&'a [&'a str]),
+}
+
+impl<'a> From<&'a Page> for Bm25Query<'a> {
+    fn from(page: &'a Page) -> Self {
+        Bm25Query::Page(page)
+    }
+}
+
+impl<'a> From<&'a [&'a str]> for Bm25Query<'a> {
+    fn from(terms: &'a [&'a str]) -> Self {
+        Bm25Query::Terms(terms)
+    }
+}
+
+impl<'a, const N: usize> From<&'a [&'a str; N]> for Bm25Query<'a> {
+    fn from(terms: &'a [&'a str; N]) -> Self {
+        Bm25Query::Terms(terms)
+    }
+}
+
+/// Scores a page against a query with Okapi BM25. Unlike cosine similarity
+/// on term frequencies, repeated terms saturate and long pages are not favoured,
+/// which ranks candidates for short queries far better
+///
+/// # Arguments
+///
+/// * `query` - a page or a list of terms, every distinct term counts once
+/// * `page` - the candidate page, it does not have to be in the corpus
+/// * `stats` - statistics of the corpus the candidates come from
+///
+/// # Returns
+///
+/// The BM25 score, 0 if no query term is in the page. Scores are only
+/// comparable between pages scored against the same query and statistics
+pub fn bm25_score<'a>(query: impl Into<Bm25Query<'a>>, page: &Page, stats: &CorpusStats) -> f64 {
+    let query = match query.into() {
+        Bm25Query::Page(query) => prepare_document(query, &stats.options).content,
+        Bm25Query::Terms(terms) => {
+            let query = Page {
+                content: terms.join(" "),
+                ..Default::default()
+            };
+            prepare_document(&query, &stats.options).content
+        }
+    };
+    let query_terms: HashSet<&str> = query.split_whitespace().collect();
+
+    let counts = count_terms(&prepare_document(page, &stats.options).content);
+    let length = counts.values().sum::<usize>() as f64;
+
+    query_terms
+        .into_iter()
+        .filter_map(|term| {
+            let tf = *counts.get(term)? as f64;
+
+            Some(bm25_term(
+                tf,
+                stats.idf(term),
+                length,
+                stats.average_length,
+                stats.k1,
+                stats.b,
+            ))
+        })
+        .sum()
+}
@@ -0,0 +1,94 @@
+use std::collections::{BTreeSet, HashMap};
+
+use log::debug;
+
+use super::Page;
+
+/// Pairwise category co-membership counts across a set of pages
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CategoryOverlap {
+    /// Every category seen in the pages, sorted by name.
+    /// Row and column `i` of `counts` belong to `categories[i]`
+    pub categories: Vec<String>,
+    /// `counts[i][j]` is the number of pages in both category `i` and category `j`.
+    /// The diagonal holds the number of pages in each category
+    pub counts: Vec<Vec<usize>>,
+}
+
+/// Builds the category co-membership matrix of a set of pages
+///
+/// # Arguments
+///
+/// * `pages` - The pages to profile
+///
+/// # Returns
+///
+/// A square matrix of how many pages each pair of categories share
+pub fn category_overlap_matrix(pages: &[Page]) -> CategoryOverlap {
+    debug!("Building category overlap matrix for {} pages", pages.len());
+
+    let categories: Vec<String> = pages
+        .iter()
+        .flat_map(|page| page.categories.iter().cloned())
+        .collect::<BTreeSet<String>>()
+        .into_iter()
+        .collect();
+
+    let index: HashMap<&str, usize> = categories
+        .iter()
+        .enumerate()
+        .map(|(i, category)| (category.as_str(), i))
+        .collect();
+
+    let mut counts = vec![vec![0; categories.len()]; categories.len()];
+
+    for page in pages {
+        // a page listing a category twice still only counts once
+        let ids: BTreeSet<usize> = page
+            .categories
+            .iter()
+            .map(|category| index[category.as_str()])
+            .collect();
+
+        for &i in &ids {
+            for &j in &ids {
+                counts[i][j] += 1;
+            }
+        }
+    }
+
+    CategoryOverlap { categories, counts }
+}
+
+/// Gets the categories shared by the most pages
+///
+/// # Arguments
+///
+/// * `pages` - The pages to profile
+/// * `n` - The max number of categories to return
+///
+/// # Returns
+///
+/// Up to `n` (category, page count) pairs, most common first.
+/// Ties are broken by category name
+pub fn most_common_categories(pages: &[Page], n: usize) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+
+    for page in pages {
+        let unique: BTreeSet<&str> = page.categories.iter().map(|c| c.as_str()).collect();
+
+        for category in unique {
+            *counts.entry(category).or_insert(0) += 1;
+        }
+    }
+
+    let mut common: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(category, count)| (category.to_owned(), count))
+        .collect();
+
+    common.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    common.truncate(n);
+
+    common
+}
@@ -0,0 +1,275 @@
+use lazy_static::lazy_static;
+use log::debug;
+use regex::Regex;
+
+use super::Page;
+
+/// Coarse type of the subject a page is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EntityType {
+    Person,
+    Place,
+    Organization,
+    /// Creative works: films, books, albums, games, etc
+    Work,
+    /// Anything that does not look like one of the other types
+    Concept,
+}
+
+/// Signals that vote for a single entity type
+struct TypeRule {
+    entity_type: EntityType,
+    /// Substrings of the infobox type classes
+    infobox: &'static [&'static str],
+    /// Substrings of (lowercased) category names
+    categories: &'static [&'static str],
+    /// Nouns that follow "is a" in the lead sentence
+    lead_nouns: &'static [&'static str],
+}
+
+const RULES: [TypeRule; 4] = [
+    TypeRule {
+        entity_type: EntityType::Person,
+        infobox: &[
+            "biography",
+            "ib-person",
+            "ib-officeholder",
+            "ib-musical-artist",
+        ],
+        categories: &["births", "deaths", "living people", "people from"],
+        lead_nouns: &[
+            "politician",
+            "writer",
+            "author",
+            "actor",
+            "actress",
+            "singer",
+            "musician",
+            "scientist",
+            "philosopher",
+            "painter",
+            "footballer",
+            "player",
+            "poet",
+            "composer",
+            "businessman",
+            "businesswoman",
+            "lawyer",
+            "journalist",
+            "general",
+            "king",
+            "queen",
+        ],
+    },
+    TypeRule {
+        entity_type: EntityType::Place,
+        infobox: &[
+            "ib-settlement",
+            "ib-country",
+            "geography",
+            "ib-river",
+            "ib-mountain",
+            "ib-island",
+            "ib-building",
+            "ib-park",
+        ],
+        categories: &[
+            "cities",
+            "towns",
+            "villages",
+            "countries",
+            "capitals",
+            "populated places",
+            "rivers",
+            "mountains",
+            "islands",
+            "regions of",
+            "buildings and structures",
+        ],
+        lead_nouns: &[
+            "city",
+            "town",
+            "village",
+            "country",
+            "capital",
+            "commune",
+            "municipality",
+            "river",
+            "mountain",
+            "island",
+            "region",
+            "province",
+            "state",
+            "county",
+            "lake",
+        ],
+    },
+    TypeRule {
+        entity_type: EntityType::Organization,
+        infobox: &[
+            "ib-company",
+            "ib-organization",
+            "ib-university",
+            "ib-political-party",
+            "ib-football-club",
+            "ib-school",
+        ],
+        categories: &[
+            "companies",
+            "organizations",
+            "organisations",
+            "universities",
+            "political parties",
+            "football clubs",
+            "agencies",
+            "brands",
+        ],
+        lead_nouns: &[
+            "company",
+            "corporation",
+            "organization",
+            "organisation",
+            "university",
+            "party",
+            "club",
+            "agency",
+            "institution",
+            "manufacturer",
+            "band",
+        ],
+    },
+    TypeRule {
+        entity_type: EntityType::Work,
+        infobox: &[
+            "ib-film",
+            "ib-album",
+            "ib-book",
+            "ib-television",
+            "ib-video-game",
+            "ib-song",
+            "ib-artwork",
+        ],
+        categories: &[
+            "films",
+            "novels",
+            "albums",
+            "songs",
+            "video games",
+            "television series",
+            "books",
+            "paintings",
+            "operas",
+        ],
+        lead_nouns: &[
+            "film", "novel", "album", "song", "book", "series", "game", "painting", "opera",
+            "play", "poem", "sitcom",
+        ],
+    },
+];
+
+// votes per signal, the infobox is the most deliberate signal an editor leaves
+const INFOBOX_WEIGHT: usize = 3;
+const LEAD_WEIGHT: usize = 2;
+const CATEGORY_WEIGHT: usize = 1;
+
+// words that end the head of a lead definition
+const DEFINITION_STOPS: [&str; 9] = [
+    "by", "of", "from", "in", "for", "who", "that", "which", "based",
+];
+
+// the lead is somewhere near the top, after the infobox text
+const LEAD_WINDOW: usize = 2000;
+
+lazy_static! {
+    static ref LEAD_DEFINITION: Regex =
+        Regex::new(r"\b(?:is|was|are|were) (?:a|an|the) ((?:[\w-]+ ){0,4}[\w-]+)").unwrap();
+    static ref BORN: Regex = Regex::new(r"\(born\b").unwrap();
+}
+
+/// Guesses the type of a named entity from the nouns in its name, using the
+/// same nouns as lead sentences, ie `"Seine River"` is a place.
+/// The head noun, the last word, is tried first
+pub(crate) fn type_from_name(name: &str) -> Option<EntityType> {
+    let words: Vec<String> = name.split_whitespace().map(str::to_lowercase).collect();
+
+    words.iter().rev().find_map(|word| {
+        RULES
+            .iter()
+            .find(|rule| rule.lead_nouns.contains(&word.as_str()))
+            .map(|rule| rule.entity_type)
+    })
+}
+
+impl Page {
+    /// Heuristically classifies what kind of subject this page is about.
+    /// Uses the infobox type, categories and the lead sentence, in that order of weight
+    ///
+    /// # Returns
+    ///
+    /// The best matching EntityType, `EntityType::Concept` if nothing matched
+    pub fn entity_type(&self) -> EntityType {
+        let mut votes = [0usize; RULES.len()];
+
+        if let Some(infobox_type) = &self.infobox_type {
+            let infobox_type = infobox_type.to_ascii_lowercase();
+
+            for (i, rule) in RULES.iter().enumerate() {
+                if rule
+                    .infobox
+                    .iter()
+                    .any(|class| infobox_type.contains(class))
+                {
+                    votes[i] += INFOBOX_WEIGHT;
+                }
+            }
+        }
+
+        for category in &self.categories {
+            let category = category.to_lowercase();
+
+            for (i, rule) in RULES.iter().enumerate() {
+                if rule.categories.iter().any(|c| category.contains(c)) {
+                    votes[i] += CATEGORY_WEIGHT;
+                }
+            }
+        }
+
+        let lead: String = self.content.chars().take(LEAD_WINDOW).collect();
+
+        if BORN.is_match(&lead) {
+            votes[0] += LEAD_WEIGHT;
+        }
+
+        // only the first definition counts, later ones describe other things
+        if let Some(definition) = LEAD_DEFINITION.captures(&lead) {
+            let definition = definition[1].to_lowercase();
+
+            // "a novel by Stephen King" is about the novel, not the king
+            let head: Vec<&str> = definition
+                .split_whitespace()
+                .take_while(|word| !DEFINITION_STOPS.contains(word))
+                .collect();
+
+            for (i, rule) in RULES.iter().enumerate() {
+                if head.iter().any(|word| rule.lead_nouns.contains(word)) {
+                    votes[i] += LEAD_WEIGHT;
+                }
+            }
+        }
+
+        debug!("Entity type votes for {}: {:?}", self.title, votes);
+
+        // first rule wins ties
+        let best = votes
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, &count)| count)
+            .filter(|(_, &count)| count > 0);
+
+        match best {
+            Some((i, _)) => RULES[i].entity_type,
+            None => EntityType::Concept,
+        }
+    }
+}
@@ -0,0 +1,620 @@
+use log::debug;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{corpus::Corpus, hashing::splitmix64};
+
+const DEFAULT_MAX_ITERATIONS: usize = 100;
+
+/// A document vector holding only its non zero (term id, weight) entries,
+/// scaled to unit length
+type SparseVec = Vec<(usize, f64)>;
+
+/// The cluster a document of a corpus was put in
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterAssignment {
+    /// Document id in the corpus
+    pub document: usize,
+    pub title: String,
+    pub cluster: usize,
+    /// Cosine similarity of the document to its cluster's centroid
+    pub similarity: f64,
+}
+
+/// Settings of `kmeans_with`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KMeansOptions {
+    /// Number of clusters, must not be 0
+    pub k: usize,
+    /// Stop after this many iterations even if documents still move between clusters
+    pub max_iterations: usize,
+    /// Seed of the k-means++ initialization, the same seed and corpus always
+    /// give the same clusters
+    pub seed: u64,
+}
+
+impl Default for KMeansOptions {
+    fn default() -> Self {
+        KMeansOptions {
+            k: 8,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            seed: 0,
+        }
+    }
+}
+
+/// The result of k-means clustering
+#[derive(Debug, Clone, PartialEq)]
+pub struct KMeans {
+    /// One assignment per document, in document id order
+    pub assignments: Vec<ClusterAssignment>,
+    /// Unit length centroid of every cluster over the corpus vocabulary
+    pub centroids: Vec<Vec<f64>>,
+    /// Number of iterations run
+    pub iterations: usize,
+}
+
+impl KMeans {
+    /// The centroid of a cluster, one weight per corpus term
+    pub fn centroid(&self, cluster: usize) -> &[f64] {
+        &self.centroids[cluster]
+    }
+
+    /// The document ids in a cluster, most central first
+    pub fn members(&self, cluster: usize) -> Vec<usize> {
+        let mut members: Vec<&ClusterAssignment> = self
+            .assignments
+            .iter()
+            .filter(|assignment| assignment.cluster == cluster)
+            .collect();
+        members.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+
+        members
+            .iter()
+            .map(|assignment| assignment.document)
+            .collect()
+    }
+
+    /// The heaviest terms of a cluster's centroid, ie to label the cluster
+    ///
+    /// # Arguments
+    ///
+    /// * `corpus` - The corpus that was clustered
+    /// * `cluster` - The cluster
+    /// * `n` - The maximum number of terms
+    ///
+    /// # Returns
+    ///
+    /// Up to `n` (term, weight) pairs, heaviest first
+    pub fn top_terms(&self, corpus: &Corpus, cluster: usize, n: usize) -> Vec<(String, f64)> {
+        let mut terms: Vec<(usize, f64)> = self.centroids[cluster]
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|&(_, weight)| weight > 0.0)
+            .collect();
+        terms.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        terms
+            .into_iter()
+            .take(n)
+            .map(|(term, weight)| (corpus.terms()[term].clone(), weight))
+            .collect()
+    }
+}
+
+/// Groups the documents of a corpus into `k` clusters of similar pages with
+/// spherical k-means over their TF-IDF vectors, see `kmeans_with`
+///
+/// # Arguments
+///
+/// * `corpus` - The pages to cluster
+/// * `k` - Number of clusters, must not be 0
+///
+/// # Returns
+///
+/// One assignment per document, in document id order
+pub fn kmeans(corpus: &Corpus, k: usize) -> Vec<ClusterAssignment> {
+    kmeans_with(
+        corpus,
+        &KMeansOptions {
+            k,
+            ..Default::default()
+        },
+    )
+    .assignments
+}
+
+/// Groups the documents of a corpus into clusters with spherical k-means:
+/// documents are compared by the cosine similarity of their TF-IDF vectors
+/// and centroids are seeded with k-means++
+///
+/// # Arguments
+///
+/// * `corpus` - The pages to cluster
+/// * `options` - Number of clusters, iterations and seed
+///
+/// # Returns
+///
+/// The assignments and the centroids. With fewer documents than clusters,
+/// every document gets its own cluster
+pub fn kmeans_with(corpus: &Corpus, options: &KMeansOptions) -> KMeans {
+    assert!(options.k > 0, "k-means needs at least one cluster");
+    let vectors = unit_vectors(corpus);
+    let k = options.k.min(vectors.len());
+    let dimensions = corpus.terms().len();
+
+    let mut centroids: Vec<Vec<f64>> = seed_centroids(&vectors, k, options.seed)
+        .into_iter()
+        .map(|doc| to_dense(&vectors[doc], dimensions))
+        .collect();
+
+    let mut assignments: Vec<(usize, f64)> = vec![(usize::MAX, 0.0); vectors.len()];
+    let mut iterations = 0;
+
+    while iterations < options.max_iterations {
+        iterations += 1;
+
+        let next: Vec<(usize, f64)> = vectors
+            .par_iter()
+            .map(|vector| nearest(vector, &centroids))
+            .collect();
+
+        let moved = next
+            .iter()
+            .zip(&assignments)
+            .any(|(next, current)| next.0 != current.0);
+        assignments = next;
+
+        if !moved {
+            break;
+        }
+
+        centroids = (0..k)
+            .map(|cluster| {
+                let mut centroid = vec![0.0; dimensions];
+                for (vector, _) in vectors
+                    .iter()
+                    .zip(&assignments)
+                    .filter(|(_, assignment)| assignment.0 == cluster)
+                {
+                    for &(term, weight) in vector {
+                        centroid[term] += weight;
+                    }
+                }
+                normalize(&mut centroid);
+                centroid
+            })
+            .collect();
+    }
+    debug!("k-means finished after {} iterations", iterations);
+
+    let assignments = assignments
+        .into_iter()
+        .enumerate()
+        .map(|(document, (cluster, similarity))| ClusterAssignment {
+            document,
+            title: corpus.documents()[document].title.clone(),
+            cluster,
+            similarity,
+        })
+        .collect();
+
+    KMeans {
+        assignments,
+        centroids,
+        iterations,
+    }
+}
+
+/// The mean silhouette of a clustering, how much closer documents are to their
+/// own cluster than to the nearest other one, under cosine distance.
+/// Useful to compare clusterings with different `k`
+///
+/// # Arguments
+///
+/// * `corpus` - The corpus that was clustered
+/// * `assignments` - One assignment per document
+///
+/// # Returns
+///
+/// The score [-1, 1], higher is better. 0 with fewer than two clusters
+pub fn silhouette_score(corpus: &Corpus, assignments: &[ClusterAssignment]) -> f64 {
+    let vectors = unit_vectors(corpus);
+    let clusters = assignments
+        .iter()
+        .map(|assignment| assignment.cluster + 1)
+        .max()
+        .unwrap_or(0);
+    if clusters < 2 || vectors.is_empty() {
+        return 0.0;
+    }
+
+    let scores: Vec<f64> = assignments
+        .par_iter()
+        .map(|assignment| {
+            let mut distance = vec![0.0; clusters];
+            let mut size = vec![0usize; clusters];
+
+            for other in assignments {
+                if other.document != assignment.document {
+                    distance[other.cluster] +=
+                        1.0 - sparse_dot(&vectors[assignment.document], &vectors[other.document]);
+                }
+                size[other.cluster] += 1;
+            }
+
+            // the document itself does not count towards its own cluster
+            size[assignment.cluster] -= 1;
+            if size[assignment.cluster] == 0 {
+                return 0.0;
+            }
+
+            let own = distance[assignment.cluster] / size[assignment.cluster] as f64;
+            let nearest_other = (0..clusters)
+                .filter(|&cluster| cluster != assignment.cluster && size[cluster] > 0)
+                .map(|cluster| distance[cluster] / size[cluster] as f64)
+                .fold(f64::INFINITY, f64::min);
+
+            if nearest_other.is_infinite() || own.max(nearest_other) == 0.0 {
+                0.0
+            } else {
+                (nearest_other - own) / own.max(nearest_other)
+            }
+        })
+        .collect();
+
+    scores.iter().sum::<f64>() / scores.len() as f64
+}
+
+/// How the distance between two clusters is measured when merging them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Linkage {
+    /// Distance of the closest pair of documents, tends to chain clusters together
+    Single,
+    /// Distance of the farthest pair of documents, gives compact clusters
+    Complete,
+    /// Mean distance over all pairs of documents (UPGMA)
+    #[default]
+    Average,
+}
+
+/// One merge of two clusters in a `Dendrogram`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Merge {
+    /// Node ids of the merged clusters. Ids below the number of documents are
+    /// documents, merge `i` creates node `documents + i`
+    pub left: usize,
+    pub right: usize,
+    /// Cosine distance [0-2] between the clusters when merged
+    pub distance: f64,
+    /// Number of documents in the new cluster
+    pub size: usize,
+}
+
+/// The merge tree of hierarchical clustering, in the layout of a scipy linkage matrix.
+/// Serializes to JSON for plotting elsewhere, see also `to_newick`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dendrogram {
+    /// Titles of the documents, the leaves
+    pub titles: Vec<String>,
+    /// Merges in order, closest first
+    pub merges: Vec<Merge>,
+}
+
+impl Dendrogram {
+    /// Cuts the tree at a distance, keeping only merges at or below it
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The largest cosine distance within a cluster
+    ///
+    /// # Returns
+    ///
+    /// The cluster of every document, in document id order. Clusters are
+    /// numbered from 0 in order of their first document
+    pub fn cut(&self, threshold: f64) -> Vec<usize> {
+        let kept = self
+            .merges
+            .iter()
+            .take_while(|merge| merge.distance <= threshold)
+            .count();
+
+        self.labels(kept)
+    }
+
+    /// Cuts the tree into (at most) `k` clusters
+    ///
+    /// # Returns
+    ///
+    /// The cluster of every document, in document id order
+    pub fn cut_into(&self, k: usize) -> Vec<usize> {
+        let documents = self.titles.len();
+        let kept = documents.saturating_sub(k.max(1)).min(self.merges.len());
+
+        self.labels(kept)
+    }
+
+    /// Writes the tree in Newick format, read by most tree viewers.
+    /// Branch lengths are half the merge distance
+    pub fn to_newick(&self) -> String {
+        let documents = self.titles.len();
+        let mut nodes: Vec<(String, f64)> = self
+            .titles
+            .iter()
+            .map(|title| (newick_label(title), 0.0))
+            .collect();
+
+        for merge in &self.merges {
+            let height = merge.distance / 2.0;
+            let (left, left_height) = nodes[merge.left].clone();
+            let (right, right_height) = nodes[merge.right].clone();
+
+            nodes.push((
+                format!(
+                    "({}:{},{}:{})",
+                    left,
+                    height - left_height,
+                    right,
+                    height - right_height
+                ),
+                height,
+            ));
+        }
+
+        // unmerged trees become one root with every remaining subtree
+        let mut merged = vec![false; nodes.len()];
+        for merge in &self.merges {
+            merged[merge.left] = true;
+            merged[merge.right] = true;
+        }
+        let roots: Vec<&str> = nodes
+            .iter()
+            .zip(&merged)
+            .filter(|(_, &merged)| !merged)
+            .map(|((node, _), _)| node.as_str())
+            .collect();
+
+        match roots.as_slice() {
+            [root] if documents > 0 => format!("{};", root),
+            _ => format!("({});", roots.join(",")),
+        }
+    }
+
+    fn labels(&self, merges: usize) -> Vec<usize> {
+        let documents = self.titles.len();
+        let mut parent: Vec<usize> = (0..documents + merges).collect();
+
+        fn find(parent: &mut [usize], node: usize) -> usize {
+            let mut root = node;
+            while parent[root] != root {
+                root = parent[root];
+            }
+            parent[node] = root;
+            root
+        }
+
+        for (i, merge) in self.merges.iter().take(merges).enumerate() {
+            parent[merge.left] = documents + i;
+            parent[merge.right] = documents + i;
+        }
+
+        let mut clusters: Vec<Option<usize>> = vec![None; parent.len()];
+        let mut next = 0;
+        (0..documents)
+            .map(|doc| {
+                let root = find(&mut parent, doc);
+                *clusters[root].get_or_insert_with(|| {
+                    next += 1;
+                    next - 1
+                })
+            })
+            .collect()
+    }
+}
+
+/// Hierarchical agglomerative clustering of the documents of a corpus by the
+/// cosine distance of their TF-IDF vectors. The closest clusters are merged until
+/// one is left, so the tree can be cut at any distance without choosing `k` first.
+/// Takes quadratic memory, fine for crawls of a few thousand pages
+///
+/// # Arguments
+///
+/// * `corpus` - The pages to cluster
+/// * `linkage` - How distances between clusters are measured
+///
+/// # Returns
+///
+/// The full merge tree
+pub fn hierarchical(corpus: &Corpus, linkage: Linkage) -> Dendrogram {
+    let vectors = unit_vectors(corpus);
+    let n = vectors.len();
+    debug!("Clustering {} documents hierarchically", n);
+
+    let mut distances: Vec<Vec<f64>> = vectors
+        .par_iter()
+        .map(|a| vectors.iter().map(|b| 1.0 - sparse_dot(a, b)).collect())
+        .collect();
+
+    // node id and size of the cluster in each slot, None once merged away
+    let mut slots: Vec<Option<(usize, usize)>> = (0..n).map(|doc| Some((doc, 1))).collect();
+    let mut merges = Vec::with_capacity(n.saturating_sub(1));
+
+    while merges.len() + 1 < n {
+        let mut best: Option<(usize, usize, f64)> = None;
+        for i in 0..n {
+            if slots[i].is_none() {
+                continue;
+            }
+            for j in i + 1..n {
+                if slots[j].is_some() && best.is_none_or(|best| distances[i][j] < best.2) {
+                    best = Some((i, j, distances[i][j]));
+                }
+            }
+        }
+        let Some((i, j, distance)) = best else {
+            break;
+        };
+
+        let (left, left_size) = slots[i].unwrap();
+        let (right, right_size) = slots[j].unwrap();
+        merges.push(Merge {
+            left,
+            right,
+            distance,
+            size: left_size + right_size,
+        });
+
+        // Lance-Williams update, the merged cluster takes slot i
+        for k in 0..n {
+            if k == i || k == j || slots[k].is_none() {
+                continue;
+            }
+            let updated = match linkage {
+                Linkage::Single => distances[i][k].min(distances[j][k]),
+                Linkage::Complete => distances[i][k].max(distances[j][k]),
+                Linkage::Average => {
+                    (distances[i][k] * left_size as f64 + distances[j][k] * right_size as f64)
+                        / (left_size + right_size) as f64
+                }
+            };
+            distances[i][k] = updated;
+            distances[k][i] = updated;
+        }
+
+        slots[i] = Some((n + merges.len() - 1, left_size + right_size));
+        slots[j] = None;
+    }
+
+    Dendrogram {
+        titles: corpus
+            .documents()
+            .iter()
+            .map(|doc| doc.title.clone())
+            .collect(),
+        merges,
+    }
+}
+
+// quoted labels keep titles with spaces, commas or parentheses intact
+fn newick_label(title: &str) -> String {
+    format!("'{}'", title.replace('\'', "''"))
+}
+
+fn unit_vectors(corpus: &Corpus) -> Vec<SparseVec> {
+    (0..corpus.len())
+        .map(|doc| {
+            let mut vector = corpus.tf_idf_row(doc);
+            let norm = vector.iter().map(|(_, w)| w * w).sum::<f64>().sqrt();
+            if norm > 0.0 {
+                vector.iter_mut().for_each(|(_, weight)| *weight /= norm);
+            }
+            vector
+        })
+        .collect()
+}
+
+// both vectors are sorted by term id, walk them together
+pub(crate) fn sparse_dot(a: &SparseVec, b: &SparseVec) -> f64 {
+    let (mut i, mut j, mut sum) = (0, 0, 0.0);
+
+    while i < a.len() && j < b.len() {
+        match a[i].0.cmp(&b[j].0) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                sum += a[i].1 * b[j].1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+
+    sum
+}
+
+/// k-means++: each next seed is drawn with probability proportional to its
+/// squared distance from the closest seed so far, which spreads the seeds out
+fn seed_centroids(vectors: &[SparseVec], k: usize, seed: u64) -> Vec<usize> {
+    let mut random = seed;
+    let mut next_random = || {
+        random = splitmix64(random);
+        (random >> 11) as f64 / (1u64 << 53) as f64
+    };
+
+    let mut seeds = Vec::with_capacity(k);
+    if vectors.is_empty() {
+        return seeds;
+    }
+    seeds.push(((next_random() * vectors.len() as f64) as usize).min(vectors.len() - 1));
+
+    // squared euclidean distance of unit vectors, 2 - 2 cos
+    let mut closest: Vec<f64> = vectors
+        .iter()
+        .map(|vector| 2.0 - 2.0 * sparse_dot(vector, &vectors[seeds[0]]))
+        .collect();
+
+    while seeds.len() < k {
+        let total: f64 = closest.iter().map(|distance| distance.max(0.0)).sum();
+
+        let next = if total <= 0.0 {
+            // every document duplicates a seed, take any unused one
+            (0..vectors.len()).find(|doc| !seeds.contains(doc)).unwrap()
+        } else {
+            let mut target = next_random() * total;
+            let mut chosen = vectors.len() - 1;
+            for (doc, distance) in closest.iter().enumerate() {
+                target -= distance.max(0.0);
+                if target <= 0.0 && !seeds.contains(&doc) {
+                    chosen = doc;
+                    break;
+                }
+            }
+            chosen
+        };
+
+        seeds.push(next);
+        for (doc, distance) in closest.iter_mut().enumerate() {
+            *distance = distance.min(2.0 - 2.0 * sparse_dot(&vectors[doc], &vectors[next]));
+        }
+    }
+
+    seeds
+}
+
+// (closest centroid, cosine similarity to it)
+fn nearest(vector: &SparseVec, centroids: &[Vec<f64>]) -> (usize, f64) {
+    centroids
+        .iter()
+        .map(|centroid| {
+            vector
+                .iter()
+                .map(|&(term, w)| w * centroid[term])
+                .sum::<f64>()
+        })
+        .enumerate()
+        .fold((0, f64::NEG_INFINITY), |best, (cluster, sim)| {
+            if sim > best.1 {
+                (cluster, sim)
+            } else {
+                best
+            }
+        })
+}
+
+fn to_dense(vector: &SparseVec, dimensions: usize) -> Vec<f64> {
+    let mut dense = vec![0.0; dimensions];
+    for &(term, weight) in vector {
+        dense[term] = weight;
+    }
+
+    dense
+}
+
+fn normalize(vector: &mut [f64]) {
+    let norm = vector.iter().map(|value| value * value).sum::<f64>().sqrt();
+
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|value| *value /= norm);
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::{HashMap, HashSet};
+
+use super::corpus::Corpus;
+
+/// Finds the significant bigrams of a corpus, pairs of adjacent terms that occur
+/// together far more often than their separate frequencies predict
+/// (`"european union"`). Scored with Dunning's log-likelihood ratio,
+/// which stays reliable for the rare terms PMI overrates
+///
+/// # Arguments
+///
+/// * `corpus` - the corpus to search
+/// * `min_count` - how many times a pair must occur to be considered
+///
+/// # Returns
+///
+/// (first term, second term, log-likelihood ratio) for every positively
+/// associated pair, highest ratio first
+pub fn collocations(corpus: &Corpus, min_count: usize) -> Vec<(String, String, f64)> {
+    let bigrams = corpus.bigrams();
+    let total: usize = bigrams.values().sum();
+
+    // how often every term starts and ends a bigram
+    let mut firsts: HashMap<usize, usize> = HashMap::new();
+    let mut seconds: HashMap<usize, usize> = HashMap::new();
+    for (&(a, b), &count) in bigrams {
+        *firsts.entry(a).or_insert(0) += count;
+        *seconds.entry(b).or_insert(0) += count;
+    }
+
+    let terms = corpus.terms();
+    let mut found: Vec<(String, String, f64)> = bigrams
+        .iter()
+        .filter(|(_, &count)| count >= min_count.max(1))
+        .filter_map(|(&(a, b), &count)| {
+            let k11 = count as f64;
+            let k12 = (firsts[&a] - count) as f64;
+            let k21 = (seconds[&b] - count) as f64;
+            let k22 = total as f64 - k11 - k12 - k21;
+
+            // a strong ratio can also mean the terms avoid each other
+            let expected = (k11 + k12) * (k11 + k21) / total as f64;
+            if k11 <= expected {
+                return None;
+            }
+
+            let score = log_likelihood_ratio(k11, k12, k21, k22);
+            Some((terms[a].clone(), terms[b].clone(), score))
+        })
+        .collect();
+
+    found.sort_by(|(a1, a2, a), (b1, b2, b)| {
+        b.total_cmp(a)
+            .then_with(|| a1.cmp(b1))
+            .then_with(|| a2.cmp(b2))
+    });
+
+    found
+}
+
+/// Joins collocations in cleaned content into single terms, `"european union"`
+/// becomes `"european_union"`, so they count as one term when vectorized
+///
+/// # Arguments
+///
+/// * `content` - cleaned content, see `clean_document`
+/// * `collocations` - the pairs to join, ie from `collocations`
+///
+/// # Returns
+///
+/// The content with every listed pair joined, scanning left to right
+pub fn merge_collocations(content: &str, collocations: &[(String, String, f64)]) -> String {
+    let pairs: HashSet<(&str, &str)> = collocations
+        .iter()
+        .map(|(a, b, _)| (a.as_str(), b.as_str()))
+        .collect();
+
+    let words: Vec<&str> = content.split_whitespace().collect();
+    let mut merged: Vec<String> = Vec::with_capacity(words.len());
+
+    let mut i = 0;
+    while i < words.len() {
+        if i + 1 < words.len() && pairs.contains(&(words[i], words[i + 1])) {
+            merged.push(format!("{}_{}", words[i], words[i + 1]));
+            i += 2;
+        } else {
+            merged.push(words[i].to_owned());
+            i += 1;
+        }
+    }
+
+    merged.join(" ")
+}
+
+fn x_log_x(x: f64) -> f64 {
+    if x <= 0.0 {
+        0.0
+    } else {
+        x * x.ln()
+    }
+}
+
+fn entropy(counts: &[f64]) -> f64 {
+    x_log_x(counts.iter().sum()) - counts.iter().map(|&k| x_log_x(k)).sum::<f64>()
+}
+
+// Dunning's G^2 over the 2x2 contingency table of a pair
+fn log_likelihood_ratio(k11: f64, k12: f64, k21: f64, k22: f64) -> f64 {
+    let matrix = entropy(&[k11, k12, k21, k22]);
+    let rows = entropy(&[k11 + k12, k21 + k22]);
+    let columns = entropy(&[k11 + k21, k12 + k22]);
+
+    (2.0 * (rows + columns - matrix)).max(0.0)
+}
@@ -0,0 +1,62 @@
+use super::{
+    tokenize::{Tokenizer, UnicodeTokenizer},
+    Page,
+};
+
+/// A single occurrence of a term with the words around it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet {
+    /// Text before the term, up to `window` words
+    pub left: String,
+    /// The term as written in the content
+    pub keyword: String,
+    /// Text after the term, up to `window` words
+    pub right: String,
+    /// Byte offset of the term in `Page::content`
+    pub start: usize,
+    /// Byte offset just past the term in `Page::content`
+    pub end: usize,
+}
+
+/// Keyword in context: finds every occurrence of a term in a page's content,
+/// ie for showing the passages behind a shared term of two similar pages
+///
+/// # Arguments
+///
+/// * `page` - the page to search
+/// * `term` - the word to find, matched case insensitively against whole words
+/// * `window` - how many words of context to keep on each side
+///
+/// # Returns
+///
+/// A snippet for every occurrence, in content order
+pub fn concordance(page: &Page, term: &str, window: usize) -> Vec<Snippet> {
+    let content = &page.content;
+    let term = term.trim().to_lowercase();
+    let tokens = UnicodeTokenizer.tokenize(content);
+    let end_of = |i: usize| tokens[i].offset + tokens[i].text.len();
+
+    tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, token)| token.text.to_lowercase() == term)
+        .map(|(i, token)| {
+            let start = token.offset;
+            let end = end_of(i);
+
+            let left_start = tokens[i.saturating_sub(window)].offset;
+            let right_end = match window {
+                0 => end,
+                _ => end_of((i + window).min(tokens.len() - 1)),
+            };
+
+            Snippet {
+                left: content[left_start..start].trim().to_owned(),
+                keyword: token.text.clone(),
+                right: content[end..right_end].trim().to_owned(),
+                start,
+                end,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,374 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use log::debug;
+
+use super::{
+    cosine_sim, count_terms, prepare_document, to_f32, vocabulary::Vocabulary, Page, VectorOptions,
+    WikiError,
+};
+
+/// A page inside a corpus, reduced to its term counts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorpusDocument {
+    pub title: String,
+    /// (term id, count) for every term in the page, sorted by term id
+    pub term_counts: Vec<(usize, usize)>,
+    /// Total number of terms in the page after cleaning
+    pub length: usize,
+    /// Term id of every term in the page in order, for positional queries
+    pub sequence: Vec<usize>,
+}
+
+/// A collection of pages sharing one growing vocabulary.
+/// Unlike the per call vocabularies of `get_page_similarity`, term ids in a
+/// corpus stay stable as pages are added. The corpus also tracks document
+/// frequencies, so pages can be weighted by TF-IDF against it
+#[derive(Debug, Clone, Default)]
+pub struct Corpus {
+    options: VectorOptions,
+    vocab: Vocabulary,
+    documents: Vec<CorpusDocument>,
+    /// Number of documents containing each term, indexed by term id
+    document_frequencies: Vec<usize>,
+    /// Count of every pair of adjacent terms, over all documents
+    bigrams: HashMap<(usize, usize), usize>,
+}
+
+impl Corpus {
+    /// Creates an empty corpus with default vectorizer options
+    pub fn new() -> Self {
+        Corpus::default()
+    }
+
+    /// Creates an empty corpus, cleaning pages with the given options
+    pub fn with_options(options: VectorOptions) -> Self {
+        Corpus {
+            options,
+            ..Default::default()
+        }
+    }
+
+    /// Adds a page to the corpus, extending the vocabulary with its new terms
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page to add
+    ///
+    /// # Returns
+    ///
+    /// The document id of the page, its index in `documents`
+    pub fn add_page(&mut self, page: &Page) -> usize {
+        debug!("Adding {} to corpus", page.title);
+        let content = prepare_document(page, &self.options).content;
+
+        let mut counts: HashMap<usize, usize> = HashMap::new();
+        let mut sequence = Vec::new();
+        let mut previous = None;
+        for word in content.split_whitespace() {
+            // the corpus vocabulary is never frozen
+            let id = self.vocab.add_term(word).unwrap();
+            if id == self.document_frequencies.len() {
+                self.document_frequencies.push(0);
+            }
+
+            *counts.entry(id).or_insert(0) += 1;
+            sequence.push(id);
+
+            if let Some(previous) = previous {
+                *self.bigrams.entry((previous, id)).or_insert(0) += 1;
+            }
+            previous = Some(id);
+        }
+
+        let mut term_counts: Vec<(usize, usize)> = counts.into_iter().collect();
+        term_counts.sort_unstable();
+
+        for &(id, _) in &term_counts {
+            self.document_frequencies[id] += 1;
+        }
+
+        self.documents.push(CorpusDocument {
+            title: page.title.clone(),
+            term_counts,
+            length: sequence.len(),
+            sequence,
+        });
+
+        self.documents.len() - 1
+    }
+
+    /// Number of pages in the corpus
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Whether the corpus has no pages
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// The pages of the corpus, indexed by document id
+    pub fn documents(&self) -> &[CorpusDocument] {
+        &self.documents
+    }
+
+    /// The vocabulary, indexed by term id
+    pub fn terms(&self) -> &[String] {
+        self.vocab.terms()
+    }
+
+    /// The vocabulary of the corpus
+    pub fn vocabulary(&self) -> &Vocabulary {
+        &self.vocab
+    }
+
+    /// Count of every pair of adjacent terms (first term id, second term id)
+    /// over all documents, see `collocations::collocations`
+    pub fn bigrams(&self) -> &HashMap<(usize, usize), usize> {
+        &self.bigrams
+    }
+
+    /// Gets the id of a (cleaned) term
+    pub fn term_id(&self, term: &str) -> Option<usize> {
+        self.vocab.id(term)
+    }
+
+    /// Number of documents containing a term
+    pub fn document_frequency(&self, term_id: usize) -> usize {
+        self.document_frequencies.get(term_id).copied().unwrap_or(0)
+    }
+
+    /// Smoothed inverse document frequency of a term, `ln((1 + n) / (1 + df)) + 1`.
+    /// Terms in every document still get a weight of 1 rather than vanishing
+    pub fn idf(&self, term_id: usize) -> f64 {
+        let documents = self.documents.len() as f64;
+        let frequency = self.document_frequency(term_id) as f64;
+
+        ((1.0 + documents) / (1.0 + frequency)).ln() + 1.0
+    }
+
+    /// Converts a page into a TF-IDF vector over the corpus vocabulary.
+    /// The page does not have to be in the corpus, terms outside the
+    /// vocabulary are ignored
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page to convert
+    ///
+    /// # Returns
+    ///
+    /// A vector with one term frequency times idf weight per vocabulary term
+    pub fn vectorize(&self, page: &Page) -> Vec<f64> {
+        let content = prepare_document(page, &self.options).content;
+        let counts = count_terms(&content);
+        let length: usize = counts.values().sum();
+
+        let mut vector = vec![0.0; self.vocab.len()];
+        for (term, count) in counts {
+            if let Some(id) = self.vocab.id(&term) {
+                vector[id] = count as f64 / length as f64 * self.idf(id);
+            }
+        }
+
+        vector
+    }
+
+    /// Converts a page into a single precision TF-IDF vector, see `vectorize`.
+    /// Halves the memory of stored vectors, compare them with `cosine_sim_f32`
+    pub fn vectorize_f32(&self, page: &Page) -> Vec<f32> {
+        to_f32(self.vectorize(page))
+    }
+
+    /// The TF-IDF weights of a document of the corpus, weighted like `vectorize`
+    ///
+    /// # Returns
+    ///
+    /// (term id, weight) for every term in the document, sorted by term id
+    pub fn tf_idf_row(&self, doc: usize) -> Vec<(usize, f64)> {
+        let doc = &self.documents[doc];
+
+        doc.term_counts
+            .iter()
+            .map(|&(term, count)| (term, count as f64 / doc.length as f64 * self.idf(term)))
+            .collect()
+    }
+
+    /// The single precision TF-IDF weights of a document of the corpus, see `tf_idf_row`
+    pub fn tf_idf_row_f32(&self, doc: usize) -> Vec<(usize, f32)> {
+        self.tf_idf_row(doc)
+            .into_iter()
+            .map(|(term, weight)| (term, weight as f32))
+            .collect()
+    }
+
+    /// The cosine similarity of two pages' TF-IDF vectors, see `vectorize`.
+    /// Common words weigh far less than in `get_page_similarity`
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The first page
+    /// * `b` - The second page
+    ///
+    /// # Returns
+    ///
+    /// The similarity [0-1], 0 if either page has no vocabulary terms
+    pub fn similarity(&self, a: &Page, b: &Page) -> f64 {
+        let sim = cosine_sim(&self.vectorize(a), &self.vectorize(b));
+
+        // empty documents have no direction, treat them as unrelated
+        if sim.is_nan() {
+            0.0
+        } else {
+            sim
+        }
+    }
+
+    /// Refines a query vector with relevance feedback (Rocchio): moves it towards
+    /// the centroid of documents marked relevant and away from the centroid of
+    /// documents marked not relevant, ie "more pages like these two but not like that one"
+    ///
+    /// # Arguments
+    ///
+    /// * `query_vec` - The query, ie from `vectorize`. Shorter vectors from before
+    ///   the vocabulary grew are fine
+    /// * `relevant_docs` - Ids of the documents to move towards
+    /// * `nonrelevant_docs` - Ids of the documents to move away from
+    /// * `alpha` - Weight of the original query
+    /// * `beta` - Weight of the relevant centroid
+    /// * `gamma` - Weight of the non relevant centroid
+    ///
+    /// # Returns
+    ///
+    /// Ok(Vec<f64>) - the new query over the whole vocabulary, negative weights
+    /// clipped to 0, comparable with `vectorize` output
+    /// Err(WikiError::InputError) - error if a document id is not in the corpus
+    pub fn rocchio(
+        &self,
+        query_vec: &[f64],
+        relevant_docs: &[usize],
+        nonrelevant_docs: &[usize],
+        alpha: f64,
+        beta: f64,
+        gamma: f64,
+    ) -> Result<Vec<f64>, WikiError> {
+        if let Some(doc) = relevant_docs
+            .iter()
+            .chain(nonrelevant_docs)
+            .find(|&&doc| doc >= self.documents.len())
+        {
+            return Err(WikiError::InputError(format!(
+                "Document {} is not in the corpus of {} documents",
+                doc,
+                self.documents.len()
+            )));
+        }
+
+        let mut query = vec![0.0; self.vocab.len().max(query_vec.len())];
+        for (weight, &value) in query.iter_mut().zip(query_vec) {
+            *weight = alpha * value;
+        }
+
+        for (docs, sign) in [(relevant_docs, beta), (nonrelevant_docs, -gamma)] {
+            if docs.is_empty() {
+                continue;
+            }
+
+            let scale = sign / docs.len() as f64;
+            for &doc in docs {
+                for (term, weight) in self.tf_idf_row(doc) {
+                    query[term] += scale * weight;
+                }
+            }
+        }
+
+        for weight in query.iter_mut() {
+            *weight = weight.max(0.0);
+        }
+
+        Ok(query)
+    }
+
+    /// The options pages are cleaned with
+    pub fn options(&self) -> &VectorOptions {
+        &self.options
+    }
+
+    /// Writes the document-term matrix of term frequencies in Matrix Market
+    /// coordinate format, readable with `scipy.io.mmread`.
+    /// Rows are documents and columns are terms. Alongside it, `<path>.vocab`
+    /// gets one term per line and `<path>.titles` one document title per line,
+    /// in row and column order
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path of the matrix file, ie `corpus.mtx`
+    ///
+    /// # Returns
+    ///
+    /// Ok(()) - all three files were written
+    /// Err(WikiError::IOError) - error if writing failed
+    pub fn export_sparse_matrix<P: AsRef<Path>>(&self, path: P) -> Result<(), WikiError> {
+        let path = path.as_ref();
+        debug!("Exporting corpus matrix to {:?}", path);
+
+        let non_zero: usize = self.documents.iter().map(|doc| doc.term_counts.len()).sum();
+
+        let mut matrix = create(path)?;
+        writeln!(matrix, "%%MatrixMarket matrix coordinate real general").map_err(io_error)?;
+        writeln!(
+            matrix,
+            "{} {} {}",
+            self.documents.len(),
+            self.vocab.len(),
+            non_zero
+        )
+        .map_err(io_error)?;
+
+        // Matrix Market indices are 1 based
+        for (row, doc) in self.documents.iter().enumerate() {
+            for &(term, count) in &doc.term_counts {
+                let tf = count as f64 / doc.length as f64;
+                writeln!(matrix, "{} {} {}", row + 1, term + 1, tf).map_err(io_error)?;
+            }
+        }
+        matrix.flush().map_err(io_error)?;
+
+        write_lines(&sibling(path, "vocab"), self.vocab.terms())?;
+
+        let titles: Vec<&String> = self.documents.iter().map(|doc| &doc.title).collect();
+        write_lines(&sibling(path, "titles"), &titles)
+    }
+}
+
+fn io_error(err: std::io::Error) -> WikiError {
+    WikiError::IOError(err.to_string())
+}
+
+fn create(path: &Path) -> Result<BufWriter<File>, WikiError> {
+    Ok(BufWriter::new(File::create(path).map_err(io_error)?))
+}
+
+// `corpus.mtx` -> `corpus.mtx.vocab`
+fn sibling(path: &Path, suffix: &str) -> std::path::PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(suffix);
+
+    name.into()
+}
+
+fn write_lines<T: AsRef<str>>(path: &Path, lines: &[T]) -> Result<(), WikiError> {
+    let mut file = create(path)?;
+
+    for line in lines {
+        // titles and terms never contain newlines, but never let one shift the rows
+        writeln!(file, "{}", line.as_ref().replace('\n', " ")).map_err(io_error)?;
+    }
+
+    file.flush().map_err(io_error)
+}
@@ -0,0 +1,194 @@
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+use super::{text::split_sentences, Page};
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+const MONTH_PATTERN: &str =
+    "(January|February|March|April|May|June|July|August|September|October|November|December)";
+
+lazy_static! {
+    // "14 July 1789"
+    static ref DAY_MONTH_YEAR: Regex =
+        Regex::new(&format!(r"\b(\d{{1,2}}) {} (\d{{3,4}})\b", MONTH_PATTERN)).unwrap();
+    // "July 14, 1789"
+    static ref MONTH_DAY_YEAR: Regex =
+        Regex::new(&format!(r"\b{} (\d{{1,2}}), (\d{{3,4}})\b", MONTH_PATTERN)).unwrap();
+    // "July 1789"
+    static ref MONTH_YEAR: Regex =
+        Regex::new(&format!(r"\b{} (\d{{3,4}})\b", MONTH_PATTERN)).unwrap();
+    // "1789", "44 BC", "AD 79"
+    static ref YEAR: Regex =
+        Regex::new(r"\b(?:AD (\d{1,4})|(\d{1,4}) (BC|BCE|AD|CE)|(1\d{3}|20\d{2}))\b").unwrap();
+}
+
+// (year, month, day) of a pattern match
+type DateParser = fn(&Captures) -> Option<(i32, Option<u8>, Option<u8>)>;
+
+/// A date or year mentioned in a page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatedMention {
+    /// The year, negative for years BC (44 BC is -44)
+    pub year: i32,
+    pub month: Option<u8>,
+    pub day: Option<u8>,
+    /// The date as written
+    pub text: String,
+    /// The sentence the date is in
+    pub context: String,
+    /// Byte offset of the date in `Page::content`
+    pub offset: usize,
+}
+
+/// Extracts dates (`"14 July 1789"`, `"July 14, 1789"`, `"July 1789"`) and years
+/// (`"1789"`, `"44 BC"`) from a page. Bare numbers only count as years from
+/// 1000 to 2099, smaller years need an era (`"AD 79"`)
+///
+/// # Arguments
+///
+/// * `page` - the page to search
+///
+/// # Returns
+///
+/// Every mention in content order
+pub fn extract_dates(page: &Page) -> Vec<DatedMention> {
+    let content = &page.content;
+    let mut mentions: Vec<DatedMention> = Vec::new();
+
+    // most specific patterns first, later ones skip text already matched
+    let patterns: [(&Regex, DateParser); 4] = [
+        (&DAY_MONTH_YEAR, |c| {
+            Some((c[3].parse().ok()?, month(&c[2]), c[1].parse().ok()))
+        }),
+        (&MONTH_DAY_YEAR, |c| {
+            Some((c[3].parse().ok()?, month(&c[1]), c[2].parse().ok()))
+        }),
+        (&MONTH_YEAR, |c| {
+            Some((c[2].parse().ok()?, month(&c[1]), None))
+        }),
+        (&YEAR, |c| Some((era_year(c)?, None, None))),
+    ];
+
+    for (pattern, parse) in patterns {
+        for captures in pattern.captures_iter(content) {
+            let matched = captures.get(0).unwrap();
+            let overlaps = mentions.iter().any(|mention| {
+                matched.start() < mention.offset + mention.text.len()
+                    && mention.offset < matched.end()
+            });
+            if overlaps {
+                continue;
+            }
+
+            let Some((year, month, day)) = parse(&captures) else {
+                continue;
+            };
+            if day.is_some_and(|day| day == 0 || day > 31) {
+                continue;
+            }
+
+            mentions.push(DatedMention {
+                year,
+                month,
+                day,
+                text: matched.as_str().to_owned(),
+                context: sentence_at(content, matched.start()),
+                offset: matched.start(),
+            });
+        }
+    }
+
+    mentions.sort_by_key(|mention| mention.offset);
+    mentions
+}
+
+/// The earliest and latest year a page mentions
+///
+/// # Arguments
+///
+/// * `page` - the page to search
+///
+/// # Returns
+///
+/// (first year, last year), None if the page mentions no dates
+pub fn time_span(page: &Page) -> Option<(i32, i32)> {
+    let years: Vec<i32> = extract_dates(page)
+        .into_iter()
+        .map(|mention| mention.year)
+        .collect();
+
+    Some((*years.iter().min()?, *years.iter().max()?))
+}
+
+/// How much the time spans of two pages overlap, as a similarity feature
+/// for comparing articles chronologically
+///
+/// # Arguments
+///
+/// * `a` - The first page
+/// * `b` - The second page
+///
+/// # Returns
+///
+/// The years both spans cover over the years either covers -> [0-1],
+/// 0 if either page mentions no dates
+pub fn time_overlap(a: &Page, b: &Page) -> f64 {
+    let (Some((a_start, a_end)), Some((b_start, b_end))) = (time_span(a), time_span(b)) else {
+        return 0.0;
+    };
+
+    // spans are inclusive, a single year covers one year
+    let shared = (a_end.min(b_end) - a_start.max(b_start) + 1).max(0);
+    let covered = a_end.max(b_end) - a_start.min(b_start) + 1;
+
+    shared as f64 / covered as f64
+}
+
+fn month(name: &str) -> Option<u8> {
+    let name = name.to_lowercase();
+    MONTHS
+        .iter()
+        .position(|month| *month == name)
+        .map(|i| i as u8 + 1)
+}
+
+fn era_year(captures: &Captures) -> Option<i32> {
+    if let Some(year) = captures.get(1) {
+        return year.as_str().parse().ok();
+    }
+    if let (Some(year), Some(era)) = (captures.get(2), captures.get(3)) {
+        let year: i32 = year.as_str().parse().ok()?;
+        return Some(match era.as_str() {
+            "BC" | "BCE" => -year,
+            _ => year,
+        });
+    }
+
+    captures.get(4)?.as_str().parse().ok()
+}
+
+fn sentence_at(content: &str, offset: usize) -> String {
+    split_sentences(content)
+        .into_iter()
+        .find(|sentence| {
+            // sentences are slices of content, so their offset is their distance
+            let start = sentence.as_ptr() as usize - content.as_ptr() as usize;
+            start <= offset && offset < start + sentence.len()
+        })
+        .unwrap_or_default()
+        .to_owned()
+}
@@ -0,0 +1,195 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    sync::Arc,
+};
+
+use log::debug;
+
+use super::{corpus::Corpus, cosine_sim_f32, prepare_document, Page, VectorOptions, WikiError};
+
+/// Pretrained word vectors, ie GloVe or fastText, for comparing pages by meaning
+/// rather than by exact words: `"car"` and `"automobile"` are close in embedding
+/// space but unrelated to the term frequency vectors of `get_page_similarity`
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordEmbeddings {
+    vectors: Arc<HashMap<String, Vec<f32>>>,
+    dimensions: usize,
+    /// How pages are cleaned before their words are looked up. Leave stemming
+    /// off, stems are rarely in an embedding vocabulary
+    pub options: VectorOptions,
+}
+
+impl WordEmbeddings {
+    /// Loads vectors in the text format shared by GloVe and fastText `.vec` files,
+    /// one `word v1 v2 ...` line per word. The `count dimensions` header line
+    /// of fastText files is skipped
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path of the vectors file
+    ///
+    /// # Returns
+    ///
+    /// Ok(WordEmbeddings) - the loaded vectors
+    /// Err(WikiError::IOError) - error if the file could not be read
+    /// Err(WikiError::ParseError) - error if a line is not a word and its vector
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, WikiError> {
+        debug!("Loading word embeddings from {:?}", path.as_ref());
+        let file = File::open(path).map_err(|err| WikiError::IOError(err.to_string()))?;
+
+        WordEmbeddings::from_reader(BufReader::new(file))
+    }
+
+    /// Loads vectors in the GloVe / fastText text format from a reader, see `from_file`
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, WikiError> {
+        let mut vectors: HashMap<String, Vec<f32>> = HashMap::new();
+        let mut dimensions = 0;
+
+        for (number, line) in reader.lines().enumerate() {
+            let line = line.map_err(|err| WikiError::IOError(err.to_string()))?;
+            let mut fields = line.split_whitespace();
+            let Some(word) = fields.next() else {
+                continue;
+            };
+
+            let vector = fields
+                .map(str::parse)
+                .collect::<Result<Vec<f32>, _>>()
+                .map_err(|_| {
+                    WikiError::ParseError(format!("Invalid embedding on line {}", number + 1))
+                })?;
+
+            // fastText header, `2000000 300`
+            if number == 0 && vector.len() == 1 && word.parse::<usize>().is_ok() {
+                continue;
+            }
+
+            if dimensions == 0 {
+                dimensions = vector.len();
+            }
+            if vector.len() != dimensions || dimensions == 0 {
+                return Err(WikiError::ParseError(format!(
+                    "Embedding on line {} has {} dimensions, expected {}",
+                    number + 1,
+                    vector.len(),
+                    dimensions
+                )));
+            }
+
+            // files are sorted by frequency, keep the most common casing
+            vectors.entry(word.to_lowercase()).or_insert(vector);
+        }
+
+        Ok(WordEmbeddings::from_vectors(vectors, dimensions))
+    }
+
+    /// Wraps vectors already in memory, all of them `dimensions` long
+    pub fn from_vectors(vectors: HashMap<String, Vec<f32>>, dimensions: usize) -> Self {
+        WordEmbeddings {
+            vectors: Arc::new(vectors),
+            dimensions,
+            options: VectorOptions::default(),
+        }
+    }
+
+    /// Length of every vector
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// Number of words with a vector
+    pub fn len(&self) -> usize {
+        self.vectors.len()
+    }
+
+    /// Whether there are no vectors
+    pub fn is_empty(&self) -> bool {
+        self.vectors.is_empty()
+    }
+
+    /// Gets the vector of a lowercase word
+    pub fn get(&self, word: &str) -> Option<&[f32]> {
+        self.vectors.get(word).map(Vec::as_slice)
+    }
+
+    /// The average embedding of the words of a page, every occurrence counting
+    /// once so frequent words weigh more. Words without a vector are skipped
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page to embed
+    ///
+    /// # Returns
+    ///
+    /// A `dimensions` long vector, all zeros if no word of the page has a vector
+    pub fn page_vector(&self, page: &Page) -> Vec<f32> {
+        self.weighted_average(page, |_| 1.0)
+    }
+
+    /// The average embedding of the words of a page, each weighted by its idf in
+    /// a corpus so words common to every page barely move the average
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page to embed
+    /// * `corpus` - Corpus the idf weights come from, words outside it get the highest weight
+    ///
+    /// # Returns
+    ///
+    /// A `dimensions` long vector, all zeros if no word of the page has a vector
+    pub fn page_vector_weighted(&self, page: &Page, corpus: &Corpus) -> Vec<f32> {
+        // one past the vocabulary is a term no document contains
+        let unseen = corpus.terms().len();
+
+        self.weighted_average(page, |word| {
+            corpus.idf(corpus.term_id(word).unwrap_or(unseen)) as f32
+        })
+    }
+
+    /// The cosine similarity of the average embeddings of two pages, see `page_vector`
+    ///
+    /// # Returns
+    ///
+    /// The similarity [-1, 1], 0 if either page has no word with a vector
+    pub fn similarity(&self, a: &Page, b: &Page) -> f64 {
+        cosine_sim_f32(&self.page_vector(a), &self.page_vector(b))
+    }
+
+    /// The cosine similarity of the idf weighted embeddings of two pages,
+    /// see `page_vector_weighted`
+    ///
+    /// # Returns
+    ///
+    /// The similarity [-1, 1], 0 if either page has no word with a vector
+    pub fn similarity_weighted(&self, a: &Page, b: &Page, corpus: &Corpus) -> f64 {
+        cosine_sim_f32(
+            &self.page_vector_weighted(a, corpus),
+            &self.page_vector_weighted(b, corpus),
+        )
+    }
+
+    fn weighted_average<F: Fn(&str) -> f32>(&self, page: &Page, weight: F) -> Vec<f32> {
+        let content = prepare_document(page, &self.options).content;
+
+        let mut sum = vec![0.0f32; self.dimensions];
+        let mut total = 0.0f32;
+        for word in content.split_whitespace() {
+            if let Some(vector) = self.vectors.get(word) {
+                let weight = weight(word);
+                for (value, component) in sum.iter_mut().zip(vector) {
+                    *value += weight * component;
+                }
+                total += weight;
+            }
+        }
+
+        if total > 0.0 {
+            sum.iter_mut().for_each(|value| *value /= total);
+        }
+
+        sum
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    classify::{type_from_name, EntityType},
+    stop_words::StopWords,
+    text::split_sentences,
+    Namespace, Page,
+};
+
+// a capitalized word after one of these names a person
+const PERSON_TITLES: [&str; 12] = [
+    "mr",
+    "mrs",
+    "ms",
+    "dr",
+    "sir",
+    "dame",
+    "lord",
+    "lady",
+    "pope",
+    "prince",
+    "princess",
+    "president",
+];
+
+// words kept inside a name, ie "Bank of England", "Leonardo da Vinci"
+const CONNECTORS: [&str; 8] = ["of", "the", "de", "da", "del", "la", "von", "van"];
+
+// name heads for organizations that are not nouns of a lead sentence
+const ORGANIZATION_HEADS: [&str; 11] = [
+    "inc",
+    "ltd",
+    "corp",
+    "bank",
+    "college",
+    "institute",
+    "society",
+    "association",
+    "council",
+    "ministry",
+    "foundation",
+];
+
+/// A named entity mentioned by a page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entity {
+    pub name: String,
+    /// The type of the entity, if it could be inferred from its name
+    pub entity_type: Option<EntityType>,
+    /// How many times the page mentions the entity
+    pub count: usize,
+    /// Url of the page the entity links to, None if it is never linked
+    pub outlink: Option<String>,
+}
+
+/// Extracts the named entities of a page: the targets of its article links and
+/// runs of capitalized words in its content (`"Bank of England"`).
+/// Single capitalized words starting a sentence are skipped, they are usually
+/// just the first word of the sentence
+///
+/// # Arguments
+///
+/// * `page` - the page to extract entities from
+///
+/// # Returns
+///
+/// The entities, most mentioned first
+pub fn extract_entities(page: &Page) -> Vec<Entity> {
+    let mut entities: HashMap<String, Entity> = HashMap::new();
+
+    for (name, entity_type) in capitalized_spans(&page.content) {
+        entities
+            .entry(name.clone())
+            .or_insert_with(|| Entity {
+                name,
+                entity_type,
+                count: 0,
+                outlink: None,
+            })
+            .count += 1;
+    }
+
+    let links = page
+        .links
+        .iter()
+        .filter(|link| link.namespace == Namespace::Article && !link.target.is_empty());
+
+    for link in links {
+        let entity = entities
+            .entry(link.target.clone())
+            .or_insert_with(|| Entity {
+                name: link.target.clone(),
+                entity_type: None,
+                count: 0,
+                outlink: None,
+            });
+
+        // anchor text is part of the content, so mentions are counted once
+        entity.count = entity.count.max(link.count);
+        entity.outlink = Some(link.outlink.clone());
+        entity.entity_type = entity
+            .entity_type
+            .or_else(|| infer_type(&link.target, None));
+    }
+
+    let mut entities: Vec<Entity> = entities.into_values().collect();
+    entities.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+
+    entities
+}
+
+/// Jaccard similarity of the entities two pages mention, case insensitive
+///
+/// # Arguments
+///
+/// * `a` - The first page
+/// * `b` - The second page
+///
+/// # Returns
+///
+/// The shared entities over all entities of both pages -> [0-1]
+pub fn entity_overlap(a: &Page, b: &Page) -> f64 {
+    let names = |page: &Page| -> HashSet<String> {
+        extract_entities(page)
+            .into_iter()
+            .map(|entity| entity.name.to_lowercase())
+            .collect()
+    };
+
+    let (a, b) = (names(a), names(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+// runs of capitalized words of every sentence, with their inferred type
+fn capitalized_spans(content: &str) -> Vec<(String, Option<EntityType>)> {
+    let stop_words = StopWords::english();
+    let mut spans = Vec::new();
+
+    for sentence in split_sentences(content) {
+        let words: Vec<&str> = sentence.split_whitespace().collect();
+        let mut i = 0;
+
+        while i < words.len() {
+            let start = i;
+            let mut span: Vec<&str> = Vec::new();
+
+            while i < words.len() {
+                let word = trim_word(words[i]);
+                let connector = !span.is_empty() && CONNECTORS.contains(&word);
+                if !(is_capitalized(word) || connector) {
+                    break;
+                }
+
+                span.push(word);
+                i += 1;
+
+                // punctuation after a word ends the name
+                if words[i - 1].ends_with(|c: char| !c.is_alphanumeric()) {
+                    break;
+                }
+            }
+
+            if span.is_empty() {
+                i += 1;
+                continue;
+            }
+
+            let mut title = start.checked_sub(1).map(|j| trim_word(words[j]));
+
+            // "The", "In", "He" starting a sentence are not part of a name
+            let mut offset = 0;
+            while span.first().is_some_and(|word| stop_words.contains(word)) {
+                span.remove(0);
+                offset += 1;
+            }
+            while span.last().is_some_and(|word| CONNECTORS.contains(word)) {
+                span.pop();
+            }
+            // "Sir Isaac Newton" is Isaac Newton
+            if span.len() > 1 && is_person_title(span[0]) {
+                title = Some(span.remove(0));
+                offset += 1;
+            }
+
+            let sentence_start = start + offset == 0;
+            if span.is_empty() || (span.len() == 1 && sentence_start) {
+                continue;
+            }
+
+            let name = span.join(" ");
+            let entity_type = infer_type(&name, title);
+            spans.push((name, entity_type));
+        }
+    }
+
+    spans
+}
+
+fn infer_type(name: &str, title: Option<&str>) -> Option<EntityType> {
+    let words: Vec<String> = name.split_whitespace().map(str::to_lowercase).collect();
+
+    if title.is_some_and(is_person_title) || is_person_title(words.first()?) {
+        return Some(EntityType::Person);
+    }
+
+    // the head of "Bank of England" is "Bank"
+    let head = words
+        .iter()
+        .take_while(|word| *word != "of")
+        .last()?
+        .trim_end_matches('.');
+
+    if ORGANIZATION_HEADS.contains(&head) {
+        return Some(EntityType::Organization);
+    }
+
+    type_from_name(head).or_else(|| type_from_name(name))
+}
+
+fn is_person_title(word: &str) -> bool {
+    PERSON_TITLES.contains(&word.to_lowercase().as_str())
+}
+
+fn trim_word(word: &str) -> &str {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+}
+
+fn is_capitalized(word: &str) -> bool {
+    word.chars().next().is_some_and(char::is_uppercase)
+}
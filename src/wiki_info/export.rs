@@ -0,0 +1,173 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::Write,
+};
+
+use serde::Serialize;
+
+use super::{build_vocab, prepare_document, CleanOptions, Page, VectorOptions, WikiError};
+
+/// Output format of the debug exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    /// Comma separated values with a header row
+    Csv,
+}
+
+/// A single term of a fitted vocabulary
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct VocabularyEntry {
+    pub term: String,
+    /// Index of the term in page vectors
+    pub id: usize,
+    /// Number of pages the term appears in
+    pub df: usize,
+}
+
+/// Gets the stop words removed by `clean_document_with`
+///
+/// # Arguments
+///
+/// * `options` - The cleaning options whose stop words are listed
+///
+/// # Returns
+///
+/// The sorted, deduplicated stop word list, empty if `remove_stop_words` is off
+pub fn stop_words(options: &CleanOptions) -> Vec<String> {
+    if options.remove_stop_words {
+        options.stop_words.to_sorted_vec()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Fits the shared vocabulary used to vectorize a set of pages, exactly as the
+/// similarity functions would
+///
+/// # Arguments
+///
+/// * `pages` - The pages to build the vocabulary over
+/// * `options` - How the pages are vectorized
+///
+/// # Returns
+///
+/// Every term that survived preprocessing, ordered by id
+pub fn fit_vocabulary(pages: &[Page], options: &VectorOptions) -> Vec<VocabularyEntry> {
+    let vocab = build_vocab(pages.iter(), options);
+
+    let mut df: HashMap<String, usize> = HashMap::new();
+    for page in pages {
+        let content = prepare_document(page, options).content;
+        let unique: HashSet<&str> = content.split_whitespace().collect();
+
+        for term in unique {
+            *df.entry(term.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    let mut entries: Vec<VocabularyEntry> = vocab
+        .into_iter()
+        .map(|(term, id)| VocabularyEntry {
+            df: df.get(&term).copied().unwrap_or(0),
+            term,
+            id,
+        })
+        .collect();
+
+    entries.sort_by_key(|entry| entry.id);
+
+    entries
+}
+
+/// Writes the stop word list, see `stop_words`
+///
+/// # Arguments
+///
+/// * `options` - The cleaning options whose stop words are written
+/// * `writer` - Where to write the list
+/// * `format` - JSON array of strings, or a single `term` column of CSV
+///
+/// # Returns
+///
+/// Ok(()) - the list was written
+/// Err(WikiError::IOError) - error if writing failed
+pub fn export_stop_words<W: Write>(
+    options: &CleanOptions,
+    writer: W,
+    format: ExportFormat,
+) -> Result<(), WikiError> {
+    let words = stop_words(options);
+
+    match format {
+        ExportFormat::Json => write_json(writer, &words),
+        ExportFormat::Csv => {
+            let rows = words.iter().map(|word| vec![word.clone()]);
+            write_csv(writer, &["term"], rows)
+        }
+    }
+}
+
+/// Writes a fitted vocabulary, see `fit_vocabulary`
+///
+/// # Arguments
+///
+/// * `entries` - The vocabulary to write
+/// * `writer` - Where to write the vocabulary
+/// * `format` - JSON array of entries, or CSV with `term,id,df` columns
+///
+/// # Returns
+///
+/// Ok(()) - the vocabulary was written
+/// Err(WikiError::IOError) - error if writing failed
+pub fn export_vocabulary<W: Write>(
+    entries: &[VocabularyEntry],
+    writer: W,
+    format: ExportFormat,
+) -> Result<(), WikiError> {
+    match format {
+        ExportFormat::Json => write_json(writer, entries),
+        ExportFormat::Csv => {
+            let rows = entries.iter().map(|entry| {
+                vec![
+                    entry.term.clone(),
+                    entry.id.to_string(),
+                    entry.df.to_string(),
+                ]
+            });
+            write_csv(writer, &["term", "id", "df"], rows)
+        }
+    }
+}
+
+fn write_json<W: Write, T: Serialize + ?Sized>(writer: W, value: &T) -> Result<(), WikiError> {
+    serde_json::to_writer_pretty(writer, value).map_err(|err| WikiError::IOError(err.to_string()))
+}
+
+fn write_csv<W: Write>(
+    mut writer: W,
+    header: &[&str],
+    rows: impl Iterator<Item = Vec<String>>,
+) -> Result<(), WikiError> {
+    let header: Vec<String> = header.iter().map(|column| column.to_string()).collect();
+
+    for row in std::iter::once(header).chain(rows) {
+        let line = row
+            .iter()
+            .map(|field| escape_csv(field))
+            .collect::<Vec<String>>()
+            .join(",");
+
+        writeln!(writer, "{}", line).map_err(|err| WikiError::IOError(err.to_string()))?;
+    }
+
+    Ok(())
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
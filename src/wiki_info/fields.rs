@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use super::{
+    count_terms, prepare_document, score_vectors, vocabulary::Vocabulary, Page, VectorOptions,
+};
+
+/// How much a term match counts in each field of a page, BM25F style: the length
+/// normalized term frequencies of every field are weighted and summed into one
+/// frequency per term. Title matches are a strong signal that a long body would
+/// otherwise drown out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldWeights {
+    /// Weight of the page title
+    pub title: f64,
+    /// Weight of the lead paragraphs, `Page::summary`
+    pub lead: f64,
+    /// Weight of the whole content, lead included
+    pub body: f64,
+}
+
+impl Default for FieldWeights {
+    fn default() -> Self {
+        FieldWeights {
+            title: 3.0,
+            lead: 2.0,
+            body: 1.0,
+        }
+    }
+}
+
+impl FieldWeights {
+    // in the order of `fields`
+    fn weights(&self) -> [f64; 3] {
+        [self.title, self.lead, self.body]
+    }
+}
+
+fn fields(page: &Page) -> [&str; 3] {
+    [&page.title, &page.summary, &page.content]
+}
+
+/// Get the similarity of two pages, weighting matches in the title and lead above
+/// matches in the body
+///
+/// # Arguments
+///
+/// * `page1` - The first page to check
+/// * `page2` - The second page to check
+/// * `weights` - The weight of each field
+///
+/// # Returns
+///
+/// The similarity [0-1], 0 if neither page has any terms
+pub fn get_fielded_similarity(page1: &Page, page2: &Page, weights: &FieldWeights) -> f64 {
+    get_fielded_similarity_with(page1, page2, weights, &VectorOptions::default())
+}
+
+/// Get the fielded similarity of two pages, vectorized with the given options,
+/// see `get_fielded_similarity`
+pub fn get_fielded_similarity_with(
+    page1: &Page,
+    page2: &Page,
+    weights: &FieldWeights,
+    options: &VectorOptions,
+) -> f64 {
+    let counts1 = field_counts(page1, options);
+    let counts2 = field_counts(page2, options);
+
+    let mut vocabulary = Vocabulary::new();
+    for counts in [&counts1, &counts2] {
+        for (term, _) in counts.iter().flatten() {
+            vocabulary.add_term(term);
+        }
+    }
+
+    let vec1 = weighted_vector(&counts1, weights, &vocabulary);
+    let vec2 = weighted_vector(&counts2, weights, &vocabulary);
+
+    let normalization = options.length_normalization.pivoted_over(&[&vec1, &vec2]);
+    let sim = score_vectors(&vec1, &[vec2], normalization)[0];
+    if sim.is_nan() {
+        0.0
+    } else {
+        sim
+    }
+}
+
+/// Convert a Page into its fielded term frequency vector over a vocabulary,
+/// the weighted sum of the term frequencies of each field
+///
+/// # Arguments
+///
+/// * `page` - The page to convert
+/// * `vocabulary` - The vocabulary to vectorize over, terms outside it are ignored
+/// * `weights` - The weight of each field
+/// * `options` - How each field is cleaned
+///
+/// # Returns
+///
+/// A vector with one weighted term frequency per vocabulary term
+pub fn fielded_vector(
+    page: &Page,
+    vocabulary: &Vocabulary,
+    weights: &FieldWeights,
+    options: &VectorOptions,
+) -> Vec<f64> {
+    weighted_vector(&field_counts(page, options), weights, vocabulary)
+}
+
+// the cleaned term counts of every field
+fn field_counts(page: &Page, options: &VectorOptions) -> [HashMap<String, usize>; 3] {
+    fields(page).map(|text| {
+        let field = Page {
+            content: text.to_owned(),
+            ..Default::default()
+        };
+
+        count_terms(&prepare_document(&field, options).content)
+    })
+}
+
+fn weighted_vector(
+    counts: &[HashMap<String, usize>; 3],
+    weights: &FieldWeights,
+    vocabulary: &Vocabulary,
+) -> Vec<f64> {
+    let mut vector = vec![0.0; vocabulary.len()];
+
+    for (field_counts, weight) in counts.iter().zip(weights.weights()) {
+        let length = field_counts.values().sum::<usize>() as f64;
+
+        for (term, &count) in field_counts {
+            if let Some(id) = vocabulary.id(term) {
+                vector[id] += weight * count as f64 / length;
+            }
+        }
+    }
+
+    vector
+}
@@ -0,0 +1,176 @@
+use std::path::Path;
+
+use log::debug;
+use tantivy::{
+    collector::TopDocs,
+    directory::MmapDirectory,
+    doc,
+    query::QueryParser,
+    schema::{Field, Schema, Value, STORED, STRING, TEXT},
+    Index, IndexWriter, Searcher, TantivyDocument, Term,
+};
+
+use super::{Page, WikiError};
+
+// heap given to the index writer, tantivy's minimum is 15MB
+const WRITER_HEAP: usize = 50_000_000;
+
+// titles are the most deliberate summary of a page
+const TITLE_BOOST: f32 = 2.0;
+
+/// The tantivy fields pages are indexed into
+#[derive(Debug, Clone, Copy)]
+pub struct PageFields {
+    /// Untokenized title, used to replace a page when it is indexed again
+    pub key: Field,
+    pub title: Field,
+    pub summary: Field,
+    pub content: Field,
+    /// One value per category
+    pub categories: Field,
+}
+
+/// A persistent BM25 index of pages, backed by tantivy.
+/// Requires the `tantivy` feature
+pub struct PageIndex {
+    index: Index,
+    searcher: Searcher,
+    fields: PageFields,
+}
+
+/// Indexes pages into a tantivy index stored in `dir`.
+/// An existing index in `dir` is reused, pages that are already indexed
+/// (by title) are replaced. Requires the `tantivy` feature
+///
+/// # Arguments
+///
+/// * `pages` - The pages to index
+/// * `dir` - Directory of the index, created if it does not exist
+///
+/// # Returns
+///
+/// Ok(PageIndex) - the index, with a searcher that sees every page
+/// Err(WikiError::IOError) - error if the index could not be opened or written
+pub fn index_pages_tantivy<P: AsRef<Path>>(pages: &[Page], dir: P) -> Result<PageIndex, WikiError> {
+    let dir = dir.as_ref();
+    debug!("Indexing {} pages into {:?}", pages.len(), dir);
+
+    std::fs::create_dir_all(dir).map_err(|err| WikiError::IOError(err.to_string()))?;
+    let directory = MmapDirectory::open(dir).map_err(|err| WikiError::IOError(err.to_string()))?;
+
+    let (schema, fields) = page_schema();
+    let index = Index::open_or_create(directory, schema).map_err(tantivy_error)?;
+
+    let mut writer: IndexWriter = index.writer(WRITER_HEAP).map_err(tantivy_error)?;
+
+    for page in pages {
+        writer.delete_term(Term::from_field_text(fields.key, &page.title));
+
+        let mut document = doc!(
+            fields.key => page.title.as_str(),
+            fields.title => page.title.as_str(),
+            fields.summary => page.summary.as_str(),
+            fields.content => page.content.as_str(),
+        );
+        for category in &page.categories {
+            document.add_text(fields.categories, category);
+        }
+
+        writer.add_document(document).map_err(tantivy_error)?;
+    }
+
+    writer.commit().map_err(tantivy_error)?;
+
+    let searcher = index.reader().map_err(tantivy_error)?.searcher();
+
+    Ok(PageIndex {
+        index,
+        searcher,
+        fields,
+    })
+}
+
+impl PageIndex {
+    /// Runs a query in tantivy's query syntax against titles, summaries,
+    /// content and categories
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - the query, ie `"dark tower"` or `"title:king"`
+    /// * `limit` - the max number of results
+    ///
+    /// # Returns
+    ///
+    /// Ok(Vec<(String, f32)>) - (title, BM25 score) of matches, best first
+    /// Err(WikiError::ParseError) - error if the query is malformed
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<(String, f32)>, WikiError> {
+        let mut parser = QueryParser::for_index(
+            &self.index,
+            vec![
+                self.fields.title,
+                self.fields.summary,
+                self.fields.content,
+                self.fields.categories,
+            ],
+        );
+        parser.set_field_boost(self.fields.title, TITLE_BOOST);
+
+        let query = parser
+            .parse_query(query)
+            .map_err(|err| WikiError::ParseError(err.to_string()))?;
+
+        let top_docs = self
+            .searcher
+            .search(&query, &TopDocs::with_limit(limit))
+            .map_err(tantivy_error)?;
+
+        top_docs
+            .into_iter()
+            .map(|(score, address)| {
+                let document: TantivyDocument =
+                    self.searcher.doc(address).map_err(tantivy_error)?;
+
+                let title = document
+                    .get_first(self.fields.key)
+                    .and_then(|value| value.as_str())
+                    .unwrap_or_default()
+                    .to_owned();
+
+                Ok((title, score))
+            })
+            .collect()
+    }
+
+    /// The underlying tantivy index
+    pub fn index(&self) -> &Index {
+        &self.index
+    }
+
+    /// A searcher over every indexed page, for building custom queries
+    pub fn searcher(&self) -> &Searcher {
+        &self.searcher
+    }
+
+    /// The fields pages are indexed into
+    pub fn fields(&self) -> PageFields {
+        self.fields
+    }
+}
+
+fn page_schema() -> (Schema, PageFields) {
+    let mut builder = Schema::builder();
+
+    let fields = PageFields {
+        key: builder.add_text_field("key", STRING | STORED),
+        title: builder.add_text_field("title", TEXT),
+        summary: builder.add_text_field("summary", TEXT),
+        content: builder.add_text_field("content", TEXT),
+        categories: builder.add_text_field("categories", TEXT),
+    };
+
+    (builder.build(), fields)
+}
+
+fn tantivy_error(err: tantivy::TantivyError) -> WikiError {
+    WikiError::IOError(err.to_string())
+}
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+
+use super::{matrix::Matrix, url_utils::extract_slug, HyperLink, Page};
+
+/// A directed link graph over crawled pages.
+/// Nodes are page titles, an edge `a -> b` means page `a` links to page `b`.
+/// Link targets that were never crawled are still nodes, they just have no outlinks
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WikiGraph {
+    titles: Vec<String>,
+    index: HashMap<String, usize>,
+    /// Outgoing edges of every node, deduplicated and in link order
+    outlinks: Vec<Vec<usize>>,
+}
+
+impl WikiGraph {
+    /// Creates an empty graph
+    pub fn new() -> Self {
+        WikiGraph::default()
+    }
+
+    /// Builds a graph from a set of crawled pages
+    ///
+    /// # Arguments
+    ///
+    /// * `pages` - the pages to add, ie the result of a traversal
+    ///
+    /// # Returns
+    ///
+    /// The new WikiGraph
+    pub fn from_pages(pages: &[Page]) -> Self {
+        let mut graph = WikiGraph::new();
+
+        for page in pages {
+            graph.add_page(page);
+        }
+
+        graph
+    }
+
+    /// Adds a page and an edge to every one of its outlinks.
+    /// Red links are left out, their pages do not exist
+    pub fn add_page(&mut self, page: &Page) {
+        let source = self.node(page.title.trim());
+
+        for link in page.links.iter().filter(|link| link.exists) {
+            let target = self.node(&link_title(link));
+
+            if target != source && !self.outlinks[source].contains(&target) {
+                self.outlinks[source].push(target);
+            }
+        }
+    }
+
+    /// Number of nodes in the graph
+    pub fn node_count(&self) -> usize {
+        self.titles.len()
+    }
+
+    /// Number of edges in the graph
+    pub fn edge_count(&self) -> usize {
+        self.outlinks.iter().map(Vec::len).sum()
+    }
+
+    /// Titles of every node, indexed by node id
+    pub fn titles(&self) -> &[String] {
+        &self.titles
+    }
+
+    /// Gets the node id of a title
+    pub fn node_id(&self, title: &str) -> Option<usize> {
+        self.index.get(title).copied()
+    }
+
+    /// Node ids the given node links to
+    pub fn outlinks(&self, node: usize) -> &[usize] {
+        &self.outlinks[node]
+    }
+
+    /// Node ids linking to the given node
+    pub fn inlinks(&self, node: usize) -> Vec<usize> {
+        (0..self.node_count())
+            .filter(|&source| self.outlinks[source].contains(&node))
+            .collect()
+    }
+
+    /// Computes the SimRank similarity of every pair of nodes: two pages are similar
+    /// when they are linked to from similar pages. Purely structural, so it can be
+    /// compared against the text based similarities.
+    /// Takes quadratic memory in the number of nodes
+    ///
+    /// # Arguments
+    ///
+    /// * `decay` - How much similarity is kept per step away from a page [0-1], usually 0.8
+    /// * `iterations` - Number of iterations, scores are within `decay^iterations`
+    ///   of their limit. 5 to 10 is usually enough
+    ///
+    /// # Returns
+    ///
+    /// A symmetric `node_count` square matrix indexed by node ids, 1 on the diagonal.
+    /// Nodes without inlinks score 0 against every other node
+    pub fn simrank(&self, decay: f64, iterations: usize) -> Matrix<f64> {
+        let n = self.node_count();
+
+        let mut inlinks: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for (source, targets) in self.outlinks.iter().enumerate() {
+            for &target in targets {
+                inlinks[target].push(source);
+            }
+        }
+
+        let mut scores = Matrix::filled(n, n, 0.0);
+        for node in 0..n {
+            scores[(node, node)] = 1.0;
+        }
+
+        for _ in 0..iterations {
+            let data: Vec<f64> = (0..n)
+                .into_par_iter()
+                .flat_map_iter(|a| {
+                    let (inlinks, scores) = (&inlinks, &scores);
+                    (0..n).map(move |b| {
+                        if a == b {
+                            return 1.0;
+                        }
+                        if inlinks[a].is_empty() || inlinks[b].is_empty() {
+                            return 0.0;
+                        }
+
+                        let sum: f64 = inlinks[a]
+                            .iter()
+                            .flat_map(|&i| inlinks[b].iter().map(move |&j| scores[(i, j)]))
+                            .sum();
+
+                        decay * sum / (inlinks[a].len() * inlinks[b].len()) as f64
+                    })
+                })
+                .collect();
+
+            scores = Matrix::from_vec(n, n, data);
+        }
+
+        scores
+    }
+
+    /// Converts the graph into a petgraph `DiGraph` so petgraph's algorithms
+    /// (shortest paths, strongly connected components, etc) can be run on it.
+    /// Node ids are kept, node `i` here is `NodeIndex::new(i)` in the result.
+    /// Requires the `petgraph` feature
+    #[cfg(feature = "petgraph")]
+    pub fn to_petgraph(&self) -> petgraph::graph::DiGraph<String, ()> {
+        let mut graph =
+            petgraph::graph::DiGraph::with_capacity(self.node_count(), self.edge_count());
+
+        for title in &self.titles {
+            graph.add_node(title.clone());
+        }
+
+        for (source, targets) in self.outlinks.iter().enumerate() {
+            for &target in targets {
+                graph.add_edge(
+                    petgraph::graph::NodeIndex::new(source),
+                    petgraph::graph::NodeIndex::new(target),
+                    (),
+                );
+            }
+        }
+
+        graph
+    }
+
+    fn node(&mut self, title: &str) -> usize {
+        if let Some(&id) = self.index.get(title) {
+            return id;
+        }
+
+        let id = self.titles.len();
+        self.titles.push(title.to_owned());
+        self.index.insert(title.to_owned(), id);
+        self.outlinks.push(Vec::new());
+
+        id
+    }
+}
+
+// `target` is already decoded and has no section anchor, so section links point at
+// the page itself. Links built by hand or stored before it existed only have the url
+fn link_title(link: &HyperLink) -> String {
+    if !link.target.is_empty() {
+        return link.target.trim().to_owned();
+    }
+
+    percent_encoding::percent_decode_str(extract_slug(&link.outlink))
+        .decode_utf8_lossy()
+        .replace('_', " ")
+        .trim()
+        .to_owned()
+}
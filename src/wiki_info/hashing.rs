@@ -0,0 +1,127 @@
+use std::hash::Hasher;
+
+use super::{prepare_document, Page, VectorOptions};
+
+/// Vectorizes pages with the hashing trick: every term is hashed straight into one
+/// of a fixed number of dimensions, so no shared vocabulary pass is needed and pages
+/// can be vectorized one at a time as they stream in.
+/// Vectors from the same vectorizer settings are always comparable with `cosine_sim`
+#[derive(Debug, Clone, PartialEq)]
+pub struct HashingVectorizer {
+    /// Length of every produced vector
+    pub dimensions: usize,
+    /// Use a second hash bit to pick the sign of each term's contribution,
+    /// so colliding terms tend to cancel out instead of piling up
+    pub signed: bool,
+    /// Cleaning and length options, shared with the vocabulary based vectorizer
+    pub options: VectorOptions,
+}
+
+impl HashingVectorizer {
+    /// Creates a signed vectorizer with default options
+    ///
+    /// # Arguments
+    ///
+    /// * `dimensions` - Length of every produced vector, must not be 0
+    pub fn new(dimensions: usize) -> Self {
+        assert!(
+            dimensions > 0,
+            "HashingVectorizer needs at least one dimension"
+        );
+
+        HashingVectorizer {
+            dimensions,
+            signed: true,
+            options: VectorOptions::default(),
+        }
+    }
+
+    /// Convert a Page into its hashed term frequency vector
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page to convert
+    ///
+    /// # Returns
+    ///
+    /// An owned vector of `dimensions` floats
+    pub fn vectorize(&self, page: &Page) -> Vec<f64> {
+        let content = prepare_document(page, &self.options).content;
+        let words: Vec<&str> = content.split_whitespace().collect();
+
+        let mut vector = vec![0.0; self.dimensions];
+        if words.is_empty() {
+            return vector;
+        }
+
+        let weight = 1.0 / words.len() as f64;
+        for word in words {
+            let (index, sign) = self.bucket(word);
+            vector[index] += sign * weight;
+        }
+
+        vector
+    }
+
+    /// Gets the dimension and sign a term is hashed to
+    ///
+    /// # Arguments
+    ///
+    /// * `term` - A cleaned term
+    ///
+    /// # Returns
+    ///
+    /// (index into the vector, +1.0 or -1.0)
+    pub fn bucket(&self, term: &str) -> (usize, f64) {
+        let hash = fnv1a(term.as_bytes());
+
+        let index = (hash % self.dimensions as u64) as usize;
+        // top bit is independent of the low bits used for the index
+        let sign = if self.signed && hash >> 63 == 1 {
+            -1.0
+        } else {
+            1.0
+        };
+
+        (index, sign)
+    }
+}
+
+// std hashers are randomly seeded per process, vectors must be stable across runs
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hasher = Fnv1aHasher::default();
+    hasher.write(bytes);
+
+    hasher.finish()
+}
+
+/// `fnv1a` as a `Hasher`, for hashing whole values the same way in every run
+pub(crate) struct Fnv1aHasher(u64);
+
+impl Default for Fnv1aHasher {
+    fn default() -> Self {
+        Fnv1aHasher(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for Fnv1aHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+// splitmix64, a cheap stateless mix of a u64. Also a deterministic random source,
+// hashing a counter gives a well distributed sequence
+pub(crate) fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,530 @@
+use std::collections::{BTreeSet, HashMap};
+
+use super::{
+    bm25::{bm25_idf, bm25_term, DEFAULT_B, DEFAULT_K1},
+    corpus::Corpus,
+    prepare_document,
+    query::BooleanQuery,
+    Block, Page, VectorOptions, WikiError,
+};
+
+/// How `InvertedIndex::search` weighs a matching term in a document
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IndexScoring {
+    /// Term frequency times the smoothed idf of `Corpus::idf`
+    TfIdf,
+    /// Okapi BM25, see `bm25::bm25_term`
+    Bm25 { k1: f64, b: f64 },
+}
+
+impl Default for IndexScoring {
+    fn default() -> Self {
+        IndexScoring::Bm25 {
+            k1: DEFAULT_K1,
+            b: DEFAULT_B,
+        }
+    }
+}
+
+/// One document containing a term
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Posting {
+    /// Document id, the index of the page in the corpus
+    pub doc: usize,
+    /// Number of times the term appears in the document
+    pub count: usize,
+    /// Every position of the term in the cleaned document, in order
+    pub positions: Vec<usize>,
+}
+
+/// An inverted index over a corpus: for every term the documents containing it,
+/// with the positions of the term in each. Searching only touches the documents
+/// of the query terms, unlike scoring every page vector of the corpus
+#[derive(Debug, Clone, Default)]
+pub struct InvertedIndex {
+    options: VectorOptions,
+    term_ids: HashMap<String, usize>,
+    /// Postings of every term, indexed by term id and sorted by document
+    postings: Vec<Vec<Posting>>,
+    /// Number of cleaned terms in every document
+    lengths: Vec<usize>,
+    titles: Vec<String>,
+    average_length: f64,
+    /// How matching documents are scored
+    pub scoring: IndexScoring,
+}
+
+impl InvertedIndex {
+    /// Indexes every document of a corpus, scoring with BM25.
+    /// Queries are cleaned with the options of the corpus
+    pub fn build(corpus: &Corpus) -> Self {
+        let mut postings = vec![Vec::new(); corpus.terms().len()];
+
+        // documents and positions are visited in order, so postings come out sorted
+        for (doc, document) in corpus.documents().iter().enumerate() {
+            for (position, &term) in document.sequence.iter().enumerate() {
+                let term_postings: &mut Vec<Posting> = &mut postings[term];
+                match term_postings.last_mut() {
+                    Some(posting) if posting.doc == doc => {
+                        posting.count += 1;
+                        posting.positions.push(position);
+                    }
+                    _ => term_postings.push(Posting {
+                        doc,
+                        count: 1,
+                        positions: vec![position],
+                    }),
+                }
+            }
+        }
+
+        let lengths: Vec<usize> = corpus.documents().iter().map(|doc| doc.length).collect();
+        let average_length = if lengths.is_empty() {
+            0.0
+        } else {
+            lengths.iter().sum::<usize>() as f64 / lengths.len() as f64
+        };
+
+        InvertedIndex {
+            options: corpus.options().clone(),
+            term_ids: corpus.vocabulary().as_map().clone(),
+            postings,
+            lengths,
+            titles: corpus
+                .documents()
+                .iter()
+                .map(|doc| doc.title.clone())
+                .collect(),
+            average_length,
+            scoring: IndexScoring::default(),
+        }
+    }
+
+    /// Sets how matching documents are scored
+    pub fn with_scoring(mut self, scoring: IndexScoring) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
+    /// Number of indexed documents
+    pub fn len(&self) -> usize {
+        self.lengths.len()
+    }
+
+    /// Whether no documents are indexed
+    pub fn is_empty(&self) -> bool {
+        self.lengths.is_empty()
+    }
+
+    /// Title of an indexed document
+    pub fn title(&self, doc: usize) -> Option<&str> {
+        self.titles.get(doc).map(String::as_str)
+    }
+
+    /// Number of cleaned terms in an indexed document
+    pub fn document_length(&self, doc: usize) -> usize {
+        self.lengths.get(doc).copied().unwrap_or(0)
+    }
+
+    /// The postings of a cleaned term, sorted by document. Empty for unknown terms
+    pub fn postings(&self, term: &str) -> &[Posting] {
+        match self.term_ids.get(term) {
+            Some(&id) => &self.postings[id],
+            None => &[],
+        }
+    }
+
+    /// Searches the index with a free text query. The query is cleaned like the
+    /// indexed pages and every distinct query term counts once. Quoted phrases
+    /// (`"prime minister of france"`) must appear in a document in order,
+    /// their terms are scored like the rest of the query
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The text to search for
+    ///
+    /// # Returns
+    ///
+    /// (document id, score) for every document containing a query term and
+    /// every phrase, best first and by document id on ties
+    pub fn search(&self, query: &str) -> Vec<(usize, f64)> {
+        // every odd piece between quotes is a phrase
+        let phrases: Vec<BTreeSet<usize>> = query
+            .split('"')
+            .skip(1)
+            .step_by(2)
+            .map(|phrase| {
+                self.phrase_matches(phrase)
+                    .into_iter()
+                    .map(|m| m.0)
+                    .collect()
+            })
+            .collect();
+
+        let mut terms = self.query_terms(&query.replace('"', " "));
+        terms.sort_unstable();
+        terms.dedup();
+
+        let mut scores: HashMap<usize, f64> = HashMap::new();
+        for term in &terms {
+            let postings = self.postings(term);
+            let idf = self.idf(postings.len());
+
+            for posting in postings {
+                if phrases.iter().all(|docs| docs.contains(&posting.doc)) {
+                    *scores.entry(posting.doc).or_insert(0.0) += self.term_score(posting, idf);
+                }
+            }
+        }
+
+        let mut ranked: Vec<(usize, f64)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        ranked
+    }
+
+    /// Finds the documents containing a phrase. The phrase is cleaned like page
+    /// content, so stop words inside it are skipped on both sides
+    ///
+    /// # Arguments
+    ///
+    /// * `phrase` - The words to find in order, ie `"prime minister of france"`
+    ///
+    /// # Returns
+    ///
+    /// (document id, number of occurrences) for every document containing the
+    /// phrase, in document order. Empty if nothing is left of the phrase after cleaning
+    pub fn phrase_matches(&self, phrase: &str) -> Vec<(usize, usize)> {
+        let terms = self.query_terms(phrase);
+        let Some((first, rest)) = terms.split_first() else {
+            return Vec::new();
+        };
+
+        let rest: Vec<&[Posting]> = rest.iter().map(|term| self.postings(term)).collect();
+
+        self.postings(first)
+            .iter()
+            .filter_map(|posting| {
+                // the posting of every later term in this document
+                let later = rest
+                    .iter()
+                    .map(|postings| {
+                        let found = postings.binary_search_by_key(&posting.doc, |p| p.doc);
+                        found.ok().map(|i| &postings[i].positions)
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+
+                let occurrences = posting
+                    .positions
+                    .iter()
+                    .filter(|&&start| {
+                        later.iter().enumerate().all(|(offset, positions)| {
+                            positions.binary_search(&(start + offset + 1)).is_ok()
+                        })
+                    })
+                    .count();
+
+                (occurrences > 0).then_some((posting.doc, occurrences))
+            })
+            .collect()
+    }
+
+    /// Finds the documents matching a boolean query, ie to filter the corpus
+    /// before ranking by similarity. See `query::BooleanQuery` for the syntax
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The boolean query text
+    ///
+    /// # Returns
+    ///
+    /// Ok(Vec<usize>) - the ids of the matching documents, in order
+    /// Err(WikiError::InputError) - error if the query does not parse
+    pub fn search_boolean(&self, query: &str) -> Result<Vec<usize>, WikiError> {
+        let query = BooleanQuery::parse(query)?;
+
+        Ok(self.matching(&query).into_iter().collect())
+    }
+
+    /// The documents matching a parsed boolean query. A term that cleans to
+    /// several terms (`"e-mail"`) needs all of them, a term that cleans to
+    /// nothing (a stop word) matches every document
+    pub fn matching(&self, query: &BooleanQuery) -> BTreeSet<usize> {
+        match query {
+            BooleanQuery::Phrase(phrase) => {
+                if self.query_terms(phrase).is_empty() {
+                    return (0..self.len()).collect();
+                }
+
+                self.phrase_matches(phrase)
+                    .into_iter()
+                    .map(|m| m.0)
+                    .collect()
+            }
+            BooleanQuery::Term(term) => {
+                let mut matching: BTreeSet<usize> = (0..self.len()).collect();
+                for term in self.query_terms(term) {
+                    let docs: BTreeSet<usize> = self
+                        .postings(&term)
+                        .iter()
+                        .map(|posting| posting.doc)
+                        .collect();
+                    matching = &matching & &docs;
+                }
+
+                matching
+            }
+            BooleanQuery::And(a, b) => &self.matching(a) & &self.matching(b),
+            BooleanQuery::Or(a, b) => &self.matching(a) | &self.matching(b),
+            BooleanQuery::Not(a) => {
+                let all: BTreeSet<usize> = (0..self.len()).collect();
+                &all - &self.matching(a)
+            }
+        }
+    }
+
+    // a query is cleaned as a page holding only the query text
+    fn query_terms(&self, query: &str) -> Vec<String> {
+        let query = Page {
+            content: query.to_owned(),
+            ..Default::default()
+        };
+
+        prepare_document(&query, &self.options)
+            .content
+            .split_whitespace()
+            .map(str::to_owned)
+            .collect()
+    }
+
+    fn idf(&self, frequency: usize) -> f64 {
+        match self.scoring {
+            IndexScoring::TfIdf => {
+                ((1.0 + self.len() as f64) / (1.0 + frequency as f64)).ln() + 1.0
+            }
+            IndexScoring::Bm25 { .. } => bm25_idf(self.len(), frequency),
+        }
+    }
+
+    fn term_score(&self, posting: &Posting, idf: f64) -> f64 {
+        let tf = posting.count as f64;
+        let length = self.lengths[posting.doc] as f64;
+
+        match self.scoring {
+            IndexScoring::TfIdf => tf / length * idf,
+            IndexScoring::Bm25 { k1, b } => bm25_term(tf, idf, length, self.average_length, k1, b),
+        }
+    }
+}
+
+/// A field of the pages in a `FieldedIndex`, named `title:`, `headings:` or `body:`
+/// in queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SearchField {
+    Title,
+    /// Every section heading, only known for pages parsed with `ParseOptions::structured`
+    Headings,
+    Body,
+}
+
+impl SearchField {
+    const ALL: [SearchField; 3] = [SearchField::Title, SearchField::Headings, SearchField::Body];
+
+    /// Gets a field by its query name, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "title" => Some(SearchField::Title),
+            "headings" | "heading" => Some(SearchField::Headings),
+            "body" => Some(SearchField::Body),
+            _ => None,
+        }
+    }
+
+    fn text(&self, page: &Page) -> String {
+        match self {
+            SearchField::Title => page.title.clone(),
+            SearchField::Headings => page
+                .blocks
+                .iter()
+                .filter_map(|block| match block {
+                    Block::Heading { text, .. } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join(" "),
+            SearchField::Body => page.content.clone(),
+        }
+    }
+}
+
+/// How much a match in each field of a `FieldedIndex` counts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldBoosts {
+    pub title: f64,
+    pub headings: f64,
+    pub body: f64,
+}
+
+impl Default for FieldBoosts {
+    fn default() -> Self {
+        FieldBoosts {
+            title: 3.0,
+            headings: 2.0,
+            body: 1.0,
+        }
+    }
+}
+
+impl FieldBoosts {
+    fn boost(&self, field: SearchField) -> f64 {
+        match field {
+            SearchField::Title => self.title,
+            SearchField::Headings => self.headings,
+            SearchField::Body => self.body,
+        }
+    }
+}
+
+/// An inverted index per field of a set of pages, searched with fielded queries
+/// like `title:rust body:ownership`. Document ids are indices into the pages
+/// the index was built from
+#[derive(Debug, Clone)]
+pub struct FieldedIndex {
+    fields: [InvertedIndex; 3],
+    /// How much a match in each field counts
+    pub boosts: FieldBoosts,
+}
+
+impl FieldedIndex {
+    /// Indexes the title, headings and body of every page, scoring with BM25
+    ///
+    /// # Arguments
+    ///
+    /// * `pages` - The pages to index
+    /// * `options` - How every field and the queries are cleaned
+    pub fn build(pages: &[Page], options: &VectorOptions) -> Self {
+        let fields = SearchField::ALL.map(|field| {
+            let mut corpus = Corpus::with_options(options.clone());
+            for page in pages {
+                let text = Page {
+                    title: page.title.clone(),
+                    content: field.text(page),
+                    ..Default::default()
+                };
+                corpus.add_page(&text);
+            }
+
+            InvertedIndex::build(&corpus)
+        });
+
+        FieldedIndex {
+            fields,
+            boosts: FieldBoosts::default(),
+        }
+    }
+
+    /// Sets the boost of every field
+    pub fn with_boosts(mut self, boosts: FieldBoosts) -> Self {
+        self.boosts = boosts;
+        self
+    }
+
+    /// Sets how matching documents are scored in every field
+    pub fn with_scoring(mut self, scoring: IndexScoring) -> Self {
+        for index in self.fields.iter_mut() {
+            index.scoring = scoring;
+        }
+        self
+    }
+
+    /// The index of a single field
+    pub fn field(&self, field: SearchField) -> &InvertedIndex {
+        &self.fields[field as usize]
+    }
+
+    /// Number of indexed documents
+    pub fn len(&self) -> usize {
+        self.fields[0].len()
+    }
+
+    /// Whether no documents are indexed
+    pub fn is_empty(&self) -> bool {
+        self.fields[0].is_empty()
+    }
+
+    /// Searches every field with a fielded query. Terms and quoted phrases
+    /// prefixed with a field name (`title:rust`, `body:"borrow checker"`) only
+    /// match that field, anything else matches any field. Each field is searched
+    /// like `InvertedIndex::search` and its scores are boosted and summed
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The fielded query
+    ///
+    /// # Returns
+    ///
+    /// (document id, score) for every matching document, best first and by
+    /// document id on ties
+    pub fn search(&self, query: &str) -> Vec<(usize, f64)> {
+        let mut field_queries: [String; 3] = Default::default();
+
+        for token in query_tokens(query) {
+            let (fields, text) = match token.split_once(':') {
+                Some((name, text)) => match SearchField::from_name(name) {
+                    Some(field) => (vec![field], text),
+                    None => (SearchField::ALL.to_vec(), token.as_str()),
+                },
+                None => (SearchField::ALL.to_vec(), token.as_str()),
+            };
+
+            for field in fields {
+                let field_query = &mut field_queries[field as usize];
+                field_query.push_str(text);
+                field_query.push(' ');
+            }
+        }
+
+        let mut scores: HashMap<usize, f64> = HashMap::new();
+        for field in SearchField::ALL {
+            let field_query = &field_queries[field as usize];
+            if field_query.trim().is_empty() {
+                continue;
+            }
+
+            for (doc, score) in self.field(field).search(field_query) {
+                *scores.entry(doc).or_insert(0.0) += self.boosts.boost(field) * score;
+            }
+        }
+
+        let mut ranked: Vec<(usize, f64)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        ranked
+    }
+}
+
+// splits a query on whitespace, keeping quoted phrases in one token with their quotes
+fn query_tokens(query: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+
+    for c in query.chars() {
+        if c == '"' {
+            quoted = !quoted;
+        }
+
+        if c.is_whitespace() && !quoted {
+            if !token.is_empty() {
+                tokens.push(std::mem::take(&mut token));
+            }
+        } else {
+            token.push(c);
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+
+    tokens
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use log::debug;
+
+use super::{clean_document, Page};
+
+// words within this distance of each other are linked in the co-occurrence graph
+const WINDOW: usize = 4;
+const DAMPING: f64 = 0.85;
+const MAX_ITERATIONS: usize = 50;
+const TOLERANCE: f64 = 1e-6;
+
+/// Extracts the topical keywords of a page with TextRank: words of the cleaned
+/// content are ranked by PageRank over a graph linking words that appear near
+/// each other, so words central to the page outrank words that are merely frequent
+///
+/// # Arguments
+///
+/// * `page` - the page to extract keywords from
+/// * `k` - the maximum number of keywords
+///
+/// # Returns
+///
+/// Up to `k` (keyword, score) pairs, best first
+pub fn extract_keywords(page: &Page, k: usize) -> Vec<(String, f64)> {
+    let content = clean_document(page).content;
+    let words: Vec<&str> = content.split_whitespace().collect();
+
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+    let mut terms: Vec<&str> = Vec::new();
+    let sequence: Vec<usize> = words
+        .iter()
+        .map(|&word| {
+            *ids.entry(word).or_insert_with(|| {
+                terms.push(word);
+                terms.len() - 1
+            })
+        })
+        .collect();
+
+    // undirected edges weighted by how often the words co-occur
+    let mut edges: Vec<HashMap<usize, f64>> = vec![HashMap::new(); terms.len()];
+    for (i, &a) in sequence.iter().enumerate() {
+        for &b in sequence.iter().skip(i + 1).take(WINDOW - 1) {
+            if a != b {
+                *edges[a].entry(b).or_insert(0.0) += 1.0;
+                *edges[b].entry(a).or_insert(0.0) += 1.0;
+            }
+        }
+    }
+
+    let out_weight: Vec<f64> = edges.iter().map(|edge| edge.values().sum()).collect();
+    let mut scores = vec![1.0; terms.len()];
+
+    for iteration in 0..MAX_ITERATIONS {
+        let next: Vec<f64> = edges
+            .iter()
+            .map(|neighbours| {
+                let rank: f64 = neighbours
+                    .iter()
+                    .map(|(&j, &weight)| weight / out_weight[j] * scores[j])
+                    .sum();
+
+                (1.0 - DAMPING) + DAMPING * rank
+            })
+            .collect();
+
+        let change: f64 = next.iter().zip(&scores).map(|(a, b)| (a - b).abs()).sum();
+        scores = next;
+
+        if change < TOLERANCE {
+            debug!("TextRank converged after {} iterations", iteration + 1);
+            break;
+        }
+    }
+
+    let mut keywords: Vec<(String, f64)> =
+        terms.into_iter().map(str::to_owned).zip(scores).collect();
+
+    keywords.sort_by(|(a_term, a), (b_term, b)| b.total_cmp(a).then_with(|| a_term.cmp(b_term)));
+    keywords.truncate(k);
+
+    keywords
+}
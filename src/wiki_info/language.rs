@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use super::Page;
+
+// detection is stable long before this, no need to scan whole articles
+const SAMPLE_CHARS: usize = 4000;
+
+/// The detected language of a text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguageTag {
+    /// ISO 639-3 code, ie `"eng"`, or `"und"` if no language could be detected
+    pub code: String,
+    /// English name of the language, ie `"English"`
+    pub name: String,
+    /// Detection confidence from 0 to 1
+    pub confidence: f64,
+    /// Whether the detection is confident enough to act on
+    pub is_reliable: bool,
+}
+
+impl LanguageTag {
+    /// The tag for text whose language could not be determined
+    pub fn undetermined() -> Self {
+        LanguageTag {
+            code: "und".to_owned(),
+            name: "Undetermined".to_owned(),
+            confidence: 0.0,
+            is_reliable: false,
+        }
+    }
+
+    /// Whether this tag is for a detected language
+    pub fn is_determined(&self) -> bool {
+        self.code != "und"
+    }
+}
+
+/// Detects the language of a page from its content, so pages from several
+/// wiki editions can be routed to the right stop words and stemmer
+///
+/// # Arguments
+///
+/// * `page` - the page to detect the language of
+///
+/// # Returns
+///
+/// The detected LanguageTag, `LanguageTag::undetermined()` for empty or ambiguous content
+pub fn detect_language(page: &Page) -> LanguageTag {
+    let sample: String = page.content.chars().take(SAMPLE_CHARS).collect();
+
+    match whatlang::detect(&sample) {
+        Some(info) => LanguageTag {
+            code: info.lang().code().to_owned(),
+            name: info.lang().eng_name().to_owned(),
+            confidence: info.confidence(),
+            is_reliable: info.is_reliable(),
+        },
+        None => LanguageTag::undetermined(),
+    }
+}
@@ -0,0 +1,176 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fs,
+    hash::{Hash, Hasher},
+    path::Path,
+    sync::Arc,
+};
+
+use lazy_static::lazy_static;
+
+use super::WikiError;
+
+lazy_static! {
+    static ref ENGLISH: Lemmatizer = Lemmatizer {
+        lemmas: Arc::new(parse_lemmas(include_str!("../../data/lemmas_en.tsv"))),
+        plural_rules: true,
+    };
+}
+
+// words ending in s that are not plurals
+const NOT_PLURAL: [&str; 12] = [
+    "series", "species", "news", "its", "this", "his", "has", "was", "gas", "bus", "yes", "lens",
+];
+
+/// A dictionary based lemmatizer, maps inflected forms to their dictionary form
+/// (`"went"` to `"go"`, `"cities"` to `"city"`). Unlike a stemmer it never produces
+/// non-words and leaves unknown words alone, so unrelated words are not conflated.
+/// Requires the `lemmatizer` feature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lemmatizer {
+    lemmas: Arc<HashMap<String, String>>,
+    plural_rules: bool,
+}
+
+// hashed sorted, hash map order differs between runs
+impl Hash for Lemmatizer {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut lemmas: Vec<(&String, &String)> = self.lemmas.iter().collect();
+        lemmas.sort();
+
+        lemmas.hash(state);
+        self.plural_rules.hash(state);
+    }
+}
+
+impl Lemmatizer {
+    /// The built in english lemmatizer: a dictionary of irregular forms
+    /// plus rules for regular plural nouns
+    pub fn english() -> Self {
+        ENGLISH.clone()
+    }
+
+    /// Builds a lemmatizer from `(form, lemma)` pairs
+    pub fn from_pairs<I, S>(pairs: I) -> Self
+    where
+        I: IntoIterator<Item = (S, S)>,
+        S: AsRef<str>,
+    {
+        let lemmas = pairs
+            .into_iter()
+            .map(|(form, lemma)| (form.as_ref().to_lowercase(), lemma.as_ref().to_lowercase()))
+            .collect();
+
+        Lemmatizer {
+            lemmas: Arc::new(lemmas),
+            plural_rules: false,
+        }
+    }
+
+    /// Loads a dictionary with one `form<TAB>lemma` pair per line, the format of
+    /// the common lemmatization lists. Lines starting with `#` are ignored.
+    /// Only the dictionary is used, see `with_plural_rules`
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path of the dictionary
+    ///
+    /// # Returns
+    ///
+    /// Ok(Lemmatizer) - the loaded lemmatizer
+    /// Err(WikiError::IOError) - error if the file could not be read
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, WikiError> {
+        let contents =
+            fs::read_to_string(path).map_err(|err| WikiError::IOError(err.to_string()))?;
+
+        Ok(Lemmatizer {
+            lemmas: Arc::new(parse_lemmas(&contents)),
+            plural_rules: false,
+        })
+    }
+
+    /// Whether words missing from the dictionary are singularized by the
+    /// english plural rules (`"governments"` to `"government"`)
+    pub fn with_plural_rules(mut self, plural_rules: bool) -> Self {
+        self.plural_rules = plural_rules;
+        self
+    }
+
+    /// Number of forms in the dictionary
+    pub fn len(&self) -> usize {
+        self.lemmas.len()
+    }
+
+    /// Whether the dictionary is empty
+    pub fn is_empty(&self) -> bool {
+        self.lemmas.is_empty()
+    }
+
+    /// Lemmatizes a single lowercase word
+    ///
+    /// # Arguments
+    ///
+    /// * `word` - the word to lemmatize
+    ///
+    /// # Returns
+    ///
+    /// The lemma, borrowed if the word is unchanged
+    pub fn lemmatize<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        if let Some(lemma) = self.lemmas.get(word) {
+            return Cow::Owned(lemma.clone());
+        }
+
+        if self.plural_rules {
+            singular(word)
+        } else {
+            Cow::Borrowed(word)
+        }
+    }
+}
+
+fn parse_lemmas(contents: &str) -> HashMap<String, String> {
+    contents
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split('\t').map(str::trim);
+            let form = fields.next().filter(|form| !form.is_empty())?;
+            let lemma = fields.next().filter(|lemma| !lemma.is_empty())?;
+
+            Some((form.to_lowercase(), lemma.to_lowercase()))
+        })
+        .collect()
+}
+
+// conservative english plural rules, words that only look plural are left alone
+fn singular(word: &str) -> Cow<'_, str> {
+    if word.len() <= 3
+        || NOT_PLURAL.contains(&word)
+        || word.ends_with("ss")
+        || word.ends_with("us")
+        || word.ends_with("is")
+        || word.ends_with("ics")
+    {
+        return Cow::Borrowed(word);
+    }
+
+    if let Some(stem) = word.strip_suffix("ies") {
+        return Cow::Owned(format!("{}y", stem));
+    }
+
+    if let Some(stem) = word.strip_suffix("es") {
+        if stem.ends_with("ch")
+            || stem.ends_with("sh")
+            || stem.ends_with('x')
+            || stem.ends_with("ss")
+        {
+            return Cow::Borrowed(stem);
+        }
+    }
+
+    match word.strip_suffix('s') {
+        Some(stem) => Cow::Borrowed(stem),
+        None => Cow::Borrowed(word),
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::HashSet;
+
+use super::{Block, HyperLink, Page};
+
+// sections of a list article that are not part of the list
+const NON_ENTRY_SECTIONS: [&str; 7] = [
+    "See also",
+    "Notes",
+    "References",
+    "Sources",
+    "Further reading",
+    "External links",
+    "Bibliography",
+];
+
+/// Gets the entries of a "List of ..." page, ie to use as candidates for
+/// `get_most_similar_page`. An entry is the first article link of every list item,
+/// and of every table row. Items in the trailing sections ("See also", "References",
+/// etc) are left out.
+/// Needs the page's structure, so the page must be parsed with `ParseOptions::structured`
+///
+/// # Arguments
+///
+/// * `page` - a structured list page
+///
+/// # Returns
+///
+/// The entry links in page order, without duplicates. Each link's `position` is
+/// its index in the returned list
+pub fn extract_list_items(page: &Page) -> Vec<HyperLink> {
+    let mut entries: Vec<HyperLink> = Vec::new();
+    let mut seen = HashSet::new();
+    let mut in_entries = true;
+
+    for block in &page.blocks {
+        let candidates: Vec<&HyperLink> = match block {
+            Block::Heading { level: 2, text } => {
+                in_entries = !NON_ENTRY_SECTIONS.contains(&text.as_str());
+                continue;
+            }
+            _ if !in_entries => continue,
+            Block::ListItem { links, .. } => links.first().into_iter().collect(),
+            Block::Table(table) => table
+                .rows
+                .iter()
+                .filter_map(|row| row.iter().find_map(|cell| cell.links.first()))
+                .collect(),
+            _ => continue,
+        };
+
+        for link in candidates {
+            if seen.insert(link.outlink.clone()) {
+                entries.push(HyperLink {
+                    position: entries.len(),
+                    ..link.clone()
+                });
+            }
+        }
+    }
+
+    entries
+}
@@ -0,0 +1,317 @@
+use log::debug;
+use rayon::prelude::*;
+
+use super::{corpus::Corpus, cosine_sim, hashing::splitmix64, matrix::Matrix, Page};
+
+// a few extra directions make the power iteration converge much faster
+const OVERSAMPLING: usize = 10;
+const POWER_ITERATIONS: usize = 8;
+const JACOBI_SWEEPS: usize = 50;
+
+/// A latent semantic analysis of a corpus: the truncated SVD of its TF-IDF
+/// document-term matrix. Pages become short dense vectors in which terms that
+/// occur in similar documents are merged, so related pages match even when they
+/// share few exact words, and the noise of sparse term vectors is smoothed out
+#[derive(Debug, Clone)]
+pub struct Lsa<'a> {
+    corpus: &'a Corpus,
+    /// Left singular vectors scaled by the singular values, one row per document
+    document_vectors: Matrix<f64>,
+    /// Right singular vectors `V = X^T U S^-1`, one row per term.
+    /// Multiplying a TF-IDF vector by this gives its latent vector
+    term_vectors: Matrix<f64>,
+    singular_values: Vec<f64>,
+}
+
+impl Corpus {
+    /// Runs latent semantic analysis over the corpus, see `Lsa`
+    ///
+    /// # Arguments
+    ///
+    /// * `dimensions` - Number of latent dimensions, typically 100 to 300 for large
+    ///   corpora. Capped at the number of documents and terms
+    ///
+    /// # Returns
+    ///
+    /// The analysis, borrowing the corpus to vectorize new pages
+    pub fn lsa(&self, dimensions: usize) -> Lsa<'_> {
+        let dimensions = dimensions.min(self.len()).min(self.terms().len());
+        debug!(
+            "Running LSA with {} dimensions over {} documents",
+            dimensions,
+            self.len()
+        );
+
+        let rows: Vec<Vec<(usize, f64)>> =
+            (0..self.len()).map(|doc| self.tf_idf_row(doc)).collect();
+        let (singular_values, left) = truncated_svd(&rows, self.terms().len(), dimensions);
+        let k = singular_values.len();
+
+        let mut document_vectors = Matrix::filled(self.len(), k, 0.0);
+        for doc in 0..self.len() {
+            for dim in 0..k {
+                document_vectors[(doc, dim)] = left[(doc, dim)] * singular_values[dim];
+            }
+        }
+
+        // V = X^T U S^-1, so x V lands in the same space as the rows of U S
+        let mut term_vectors = Matrix::filled(self.terms().len(), k, 0.0);
+        for (doc, row) in rows.iter().enumerate() {
+            for &(term, weight) in row {
+                for dim in 0..k {
+                    term_vectors[(term, dim)] += weight * left[(doc, dim)] / singular_values[dim];
+                }
+            }
+        }
+
+        Lsa {
+            corpus: self,
+            document_vectors,
+            term_vectors,
+            singular_values,
+        }
+    }
+}
+
+impl Lsa<'_> {
+    /// Number of latent dimensions, can be less than asked for on tiny corpora
+    pub fn dimensions(&self) -> usize {
+        self.singular_values.len()
+    }
+
+    /// The singular values, largest first
+    pub fn singular_values(&self) -> &[f64] {
+        &self.singular_values
+    }
+
+    /// The latent vector of a document of the corpus
+    pub fn document_vector(&self, doc: usize) -> &[f64] {
+        self.document_vectors.row(doc)
+    }
+
+    /// Folds any page into the latent space through its TF-IDF vector.
+    /// Terms outside the corpus vocabulary are ignored
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page to convert
+    ///
+    /// # Returns
+    ///
+    /// A `dimensions` long latent vector
+    pub fn vectorize(&self, page: &Page) -> Vec<f64> {
+        let mut latent = vec![0.0; self.dimensions()];
+
+        for (term, weight) in self.corpus.vectorize(page).into_iter().enumerate() {
+            if weight != 0.0 {
+                for (value, term_value) in latent.iter_mut().zip(self.term_vectors.row(term)) {
+                    *value += weight * term_value;
+                }
+            }
+        }
+
+        latent
+    }
+
+    /// The cosine similarity of two pages in latent space
+    ///
+    /// # Returns
+    ///
+    /// The similarity [-1, 1], 0 if either page has no vocabulary terms
+    pub fn similarity(&self, a: &Page, b: &Page) -> f64 {
+        zero_if_nan(cosine_sim(&self.vectorize(a), &self.vectorize(b)))
+    }
+
+    /// The cosine similarity of two documents of the corpus in latent space
+    ///
+    /// # Returns
+    ///
+    /// The similarity [-1, 1], 0 if either document has no terms
+    pub fn document_similarity(&self, a: usize, b: usize) -> f64 {
+        zero_if_nan(cosine_sim(self.document_vector(a), self.document_vector(b)))
+    }
+}
+
+fn zero_if_nan(sim: f64) -> f64 {
+    if sim.is_nan() {
+        0.0
+    } else {
+        sim
+    }
+}
+
+/// Randomized truncated SVD of a sparse matrix (Halko et al.): power iteration
+/// finds a small basis for the dominant rows, then the SVD of the matrix projected
+/// onto that basis is solved exactly
+///
+/// # Returns
+///
+/// (the k largest non zero singular values, the matching left singular vectors as columns)
+fn truncated_svd(rows: &[Vec<(usize, f64)>], cols: usize, k: usize) -> (Vec<f64>, Matrix<f64>) {
+    let n = rows.len();
+    let width = (k + OVERSAMPLING).min(n);
+
+    // deterministic start, results must not change between runs
+    let mut basis = Matrix::filled(n, width, 0.0);
+    for i in 0..n {
+        for j in 0..width {
+            basis[(i, j)] = pseudo_random((i * width + j) as u64);
+        }
+    }
+    orthonormalize(&mut basis);
+
+    for _ in 0..POWER_ITERATIONS {
+        let projected = transpose_times(rows, &basis, cols);
+        basis = times(rows, &projected);
+        orthonormalize(&mut basis);
+    }
+
+    // B B^T = Q^T X X^T Q, whose eigenvalues are the squared singular values
+    let projected = transpose_times(rows, &basis, cols);
+    let mut gram = Matrix::filled(width, width, 0.0);
+    for i in 0..width {
+        for j in i..width {
+            let dot: f64 = (0..cols)
+                .map(|t| projected[(t, i)] * projected[(t, j)])
+                .sum();
+            gram[(i, j)] = dot;
+            gram[(j, i)] = dot;
+        }
+    }
+    let (eigenvalues, eigenvectors) = symmetric_eigen(gram);
+
+    let mut order: Vec<usize> = (0..width).collect();
+    order.sort_by(|&a, &b| eigenvalues[b].total_cmp(&eigenvalues[a]));
+    order.retain(|&i| eigenvalues[i] > 1e-12);
+    order.truncate(k);
+
+    let singular_values: Vec<f64> = order.iter().map(|&i| eigenvalues[i].sqrt()).collect();
+
+    let mut left = Matrix::filled(n, order.len(), 0.0);
+    for doc in 0..n {
+        for (dim, &i) in order.iter().enumerate() {
+            left[(doc, dim)] = (0..width)
+                .map(|j| basis[(doc, j)] * eigenvectors[(j, i)])
+                .sum();
+        }
+    }
+
+    (singular_values, left)
+}
+
+// X^T Q, cols x width
+fn transpose_times(rows: &[Vec<(usize, f64)>], basis: &Matrix<f64>, cols: usize) -> Matrix<f64> {
+    let width = basis.cols();
+    let mut out = Matrix::filled(cols, width, 0.0);
+
+    for (doc, row) in rows.iter().enumerate() {
+        for &(term, weight) in row {
+            for j in 0..width {
+                out[(term, j)] += weight * basis[(doc, j)];
+            }
+        }
+    }
+
+    out
+}
+
+// X Z, documents x width
+fn times(rows: &[Vec<(usize, f64)>], projected: &Matrix<f64>) -> Matrix<f64> {
+    let width = projected.cols();
+
+    let data: Vec<f64> = rows
+        .par_iter()
+        .flat_map_iter(|row| {
+            (0..width).map(move |j| {
+                row.iter()
+                    .map(|&(term, weight)| weight * projected[(term, j)])
+                    .sum::<f64>()
+            })
+        })
+        .collect();
+
+    Matrix::from_vec(rows.len(), width, data)
+}
+
+// modified Gram-Schmidt over the columns, dependent columns are zeroed
+fn orthonormalize(matrix: &mut Matrix<f64>) {
+    let (n, width) = (matrix.rows(), matrix.cols());
+
+    for j in 0..width {
+        for previous in 0..j {
+            let dot: f64 = (0..n).map(|i| matrix[(i, j)] * matrix[(i, previous)]).sum();
+            for i in 0..n {
+                matrix[(i, j)] -= dot * matrix[(i, previous)];
+            }
+        }
+
+        let norm = (0..n).map(|i| matrix[(i, j)].powi(2)).sum::<f64>().sqrt();
+        for i in 0..n {
+            matrix[(i, j)] = if norm > 1e-12 {
+                matrix[(i, j)] / norm
+            } else {
+                0.0
+            };
+        }
+    }
+}
+
+/// Cyclic Jacobi eigenvalue algorithm for a small symmetric matrix
+///
+/// # Returns
+///
+/// (eigenvalues, eigenvectors as columns), unordered
+fn symmetric_eigen(mut a: Matrix<f64>) -> (Vec<f64>, Matrix<f64>) {
+    let n = a.rows();
+    let mut vectors = Matrix::filled(n, n, 0.0);
+    for i in 0..n {
+        vectors[(i, i)] = 1.0;
+    }
+
+    for _ in 0..JACOBI_SWEEPS {
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[(i, j)].powi(2))
+            .sum();
+        if off_diagonal < 1e-20 {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[(p, q)].abs() < 1e-300 {
+                    continue;
+                }
+
+                let theta = (a[(q, q)] - a[(p, p)]) / (2.0 * a[(p, q)]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let t = if theta == 0.0 { 1.0 } else { t };
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for k in 0..n {
+                    let (akp, akq) = (a[(k, p)], a[(k, q)]);
+                    a[(k, p)] = c * akp - s * akq;
+                    a[(k, q)] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let (apk, aqk) = (a[(p, k)], a[(q, k)]);
+                    a[(p, k)] = c * apk - s * aqk;
+                    a[(q, k)] = s * apk + c * aqk;
+                }
+                for k in 0..n {
+                    let (vkp, vkq) = (vectors[(k, p)], vectors[(k, q)]);
+                    vectors[(k, p)] = c * vkp - s * vkq;
+                    vectors[(k, q)] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    ((0..n).map(|i| a[(i, i)]).collect(), vectors)
+}
+
+// mapped to [-1, 1)
+fn pseudo_random(index: u64) -> f64 {
+    (splitmix64(index) >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+}
@@ -0,0 +1,121 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+    static ref ARTICLE_ID: Regex = Regex::new(r#""wgArticleId":(\d+)"#).unwrap();
+    static ref REVISION_ID: Regex = Regex::new(r#""wgRevisionId":(\d+)"#).unwrap();
+    static ref RESTRICTION_EDIT: Regex =
+        Regex::new(r#""wgRestrictionEdit":\[([^\]]*)\]"#).unwrap();
+    static ref RESTRICTION_MOVE: Regex =
+        Regex::new(r#""wgRestrictionMove":\[([^\]]*)\]"#).unwrap();
+    // "This page was last edited on 12 October 2024, at 10:05"
+    static ref LAST_EDITED: Regex =
+        Regex::new(r"(\d{1,2}) ([A-Z][a-z]+) (\d{4}), at (\d{2}):(\d{2})").unwrap();
+}
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Metadata about a fetched page, for deciding when cached copies are stale
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
+pub struct PageMeta {
+    /// Stable id of the page, survives renames
+    pub page_id: Option<u64>,
+    /// Id of the revision that was fetched, changes with every edit
+    pub revision_id: Option<u64>,
+    /// When the page was last edited, as an ISO 8601 UTC timestamp
+    /// (`2024-10-12T10:05:00Z`) so timestamps compare as strings
+    pub last_modified: Option<String>,
+    /// Group allowed to edit the page, ie `autoconfirmed`, None if unprotected
+    pub edit_protection: Option<String>,
+    /// Group allowed to move the page, None if unprotected
+    pub move_protection: Option<String>,
+    /// The entry of `CONTENT_SELECTORS` that found the content root, for debugging
+    /// pages rendered by other skins
+    pub content_selector: Option<String>,
+}
+
+impl PageMeta {
+    /// Whether editing the page is restricted to some group
+    pub fn is_protected(&self) -> bool {
+        self.edit_protection.is_some()
+    }
+}
+
+/// Reads page metadata from a full fetched document.
+/// The ids and protection come from the page config script in the head,
+/// the last edit time from the footer
+///
+/// # Arguments
+///
+/// * `document` - the whole fetched page, not just the content element
+///
+/// # Returns
+///
+/// The metadata, with None for anything that was not found
+pub fn parse_page_meta(document: &Html) -> PageMeta {
+    let script_selector = Selector::parse("script").unwrap();
+    let lastmod_selector = Selector::parse("#footer-info-lastmod").unwrap();
+
+    let config = document
+        .select(&script_selector)
+        .map(|script| script.text().collect::<String>())
+        .find(|script| script.contains("wgArticleId"))
+        .unwrap_or_default();
+
+    let id = |regex: &Regex| {
+        regex
+            .captures(&config)
+            .and_then(|captures| captures[1].parse().ok())
+    };
+    // the first (strongest) group in a restriction list, ie ["sysop"]
+    let restriction = |regex: &Regex| {
+        let captures = regex.captures(&config)?;
+        let group = captures[1].split(',').next()?.trim().trim_matches('"');
+
+        (!group.is_empty()).then(|| group.to_owned())
+    };
+
+    let last_modified = document
+        .select(&lastmod_selector)
+        .next()
+        .and_then(|footer| parse_last_edited(&footer.text().collect::<String>()));
+
+    PageMeta {
+        page_id: id(&ARTICLE_ID),
+        revision_id: id(&REVISION_ID),
+        last_modified,
+        edit_protection: restriction(&RESTRICTION_EDIT),
+        move_protection: restriction(&RESTRICTION_MOVE),
+        content_selector: None,
+    }
+}
+
+// footer text to an ISO 8601 timestamp, the footer is always in UTC
+fn parse_last_edited(text: &str) -> Option<String> {
+    let captures = LAST_EDITED.captures(text)?;
+    let month = MONTHS.iter().position(|&month| month == &captures[2])? + 1;
+
+    Some(format!(
+        "{}-{:02}-{:02}T{}:{}:00Z",
+        &captures[3],
+        month,
+        captures[1].parse::<u32>().ok()?,
+        &captures[4],
+        &captures[5]
+    ))
+}
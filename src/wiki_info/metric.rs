@@ -0,0 +1,379 @@
+use std::{collections::HashMap, sync::Arc};
+
+use rayon::prelude::*;
+
+use super::{
+    bm25::{bm25_idf, bm25_term, DEFAULT_B, DEFAULT_K1},
+    cosine_sim, count_terms, prepare_document, Page, VectorOptions,
+};
+
+/// A page vectorized together with the pages it is compared to, see `vectorize_pages`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageVector {
+    /// Count of every term of the shared vocabulary in the page
+    pub counts: Vec<f64>,
+    /// Number of terms in the page after cleaning
+    pub length: f64,
+    /// Statistics of the set of pages vectorized together
+    pub collection: Arc<CollectionStats>,
+}
+
+/// Statistics of a set of pages vectorized together, for metrics weighting terms by rarity
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectionStats {
+    /// Number of pages
+    pub documents: usize,
+    /// Number of pages containing each vocabulary term
+    pub document_frequencies: Vec<usize>,
+    /// Total count of each vocabulary term over all pages
+    pub collection_frequencies: Vec<usize>,
+    /// Average number of terms per page
+    pub average_length: f64,
+}
+
+impl PageVector {
+    /// The term frequencies of the page, counts divided by the page length.
+    /// The same values as `page_to_vec_with`
+    pub fn term_frequencies(&self) -> Vec<f64> {
+        if self.length == 0.0 {
+            return vec![0.0; self.counts.len()];
+        }
+
+        self.counts
+            .iter()
+            .map(|count| count / self.length)
+            .collect()
+    }
+}
+
+/// Scores how similar two page vectors are, see `get_most_similar_page_by`.
+/// Implement it to try a different metric without touching the vectorizer
+pub trait SimilarityMetric: Send + Sync {
+    /// Scores two vectors built over the same vocabulary
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The primary page, ie the query
+    /// * `b` - The candidate page
+    ///
+    /// # Returns
+    ///
+    /// The score, higher is more similar. Need not be symmetric or bounded
+    fn score(&self, a: &PageVector, b: &PageVector) -> f64;
+}
+
+/// Cosine similarity of the term frequencies, what `get_page_similarity` uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Cosine;
+
+impl SimilarityMetric for Cosine {
+    fn score(&self, a: &PageVector, b: &PageVector) -> f64 {
+        let sim = cosine_sim(&a.counts, &b.counts);
+
+        if sim.is_nan() {
+            0.0
+        } else {
+            sim
+        }
+    }
+}
+
+/// Jaccard similarity of the term sets, ignoring how often terms occur
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Jaccard;
+
+impl SimilarityMetric for Jaccard {
+    fn score(&self, a: &PageVector, b: &PageVector) -> f64 {
+        let (mut both, mut either) = (0usize, 0usize);
+        for (&x, &y) in a.counts.iter().zip(&b.counts) {
+            both += (x > 0.0 && y > 0.0) as usize;
+            either += (x > 0.0 || y > 0.0) as usize;
+        }
+
+        if either == 0 {
+            0.0
+        } else {
+            both as f64 / either as f64
+        }
+    }
+}
+
+/// Euclidean distance of the term frequencies, turned into a similarity
+/// as `1 / (1 + distance)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Euclidean;
+
+impl SimilarityMetric for Euclidean {
+    fn score(&self, a: &PageVector, b: &PageVector) -> f64 {
+        let distance = a
+            .term_frequencies()
+            .iter()
+            .zip(b.term_frequencies())
+            .map(|(x, y)| (x - y).powi(2))
+            .sum::<f64>()
+            .sqrt();
+
+        1.0 / (1.0 + distance)
+    }
+}
+
+/// Okapi BM25 of the candidate for the terms of the primary page, with document
+/// frequencies taken over the pages vectorized together. Not symmetric, see
+/// `bm25::bm25_score` to score against a whole corpus
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bm25 {
+    /// Term frequency saturation
+    pub k1: f64,
+    /// Length normalization [0-1]
+    pub b: f64,
+}
+
+impl Default for Bm25 {
+    fn default() -> Self {
+        Bm25 {
+            k1: DEFAULT_K1,
+            b: DEFAULT_B,
+        }
+    }
+}
+
+impl SimilarityMetric for Bm25 {
+    fn score(&self, a: &PageVector, b: &PageVector) -> f64 {
+        let collection = &b.collection;
+
+        a.counts
+            .iter()
+            .zip(&b.counts)
+            .zip(&collection.document_frequencies)
+            .filter(|((&query, &tf), _)| query > 0.0 && tf > 0.0)
+            .map(|((_, &tf), &frequency)| {
+                let idf = bm25_idf(collection.documents, frequency);
+
+                bm25_term(
+                    tf,
+                    idf,
+                    b.length,
+                    collection.average_length,
+                    self.k1,
+                    self.b,
+                )
+            })
+            .sum()
+    }
+}
+
+/// One minus the Jensen-Shannon divergence (base 2) of the pages' unigram
+/// distributions. Symmetric and bounded [0-1], and unlike cosine it compares
+/// probability distributions, so it behaves better for pages of very different lengths
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct JensenShannon;
+
+impl SimilarityMetric for JensenShannon {
+    fn score(&self, a: &PageVector, b: &PageVector) -> f64 {
+        if a.length == 0.0 || b.length == 0.0 {
+            return 0.0;
+        }
+
+        // each side's share of the divergence from the average distribution
+        let divergence: f64 = a
+            .term_frequencies()
+            .iter()
+            .zip(b.term_frequencies())
+            .map(|(&p, q)| {
+                let m = (p + q) / 2.0;
+                let part = |x: f64| if x > 0.0 { x * (x / m).log2() } else { 0.0 };
+
+                (part(p) + part(q)) / 2.0
+            })
+            .sum();
+
+        (1.0 - divergence).clamp(0.0, 1.0)
+    }
+}
+
+/// Kullback-Leibler divergence of the primary page's unigram distribution from the
+/// candidate's, with the candidate Dirichlet smoothed by the term distribution of
+/// all pages vectorized together, turned into a similarity as `exp(-divergence)`.
+/// Smoothing keeps terms missing from a short candidate from making the divergence
+/// infinite. Not symmetric, the primary page is the query
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DirichletKl {
+    /// Smoothing strength, in pseudo counts. Higher values lean more on the collection,
+    /// the default of 2000 suits full articles and should be lowered for stubs
+    pub mu: f64,
+}
+
+impl Default for DirichletKl {
+    fn default() -> Self {
+        DirichletKl { mu: 2000.0 }
+    }
+}
+
+impl SimilarityMetric for DirichletKl {
+    fn score(&self, a: &PageVector, b: &PageVector) -> f64 {
+        let collection = &b.collection;
+        let collection_length = collection.collection_frequencies.iter().sum::<usize>() as f64;
+        if a.length == 0.0 || collection_length == 0.0 {
+            return 0.0;
+        }
+
+        let divergence: f64 = a
+            .counts
+            .iter()
+            .zip(&b.counts)
+            .zip(&collection.collection_frequencies)
+            .filter(|((&count, _), _)| count > 0.0)
+            .map(|((&count, &candidate), &frequency)| {
+                let p = count / a.length;
+                let background = frequency as f64 / collection_length;
+                let q = (candidate + self.mu * background) / (b.length + self.mu);
+
+                p * (p / q).ln()
+            })
+            .sum();
+
+        (-divergence).exp()
+    }
+}
+
+/// Vectorizes a set of pages over one shared vocabulary, cleaning every page once
+///
+/// # Arguments
+///
+/// * `pages` - The pages to vectorize
+/// * `options` - How the pages are cleaned
+///
+/// # Returns
+///
+/// One vector per page, in the same order
+pub fn vectorize_pages(pages: &[&Page], options: &VectorOptions) -> Vec<PageVector> {
+    let counts: Vec<HashMap<String, usize>> = pages
+        .par_iter()
+        .map(|page| count_terms(&prepare_document(page, options).content))
+        .collect();
+
+    let mut vocab: HashMap<&str, usize> = HashMap::new();
+    let mut document_frequencies: Vec<usize> = Vec::new();
+    let mut collection_frequencies: Vec<usize> = Vec::new();
+    for page_counts in &counts {
+        for (term, &count) in page_counts {
+            let id = *vocab.entry(term).or_insert_with(|| {
+                document_frequencies.push(0);
+                collection_frequencies.push(0);
+                document_frequencies.len() - 1
+            });
+            document_frequencies[id] += 1;
+            collection_frequencies[id] += count;
+        }
+    }
+
+    let lengths: Vec<f64> = counts
+        .iter()
+        .map(|page_counts| page_counts.values().sum::<usize>() as f64)
+        .collect();
+    let collection = Arc::new(CollectionStats {
+        documents: pages.len(),
+        average_length: lengths.iter().sum::<f64>() / pages.len().max(1) as f64,
+        document_frequencies,
+        collection_frequencies,
+    });
+
+    counts
+        .iter()
+        .zip(lengths)
+        .map(|(page_counts, length)| {
+            let mut vector = vec![0.0; vocab.len()];
+            for (term, &count) in page_counts {
+                vector[vocab[term.as_str()]] = count as f64;
+            }
+
+            PageVector {
+                counts: vector,
+                length,
+                collection: collection.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Get the similarity of two pages under any metric
+///
+/// # Arguments
+///
+/// * `page1` - The first page, the query for asymmetric metrics
+/// * `page2` - The second page
+/// * `metric` - How the page vectors are scored
+/// * `options` - How the pages are vectorized
+///
+/// # Returns
+///
+/// The score of the metric
+pub fn get_page_similarity_by<M: SimilarityMetric + ?Sized>(
+    page1: &Page,
+    page2: &Page,
+    metric: &M,
+    options: &VectorOptions,
+) -> f64 {
+    let vectors = vectorize_pages(&[page1, page2], options);
+    metric.score(&vectors[0], &vectors[1])
+}
+
+/// Get the most similar page from a set of pages under any metric
+///
+/// # Arguments
+///
+/// * `primary_page` - The page to check for similarity to
+/// * `pages` - The set of pages to check against
+/// * `metric` - How the page vectors are scored
+/// * `options` - How the pages are vectorized
+///
+/// # Returns
+///
+/// The ARGMAX of the most similar page, 0 if `pages` is empty
+pub fn get_most_similar_page_by<M: SimilarityMetric + ?Sized>(
+    primary_page: &Page,
+    pages: &[Page],
+    metric: &M,
+    options: &VectorOptions,
+) -> usize {
+    get_top_k_similar_by(primary_page, pages, 1, metric, options)
+        .first()
+        .map_or(0, |&(index, _)| index)
+}
+
+/// Get the k most similar pages from a set of pages under any metric
+///
+/// # Arguments
+///
+/// * `primary_page` - The page to check for similarity to
+/// * `pages` - The set of pages to check against
+/// * `k` - The maximum number of pages to return
+/// * `metric` - How the page vectors are scored
+/// * `options` - How the pages are vectorized
+///
+/// # Returns
+///
+/// Up to `k` (index into `pages`, score) pairs, highest score first
+pub fn get_top_k_similar_by<M: SimilarityMetric + ?Sized>(
+    primary_page: &Page,
+    pages: &[Page],
+    k: usize,
+    metric: &M,
+    options: &VectorOptions,
+) -> Vec<(usize, f64)> {
+    let all: Vec<&Page> = std::iter::once(primary_page).chain(pages).collect();
+    let vectors = vectorize_pages(&all, options);
+
+    let mut ranked: Vec<(usize, f64)> = vectors[1..]
+        .par_iter()
+        .map(|vector| metric.score(&vectors[0], vector))
+        .map(|score| if score.is_nan() { 0.0 } else { score })
+        .enumerate()
+        .collect();
+
+    // stable, so ties keep the order of `pages`
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked.truncate(k);
+
+    ranked
+}
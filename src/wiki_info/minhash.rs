@@ -0,0 +1,257 @@
+use std::collections::{HashMap, HashSet};
+
+use rayon::prelude::*;
+
+use super::{
+    hashing::{fnv1a, splitmix64},
+    prepare_document, Page, VectorOptions,
+};
+
+/// Produces fixed size MinHash signatures of pages. The fraction of equal
+/// positions in two signatures estimates the Jaccard similarity of the pages'
+/// shingle sets, at a cost independent of page length
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinHasher {
+    /// Number of hash functions, the length of every signature
+    pub num_hashes: usize,
+    /// Number of consecutive cleaned terms per shingle. 1 compares term sets
+    /// like `jaccard_sim`, larger shingles only match repeated passages
+    pub shingle_size: usize,
+    /// How pages are cleaned before shingling
+    pub options: VectorOptions,
+}
+
+/// The MinHash signature of a page, see `MinHasher`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MinHashSignature(pub Vec<u64>);
+
+impl MinHasher {
+    /// Creates a hasher over single terms with default options
+    ///
+    /// # Arguments
+    ///
+    /// * `num_hashes` - Length of every signature, must not be 0.
+    ///   The estimate's error shrinks with `1 / sqrt(num_hashes)`
+    pub fn new(num_hashes: usize) -> Self {
+        assert!(num_hashes > 0, "MinHasher needs at least one hash");
+
+        MinHasher {
+            num_hashes,
+            shingle_size: 1,
+            options: VectorOptions::default(),
+        }
+    }
+
+    /// Computes the signature of a page
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page to hash
+    ///
+    /// # Returns
+    ///
+    /// A signature of `num_hashes` values, all `u64::MAX` if the page has no shingles
+    pub fn signature(&self, page: &Page) -> MinHashSignature {
+        let content = prepare_document(page, &self.options).content;
+        let words: Vec<&str> = content.split_whitespace().collect();
+
+        let shingles: HashSet<u64> = words
+            .windows(self.shingle_size.max(1))
+            .map(|shingle| fnv1a(shingle.join(" ").as_bytes()))
+            .collect();
+
+        let mut signature = vec![u64::MAX; self.num_hashes];
+        for shingle in shingles {
+            for (seed, min) in signature.iter_mut().enumerate() {
+                *min = (*min).min(mix(shingle, seed as u64));
+            }
+        }
+
+        MinHashSignature(signature)
+    }
+}
+
+impl MinHashSignature {
+    /// Estimates the Jaccard similarity of the pages behind two signatures
+    ///
+    /// # Returns
+    ///
+    /// The fraction of equal positions [0-1], 0 if either page had no shingles
+    pub fn jaccard(&self, other: &MinHashSignature) -> f64 {
+        assert_eq!(self.0.len(), other.0.len(), "signature lengths differ");
+
+        if self.is_empty() || other.is_empty() {
+            return 0.0;
+        }
+
+        let equal = self.0.iter().zip(&other.0).filter(|(a, b)| a == b).count();
+        equal as f64 / self.0.len() as f64
+    }
+
+    /// Whether the signature is of a page without shingles
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&value| value == u64::MAX)
+    }
+}
+
+/// A locality sensitive hashing index over MinHash signatures. Signatures are
+/// cut into bands and pages sharing any whole band become candidates, so
+/// similar pages are found without comparing against every page.
+/// Pages with Jaccard similarity `s` become candidates with probability
+/// `1 - (1 - s^rows)^bands`, the threshold is roughly `(1 / bands)^(1 / rows)`
+#[derive(Debug, Clone)]
+pub struct LshIndex {
+    hasher: MinHasher,
+    bands: usize,
+    rows: usize,
+    buckets: Vec<HashMap<u64, Vec<usize>>>,
+    signatures: Vec<MinHashSignature>,
+}
+
+impl LshIndex {
+    /// Creates an empty index
+    ///
+    /// # Arguments
+    ///
+    /// * `hasher` - The hasher pages are signed with
+    /// * `bands` - Number of bands, must divide `hasher.num_hashes`.
+    ///   More bands find less similar pages at the cost of more false candidates
+    pub fn new(hasher: MinHasher, bands: usize) -> Self {
+        assert!(
+            bands > 0 && hasher.num_hashes.is_multiple_of(bands),
+            "bands must divide the number of hashes"
+        );
+
+        LshIndex {
+            rows: hasher.num_hashes / bands,
+            hasher,
+            bands,
+            buckets: vec![HashMap::new(); bands],
+            signatures: Vec::new(),
+        }
+    }
+
+    /// Adds a page to the index
+    ///
+    /// # Returns
+    ///
+    /// The id of the page, the order it was added in
+    pub fn add_page(&mut self, page: &Page) -> usize {
+        let signature = self.hasher.signature(page);
+        self.add_signature(signature)
+    }
+
+    /// Adds many pages, signing them in parallel
+    ///
+    /// # Returns
+    ///
+    /// The ids of the pages, in the same order
+    pub fn add_pages(&mut self, pages: &[Page]) -> Vec<usize> {
+        let signatures: Vec<MinHashSignature> = pages
+            .par_iter()
+            .map(|page| self.hasher.signature(page))
+            .collect();
+
+        signatures
+            .into_iter()
+            .map(|signature| self.add_signature(signature))
+            .collect()
+    }
+
+    /// Number of pages in the index
+    pub fn len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// Whether the index has no pages
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+
+    /// The signature of an indexed page
+    pub fn signature(&self, id: usize) -> Option<&MinHashSignature> {
+        self.signatures.get(id)
+    }
+
+    /// Finds the indexed pages sharing at least one band with a page
+    ///
+    /// # Returns
+    ///
+    /// The candidate ids, sorted
+    pub fn candidates(&self, page: &Page) -> Vec<usize> {
+        self.band_candidates(&self.hasher.signature(page))
+    }
+
+    /// Finds the indexed pages similar to a page, checking only the candidates
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page to look up
+    /// * `threshold` - Minimum estimated Jaccard similarity [0-1]
+    ///
+    /// # Returns
+    ///
+    /// (id, estimated Jaccard similarity) of the matching pages, most similar first
+    pub fn query(&self, page: &Page, threshold: f64) -> Vec<(usize, f64)> {
+        let signature = self.hasher.signature(page);
+
+        let mut matches: Vec<(usize, f64)> = self
+            .band_candidates(&signature)
+            .into_iter()
+            .map(|id| (id, signature.jaccard(&self.signatures[id])))
+            .filter(|&(_, sim)| sim >= threshold)
+            .collect();
+
+        matches.sort_by(|a, b| b.1.total_cmp(&a.1));
+        matches
+    }
+
+    fn add_signature(&mut self, signature: MinHashSignature) -> usize {
+        let id = self.signatures.len();
+
+        // pages without shingles would all share every band
+        if !signature.is_empty() {
+            for (band, key) in self.band_keys(&signature).into_iter().enumerate() {
+                self.buckets[band].entry(key).or_default().push(id);
+            }
+        }
+        self.signatures.push(signature);
+
+        id
+    }
+
+    fn band_candidates(&self, signature: &MinHashSignature) -> Vec<usize> {
+        if signature.is_empty() {
+            return Vec::new();
+        }
+
+        let mut candidates: Vec<usize> = self
+            .band_keys(signature)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(band, key)| self.buckets[band].get(&key))
+            .flatten()
+            .copied()
+            .collect();
+
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+    }
+
+    fn band_keys(&self, signature: &MinHashSignature) -> Vec<u64> {
+        signature
+            .0
+            .chunks(self.rows)
+            .map(|band| {
+                let bytes: Vec<u8> = band.iter().flat_map(|value| value.to_le_bytes()).collect();
+                fnv1a(&bytes)
+            })
+            .collect()
+    }
+}
+
+// turns one shingle hash into an independent hash per seed
+fn mix(hash: u64, seed: u64) -> u64 {
+    splitmix64(hash ^ splitmix64(seed))
+}
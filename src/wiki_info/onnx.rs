@@ -0,0 +1,200 @@
+use std::path::Path;
+
+use log::debug;
+use tokenizers::{Tokenizer, TruncationParams};
+use tract_onnx::prelude::*;
+
+use super::{cosine_sim_f32, Page, WikiError};
+
+// MiniLM style models are trained on sequences of at most 256 word pieces
+const DEFAULT_MAX_TOKENS: usize = 256;
+
+// pages are embedded in chunks of this many words, well under the token limit
+const CHUNK_WORDS: usize = 150;
+
+type Model = SimplePlan<TypedFact, Box<dyn TypedOp>, Graph<TypedFact, Box<dyn TypedOp>>>;
+
+/// A local sentence-transformer (ie all-MiniLM-L6-v2 exported to ONNX) for
+/// semantic page similarity without calling any external API.
+/// Requires the `onnx` feature
+pub struct SentenceEncoder {
+    model: Model,
+    tokenizer: Tokenizer,
+    // names of the model inputs, in order
+    inputs: Vec<String>,
+}
+
+impl SentenceEncoder {
+    /// Loads a model and its tokenizer
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - path of the `model.onnx` file
+    /// * `tokenizer` - path of the matching huggingface `tokenizer.json`
+    ///
+    /// # Returns
+    ///
+    /// Ok(SentenceEncoder) - the loaded encoder
+    /// Err(WikiError::IOError) - error if either file could not be loaded
+    pub fn from_files<P: AsRef<Path>, T: AsRef<Path>>(
+        model: P,
+        tokenizer: T,
+    ) -> Result<Self, WikiError> {
+        debug!("Loading sentence encoder from {:?}", model.as_ref());
+
+        let mut tokenizer = Tokenizer::from_file(tokenizer)
+            .map_err(|err| WikiError::IOError(format!("Invalid tokenizer: {}", err)))?;
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: DEFAULT_MAX_TOKENS,
+                ..Default::default()
+            }))
+            .map_err(|err| WikiError::IOError(format!("Invalid tokenizer: {}", err)))?;
+        tokenizer.with_padding(None);
+
+        let model = tract_onnx::onnx()
+            .model_for_path(model)
+            .and_then(|model| model.into_optimized())
+            .and_then(|model| model.into_runnable())
+            .map_err(|err| WikiError::IOError(format!("Invalid model: {}", err)))?;
+
+        let graph = model.model();
+        let inputs = graph
+            .input_outlets()
+            .map_err(onnx_error)?
+            .iter()
+            .map(|outlet| graph.node(outlet.node).name.clone())
+            .collect();
+
+        Ok(SentenceEncoder {
+            model,
+            tokenizer,
+            inputs,
+        })
+    }
+
+    /// Embeds a text, longer texts are truncated to the model's token limit
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to embed
+    ///
+    /// # Returns
+    ///
+    /// Ok(Vec<f32>) - the mean pooled embedding, scaled to unit length
+    /// Err(WikiError::ParseError) - error if the text could not be tokenized or the model failed
+    pub fn embed_text(&self, text: &str) -> Result<Vec<f32>, WikiError> {
+        let encoding = self
+            .tokenizer
+            .encode(text, true)
+            .map_err(|err| WikiError::ParseError(format!("Tokenizing failed: {}", err)))?;
+
+        let length = encoding.get_ids().len();
+        let as_tensor = |values: &[u32]| {
+            let values: Vec<i64> = values.iter().map(|&value| value as i64).collect();
+            Tensor::from_shape(&[1, length], &values).map_err(onnx_error)
+        };
+
+        let mut inputs: TVec<TValue> = TVec::new();
+        for name in &self.inputs {
+            let values = match name.as_str() {
+                "attention_mask" => encoding.get_attention_mask(),
+                "token_type_ids" => encoding.get_type_ids(),
+                _ => encoding.get_ids(),
+            };
+            inputs.push(as_tensor(values)?.into());
+        }
+
+        let outputs = self.model.run(inputs).map_err(onnx_error)?;
+        let output = outputs[0].to_array_view::<f32>().map_err(onnx_error)?;
+
+        let mut embedding = match output.shape() {
+            // already pooled, [batch, dimensions]
+            [1, _] => output.iter().copied().collect(),
+            // token embeddings, [batch, tokens, dimensions], averaged over real tokens
+            [1, tokens, dimensions] => {
+                let mask = encoding.get_attention_mask();
+                let mut sum = vec![0.0f32; *dimensions];
+                let mut count = 0.0f32;
+
+                for token in 0..*tokens {
+                    if mask.get(token).copied().unwrap_or(0) == 0 {
+                        continue;
+                    }
+                    for (dimension, value) in sum.iter_mut().enumerate() {
+                        *value += output[[0, token, dimension]];
+                    }
+                    count += 1.0;
+                }
+
+                sum.iter_mut().for_each(|value| *value /= count.max(1.0));
+                sum
+            }
+            shape => {
+                return Err(WikiError::ParseError(format!(
+                    "Unexpected model output shape {:?}",
+                    shape
+                )))
+            }
+        };
+
+        normalize(&mut embedding);
+        Ok(embedding)
+    }
+
+    /// Embeds a page. The title and content are embedded in chunks that fit the
+    /// model, and the chunk embeddings averaged, so the whole page counts
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page to embed
+    ///
+    /// # Returns
+    ///
+    /// Ok(Vec<f32>) - the page embedding, scaled to unit length
+    /// Err(WikiError::ParseError) - error if the model failed
+    pub fn embed_page(&self, page: &Page) -> Result<Vec<f32>, WikiError> {
+        let words: Vec<&str> = page.content.split_whitespace().collect();
+
+        let mut chunks = vec![page.title.clone()];
+        chunks.extend(words.chunks(CHUNK_WORDS).map(|chunk| chunk.join(" ")));
+
+        let mut embedding: Vec<f32> = Vec::new();
+        for chunk in chunks.iter().filter(|chunk| !chunk.trim().is_empty()) {
+            let chunk_embedding = self.embed_text(chunk)?;
+
+            if embedding.is_empty() {
+                embedding = chunk_embedding;
+            } else {
+                for (value, chunk_value) in embedding.iter_mut().zip(chunk_embedding) {
+                    *value += chunk_value;
+                }
+            }
+        }
+
+        normalize(&mut embedding);
+        Ok(embedding)
+    }
+
+    /// The cosine similarity of the embeddings of two pages, see `embed_page`
+    ///
+    /// # Returns
+    ///
+    /// Ok(f64) - the similarity [-1, 1], 0 if either page is empty
+    /// Err(WikiError::ParseError) - error if the model failed
+    pub fn similarity(&self, a: &Page, b: &Page) -> Result<f64, WikiError> {
+        Ok(cosine_sim_f32(&self.embed_page(a)?, &self.embed_page(b)?))
+    }
+}
+
+fn onnx_error<E: std::fmt::Display>(err: E) -> WikiError {
+    WikiError::ParseError(format!("Model error: {}", err))
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|value| *value /= norm);
+    }
+}
@@ -0,0 +1,70 @@
+use serde::Serialize;
+
+use super::{build_vocab, magnitude, page_to_vec_with, Page, VectorOptions};
+
+/// A term shared by two pages, with its weight in each
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TermOverlap {
+    pub term: String,
+    /// Term frequency weight in the first page
+    pub weight_a: f64,
+    /// Term frequency weight in the second page
+    pub weight_b: f64,
+    /// Share of the cosine similarity from this term, `weight_a * weight_b`
+    /// over the product of the vector magnitudes. Shares of all terms sum to the cosine
+    pub contribution: f64,
+}
+
+/// Lists the terms two pages have in common, ie for rendering overlap heatmaps
+///
+/// # Arguments
+///
+/// * `a` - The first page
+/// * `b` - The second page
+///
+/// # Returns
+///
+/// Every shared term with its weight in both pages, ordered by the
+/// product of the weights (its pull on the similarity score), largest first
+pub fn term_overlap_report(a: &Page, b: &Page) -> Vec<TermOverlap> {
+    term_overlap_report_with(a, b, &VectorOptions::default())
+}
+
+/// Lists the terms two pages have in common, vectorized with the given options
+///
+/// # Arguments
+///
+/// * `a` - The first page
+/// * `b` - The second page
+/// * `options` - How the pages are vectorized
+///
+/// # Returns
+///
+/// Every shared term with its weight in both pages, largest product first
+pub fn term_overlap_report_with(a: &Page, b: &Page, options: &VectorOptions) -> Vec<TermOverlap> {
+    let vocab = build_vocab([a, b].into_iter(), options);
+
+    let vec_a = page_to_vec_with(a, &vocab, options);
+    let vec_b = page_to_vec_with(b, &vocab, options);
+    let magnitudes = magnitude(&vec_a) * magnitude(&vec_b);
+
+    let mut report: Vec<TermOverlap> = vocab
+        .into_iter()
+        .filter(|(_, id)| vec_a[*id] > 0.0 && vec_b[*id] > 0.0)
+        .map(|(term, id)| TermOverlap {
+            term,
+            weight_a: vec_a[id],
+            weight_b: vec_b[id],
+            // only shared terms are reported, so the magnitudes are never 0
+            contribution: vec_a[id] * vec_b[id] / magnitudes,
+        })
+        .collect();
+
+    report.sort_by(|x, y| {
+        y.contribution
+            .total_cmp(&x.contribution)
+            .then_with(|| x.term.cmp(&y.term))
+    });
+
+    report
+}
@@ -0,0 +1,47 @@
+//! The commonly used types and functions under one import path:
+//!
+//! ```
+//! use wiki_info::wiki_info::prelude::*;
+//! ```
+//!
+//! Everything re-exported here stays available from the prelude across
+//! releases, even when the module it is defined in moves. Items are only
+//! removed after being deprecated for at least one minor release.
+//! Anything not re-exported here may still move between modules
+
+pub use super::{
+    clean_document, clean_document_with, cosine_sim, cosine_sim_f32, explain_similarity,
+    explain_similarity_with, find_most_similar_page, find_most_similar_page_with,
+    get_most_similar_page, get_most_similar_page_with, get_page_similarity,
+    get_page_similarity_with, get_top_k_similar, get_top_k_similar_with, jaccard_sim,
+    link_jaccard_sim, link_overlap_sim, overlap_sim, page_from_title, page_from_title_with,
+    page_from_url, page_from_url_with, page_summary_from_title, plain_text_from_title,
+    query_similarity, query_similarity_with, rank_outlinks_by_similarity,
+    rank_outlinks_by_similarity_with, rank_pages_by_query, rank_pages_by_query_with, read_titles,
+    rerank_mmr, rerank_mmr_with, similarity_matrix, similarity_matrix_with, Block, CleanOptions,
+    Coordinates, FetchMode, HyperLink, LengthNormalization, LinkContext, Namespace, Page,
+    ParseOptions, PunctuationPolicy, SimilarityResult, TermContribution, TokenCap, VectorOptions,
+    WikiError,
+};
+
+pub use super::{
+    aliases::AliasTable,
+    bm25::{bm25_score, CorpusStats},
+    corpus::Corpus,
+    fields::{get_fielded_similarity, get_fielded_similarity_with, FieldWeights},
+    graph::WikiGraph,
+    index::{FieldBoosts, FieldedIndex, IndexScoring, InvertedIndex, SearchField},
+    language::{detect_language, LanguageTag},
+    matrix::Matrix,
+    meta::PageMeta,
+    query::BooleanQuery,
+    signature::{is_near_duplicate, simhash, Signature},
+    significance::{similarity_significance, Significance},
+    sink::PageSink,
+    stats::PageStats,
+    stem::Stemmer,
+    stop_words::StopWords,
+    titles::{closest_title, title_distance},
+    vector_cache::{CleanCache, VectorCache},
+    vocabulary::Vocabulary,
+};
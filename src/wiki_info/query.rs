@@ -0,0 +1,159 @@
+use super::WikiError;
+
+/// A boolean query over an `InvertedIndex`, ie `rust AND (compiler OR borrow) NOT game`.
+/// `NOT` binds tightest, then `AND`, then `OR`. Terms next to each other without an
+/// operator are joined with `AND`, and `a NOT b` is `a AND NOT b`.
+/// Operators must be uppercase, lowercase `and`/`or`/`not` are plain terms.
+/// Quoted words (`"borrow checker"`) are a phrase, matched in order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BooleanQuery {
+    /// Documents containing the term, after cleaning it like page content
+    Term(String),
+    /// Documents containing the words in order, see `InvertedIndex::phrase_matches`
+    Phrase(String),
+    And(Box<BooleanQuery>, Box<BooleanQuery>),
+    Or(Box<BooleanQuery>, Box<BooleanQuery>),
+    Not(Box<BooleanQuery>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Phrase(String),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl BooleanQuery {
+    /// Parses a boolean query
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query text
+    ///
+    /// # Returns
+    ///
+    /// Ok(BooleanQuery) - the parsed query
+    /// Err(WikiError::InputError) - error if the query is empty, a parenthesis or
+    /// quote is unbalanced or an operator is missing an operand
+    pub fn parse(query: &str) -> Result<Self, WikiError> {
+        let tokens = tokenize(query)?;
+        let mut parser = Parser { tokens, next: 0 };
+
+        let parsed = parser.or_expr()?;
+        match parser.peek() {
+            None => Ok(parsed),
+            Some(token) => Err(WikiError::InputError(format!(
+                "Unexpected {:?} in boolean query",
+                token
+            ))),
+        }
+    }
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>, WikiError> {
+    let mut tokens = Vec::new();
+
+    // every odd piece between quotes is a phrase
+    let pieces: Vec<&str> = query.split('"').collect();
+    if pieces.len().is_multiple_of(2) {
+        return Err(WikiError::InputError(
+            "Unclosed quote in boolean query".to_owned(),
+        ));
+    }
+
+    for (i, piece) in pieces.into_iter().enumerate() {
+        if i % 2 == 1 {
+            tokens.push(Token::Phrase(piece.to_owned()));
+            continue;
+        }
+
+        let spaced = piece.replace('(', " ( ").replace(')', " ) ");
+        for word in spaced.split_whitespace() {
+            tokens.push(match word {
+                "AND" => Token::And,
+                "OR" => Token::Or,
+                "NOT" => Token::Not,
+                "(" => Token::Open,
+                ")" => Token::Close,
+                word => Token::Word(word.to_owned()),
+            });
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    next: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.next).cloned();
+        self.next += 1;
+        token
+    }
+
+    fn or_expr(&mut self) -> Result<BooleanQuery, WikiError> {
+        let mut query = self.and_expr()?;
+
+        while self.peek() == Some(&Token::Or) {
+            self.advance();
+            query = BooleanQuery::Or(Box::new(query), Box::new(self.and_expr()?));
+        }
+
+        Ok(query)
+    }
+
+    fn and_expr(&mut self) -> Result<BooleanQuery, WikiError> {
+        let mut query = self.unary()?;
+
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.advance();
+                }
+                // implicit AND, NOT is left for `unary`
+                Some(Token::Word(_) | Token::Phrase(_) | Token::Open | Token::Not) => {}
+                _ => break,
+            }
+
+            query = BooleanQuery::And(Box::new(query), Box::new(self.unary()?));
+        }
+
+        Ok(query)
+    }
+
+    fn unary(&mut self) -> Result<BooleanQuery, WikiError> {
+        match self.advance() {
+            Some(Token::Not) => Ok(BooleanQuery::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let query = self.or_expr()?;
+                match self.advance() {
+                    Some(Token::Close) => Ok(query),
+                    _ => Err(WikiError::InputError(
+                        "Unclosed parenthesis in boolean query".to_owned(),
+                    )),
+                }
+            }
+            Some(Token::Word(word)) => Ok(BooleanQuery::Term(word)),
+            Some(Token::Phrase(phrase)) => Ok(BooleanQuery::Phrase(phrase)),
+            Some(token) => Err(WikiError::InputError(format!(
+                "Expected a term in boolean query, found {:?}",
+                token
+            ))),
+            None => Err(WikiError::InputError(
+                "Boolean query ended where a term was expected".to_owned(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+use super::{text::split_sentences, Page};
+
+/// Readability statistics and scores of a page's content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct ReadabilityScores {
+    pub sentences: usize,
+    pub words: usize,
+    pub syllables: usize,
+    /// Words of three or more syllables
+    pub polysyllables: usize,
+    /// Flesch reading ease, higher is easier. 60-70 is plain English,
+    /// below 30 is academic text
+    pub flesch_reading_ease: f64,
+    /// Flesch-Kincaid grade level, the US school grade needed to follow the text
+    pub flesch_kincaid_grade: f64,
+    /// SMOG grade, years of education needed to understand the text.
+    /// Designed for texts of at least 30 sentences, rougher below that
+    pub smog_grade: f64,
+}
+
+/// Scores how easy a page is to read from its sentence and syllable statistics.
+/// Syllables are estimated from vowel groups, which is close for English text
+///
+/// # Arguments
+///
+/// * `page` - the page to score
+///
+/// # Returns
+///
+/// The scores of the page, all zero if it has no words
+pub fn readability(page: &Page) -> ReadabilityScores {
+    let mut scores = ReadabilityScores::default();
+
+    for sentence in split_sentences(&page.content) {
+        let mut sentence_words = 0;
+
+        let words = sentence
+            .split_whitespace()
+            .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+            .filter(|word| word.chars().any(char::is_alphabetic));
+
+        for word in words {
+            let syllables = count_syllables(word);
+            scores.syllables += syllables;
+            if syllables >= 3 {
+                scores.polysyllables += 1;
+            }
+            sentence_words += 1;
+        }
+
+        if sentence_words > 0 {
+            scores.words += sentence_words;
+            scores.sentences += 1;
+        }
+    }
+
+    if scores.words == 0 {
+        return scores;
+    }
+
+    let words_per_sentence = scores.words as f64 / scores.sentences as f64;
+    let syllables_per_word = scores.syllables as f64 / scores.words as f64;
+
+    scores.flesch_reading_ease = 206.835 - 1.015 * words_per_sentence - 84.6 * syllables_per_word;
+    scores.flesch_kincaid_grade = 0.39 * words_per_sentence + 11.8 * syllables_per_word - 15.59;
+    scores.smog_grade =
+        1.043 * (scores.polysyllables as f64 * 30.0 / scores.sentences as f64).sqrt() + 3.1291;
+
+    scores
+}
+
+/// Estimates the syllables of an English word from its vowel groups,
+/// ignoring a silent final `e` (`"make"`) but not `"le"` (`"table"`)
+pub(crate) fn count_syllables(word: &str) -> usize {
+    let word = word.to_lowercase();
+    let is_vowel = |c: char| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+
+    let mut count = 0;
+    let mut previous_vowel = false;
+    for c in word.chars() {
+        let vowel = is_vowel(c);
+        if vowel && !previous_vowel {
+            count += 1;
+        }
+        previous_vowel = vowel;
+    }
+
+    let silent_e = word.ends_with('e')
+        && !word.ends_with("le")
+        && !word.ends_with("ee")
+        && word.chars().rev().nth(1).is_some_and(|c| !is_vowel(c));
+    if silent_e && count > 1 {
+        count -= 1;
+    }
+
+    count.max(1)
+}
@@ -0,0 +1,121 @@
+use log::debug;
+
+use super::{cosine_sim, hashing::HashingVectorizer, Page};
+
+/// A labeled topic, represented by the mean vector of its example pages
+#[derive(Debug, Clone, PartialEq)]
+pub struct Topic {
+    pub label: String,
+    pub centroid: Vec<f64>,
+    /// Number of examples folded into the centroid
+    pub examples: usize,
+}
+
+/// Routes pages into known topics by their nearest centroid.
+/// Pages are vectorized with a HashingVectorizer, so centroids live in a fixed
+/// space and can be updated online one example at a time
+#[derive(Debug, Clone)]
+pub struct TopicRouter {
+    vectorizer: HashingVectorizer,
+    topics: Vec<Topic>,
+}
+
+impl TopicRouter {
+    /// Creates a router with no topics
+    ///
+    /// # Arguments
+    ///
+    /// * `vectorizer` - How pages are vectorized, shared by every topic
+    pub fn new(vectorizer: HashingVectorizer) -> Self {
+        TopicRouter {
+            vectorizer,
+            topics: Vec::new(),
+        }
+    }
+
+    /// The known topics, in the order they were first seen
+    pub fn topics(&self) -> &[Topic] {
+        &self.topics
+    }
+
+    /// Folds a labeled example page into its topic centroid, creating the topic
+    /// if the label is new
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The topic the page belongs to
+    /// * `page` - The example page
+    pub fn add_example(&mut self, label: &str, page: &Page) {
+        let vector = self.vectorizer.vectorize(page);
+        self.add_example_vector(label, &vector);
+    }
+
+    /// Folds an already vectorized example into its topic centroid.
+    /// The vector must come from this router's vectorizer
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The topic the vector belongs to
+    /// * `vector` - The example vector
+    pub fn add_example_vector(&mut self, label: &str, vector: &[f64]) {
+        debug!("Adding example to topic {}", label);
+
+        match self.topics.iter_mut().find(|topic| topic.label == label) {
+            Some(topic) => {
+                // running mean, no need to keep old examples around
+                topic.examples += 1;
+                let n = topic.examples as f64;
+
+                for (c, v) in topic.centroid.iter_mut().zip(vector) {
+                    *c += (v - *c) / n;
+                }
+            }
+            None => self.topics.push(Topic {
+                label: label.to_owned(),
+                centroid: vector.to_vec(),
+                examples: 1,
+            }),
+        }
+    }
+
+    /// Scores a page against every topic
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page to route
+    ///
+    /// # Returns
+    ///
+    /// (label, cosine similarity to the centroid) for every topic, most similar first
+    pub fn rank(&self, page: &Page) -> Vec<(&str, f64)> {
+        let vector = self.vectorizer.vectorize(page);
+
+        let mut ranked: Vec<(&str, f64)> = self
+            .topics
+            .iter()
+            .map(|topic| {
+                let sim = cosine_sim(&vector, &topic.centroid);
+                // empty pages have no direction, treat them as unrelated
+                let sim = if sim.is_nan() { 0.0 } else { sim };
+                (topic.label.as_str(), sim)
+            })
+            .collect();
+
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        ranked
+    }
+
+    /// Routes a page to its nearest topic
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page to route
+    ///
+    /// # Returns
+    ///
+    /// Some((label, similarity)) of the nearest topic, None if there are no topics
+    pub fn classify(&self, page: &Page) -> Option<(&str, f64)> {
+        self.rank(page).into_iter().next()
+    }
+}
@@ -0,0 +1,191 @@
+use rayon::prelude::*;
+
+use super::{build_vocab, page_to_vec_with, score_vectors, Block, Page, VectorOptions};
+
+/// Index of a section in `Page::sections`
+pub type SectionId = usize;
+
+/// A top level (`h2`) section of a page, subsections included
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    /// Heading of the section, None for the lead
+    pub title: Option<String>,
+    /// Text of the paragraphs, list items and subheadings of the section
+    pub text: String,
+}
+
+impl Page {
+    /// Splits the page into its lead and top level sections.
+    /// Section boundaries are only known for pages parsed with
+    /// `ParseOptions::structured`, any other page is a single lead section
+    ///
+    /// # Returns
+    ///
+    /// The sections in page order, the lead first when it has any text
+    pub fn sections(&self) -> Vec<Section> {
+        if self.blocks.is_empty() {
+            return vec![Section {
+                title: None,
+                text: self.content.clone(),
+            }];
+        }
+
+        let mut sections = vec![Section {
+            title: None,
+            text: String::new(),
+        }];
+
+        for block in &self.blocks {
+            let text = match block {
+                Block::Heading { level: 2, text } => {
+                    sections.push(Section {
+                        title: Some(text.clone()),
+                        text: String::new(),
+                    });
+                    continue;
+                }
+                Block::Heading { text, .. }
+                | Block::Paragraph(text)
+                | Block::ListItem { text, .. } => text,
+                Block::Table(_) => continue,
+            };
+
+            let section = sections.last_mut().unwrap();
+            if !section.text.is_empty() {
+                section.text.push(' ');
+            }
+            section.text.push_str(text);
+        }
+
+        if sections[0].text.is_empty() {
+            sections.remove(0);
+        }
+
+        sections
+    }
+
+    /// Finds a section by its heading, ignoring case
+    ///
+    /// # Returns
+    ///
+    /// The first section with that heading, None if there is none
+    pub fn section(&self, title: &str) -> Option<Section> {
+        self.sections().into_iter().find(|section| {
+            section
+                .title
+                .as_deref()
+                .is_some_and(|heading| heading.eq_ignore_ascii_case(title))
+        })
+    }
+}
+
+/// Get the similarity of a section of one page to a section of another, ie
+/// to compare just the "History" of two cities. Pages must be parsed with
+/// `ParseOptions::structured`
+///
+/// # Arguments
+///
+/// * `page1` - The first page
+/// * `section1` - Heading of the section of the first page, ignoring case
+/// * `page2` - The second page
+/// * `section2` - Heading of the section of the second page, ignoring case
+///
+/// # Returns
+///
+/// The section similarity [0-1], None if either page has no such section
+pub fn get_section_similarity(
+    page1: &Page,
+    section1: &str,
+    page2: &Page,
+    section2: &str,
+) -> Option<f64> {
+    get_section_similarity_with(page1, section1, page2, section2, &VectorOptions::default())
+}
+
+/// Get the similarity of a section of one page to a section of another,
+/// vectorized with the given options, see `get_section_similarity`
+pub fn get_section_similarity_with(
+    page1: &Page,
+    section1: &str,
+    page2: &Page,
+    section2: &str,
+    options: &VectorOptions,
+) -> Option<f64> {
+    let a = section_page(&page1.section(section1)?);
+    let b = section_page(&page2.section(section2)?);
+
+    Some(section_scores(&[a], &[b], options)[0][0])
+}
+
+/// Pairs every section of one page with its most similar section of another,
+/// ie to find where two articles talk about the same thing
+///
+/// # Arguments
+///
+/// * `a` - The first page
+/// * `b` - The second page
+///
+/// # Returns
+///
+/// (section of `a`, its best section of `b`, similarity) for every section of `a`
+/// with any match, most similar first. Ids index into `Page::sections`
+pub fn best_matching_sections(a: &Page, b: &Page) -> Vec<(SectionId, SectionId, f64)> {
+    best_matching_sections_with(a, b, &VectorOptions::default())
+}
+
+/// Pairs every section of one page with its most similar section of another,
+/// vectorized with the given options, see `best_matching_sections`
+pub fn best_matching_sections_with(
+    a: &Page,
+    b: &Page,
+    options: &VectorOptions,
+) -> Vec<(SectionId, SectionId, f64)> {
+    let sections_a: Vec<Page> = a.sections().iter().map(section_page).collect();
+    let sections_b: Vec<Page> = b.sections().iter().map(section_page).collect();
+
+    let mut matches: Vec<(SectionId, SectionId, f64)> =
+        section_scores(&sections_a, &sections_b, options)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, scores)| {
+                scores
+                    .into_iter()
+                    .enumerate()
+                    .filter(|&(_, sim)| sim > 0.0)
+                    .max_by(|x, y| x.1.total_cmp(&y.1))
+                    .map(|(j, sim)| (i, j, sim))
+            })
+            .collect();
+
+    matches.sort_by(|x, y| y.2.total_cmp(&x.2));
+    matches
+}
+
+fn section_page(section: &Section) -> Page {
+    Page {
+        title: section.title.clone().unwrap_or_default(),
+        content: section.text.clone(),
+        ..Default::default()
+    }
+}
+
+// every section of `a` against every section of `b` over one shared vocabulary,
+// empty sections score 0
+fn section_scores(a: &[Page], b: &[Page], options: &VectorOptions) -> Vec<Vec<f64>> {
+    let vocab = build_vocab(a.iter().chain(b), options);
+
+    let vectors_b: Vec<Vec<f64>> = b
+        .par_iter()
+        .map(|section| page_to_vec_with(section, &vocab, options))
+        .collect();
+
+    a.par_iter()
+        .map(|section| {
+            let vector = page_to_vec_with(section, &vocab, options);
+            score_vectors(&vector, &vectors_b, options.length_normalization)
+                .into_iter()
+                .map(|sim| if sim.is_nan() { 0.0 } else { sim })
+                .collect()
+        })
+        .collect()
+}
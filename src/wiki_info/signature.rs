@@ -0,0 +1,149 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use super::{hashing::fnv1a, Page, WikiError};
+
+/// A compact, stable fingerprint of a page for dedup and linkage across systems.
+/// Built from the raw text rather than cleaned terms, so it works for any language.
+/// Formats as `simhash-length-title_hash` in hex, ie `"9f3a...-4e2-1b7c..."`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Signature {
+    /// 64 bit SimHash of the content terms, near duplicate pages differ in few bits
+    pub simhash: u64,
+    /// Number of terms in the content
+    pub length: u32,
+    /// Hash of the normalized title
+    pub title_hash: u64,
+}
+
+impl Page {
+    /// Computes the page's signature, see `Signature`
+    ///
+    /// # Returns
+    ///
+    /// The signature, the same page content and title always give the same signature
+    pub fn signature(&self) -> Signature {
+        let terms = signature_terms(&self.content);
+        let simhash = simhash_terms(&terms);
+
+        let title = self.title.trim().replace('_', " ").to_lowercase();
+
+        Signature {
+            simhash,
+            length: terms.len().min(u32::MAX as usize) as u32,
+            title_hash: fnv1a(title.as_bytes()),
+        }
+    }
+}
+
+impl Signature {
+    /// Number of differing SimHash bits, 0 for identical content.
+    /// A handful of bits (say 3 or fewer) usually means a near duplicate
+    pub fn hamming_distance(&self, other: &Signature) -> u32 {
+        (self.simhash ^ other.simhash).count_ones()
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:016x}-{:x}-{:016x}",
+            self.simhash, self.length, self.title_hash
+        )
+    }
+}
+
+impl FromStr for Signature {
+    type Err = WikiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || WikiError::ParseError(format!("Invalid page signature: {}", s));
+
+        let mut parts = s.trim().split('-');
+        let mut next = || parts.next().ok_or_else(invalid);
+
+        let simhash = u64::from_str_radix(next()?, 16).map_err(|_| invalid())?;
+        let length = u32::from_str_radix(next()?, 16).map_err(|_| invalid())?;
+        let title_hash = u64::from_str_radix(next()?, 16).map_err(|_| invalid())?;
+
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        Ok(Signature {
+            simhash,
+            length,
+            title_hash,
+        })
+    }
+}
+
+/// Computes the 64 bit SimHash of a page's content, the same value as
+/// `Signature::simhash`. Cheap enough to run on every page of a crawl,
+/// ie to skip mirrored content before doing any vectorization
+///
+/// # Arguments
+///
+/// * `page` - The page to hash
+///
+/// # Returns
+///
+/// The SimHash, near duplicate pages differ in few bits
+pub fn simhash(page: &Page) -> u64 {
+    simhash_terms(&signature_terms(&page.content))
+}
+
+/// Whether two pages are near duplicates, their SimHashes differing in at most
+/// `hamming_threshold` bits. Titles are not compared, mirrors often rename pages
+///
+/// # Arguments
+///
+/// * `a` - The first page
+/// * `b` - The second page
+/// * `hamming_threshold` - Maximum number of differing bits, 3 is a common choice
+///
+/// # Returns
+///
+/// true if the pages are near duplicates
+pub fn is_near_duplicate(a: &Page, b: &Page, hamming_threshold: u32) -> bool {
+    (simhash(a) ^ simhash(b)).count_ones() <= hamming_threshold
+}
+
+fn simhash_terms(terms: &[String]) -> u64 {
+    let mut counts: HashMap<&str, i64> = HashMap::new();
+    for term in terms {
+        *counts.entry(term.as_str()).or_insert(0) += 1;
+    }
+
+    // every term votes on every bit, weighted by its count
+    let mut bits = [0i64; 64];
+    for (term, count) in counts {
+        let hash = fnv1a(term.as_bytes());
+        for (bit, vote) in bits.iter_mut().enumerate() {
+            if hash >> bit & 1 == 1 {
+                *vote += count;
+            } else {
+                *vote -= count;
+            }
+        }
+    }
+
+    bits.iter()
+        .enumerate()
+        .filter(|(_, &vote)| vote > 0)
+        .fold(0u64, |hash, (bit, _)| hash | 1 << bit)
+}
+
+// lowercased words with surrounding punctuation trimmed, any script
+fn signature_terms(content: &str) -> Vec<String> {
+    content
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
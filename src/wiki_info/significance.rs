@@ -0,0 +1,123 @@
+use super::{
+    cosine_sim, hashing::splitmix64, vocabulary::Vocabulary, Page, VectorOptions, WikiError,
+};
+
+// fixed so the null distribution, and so the p-value, is the same on every run
+const SEED: u64 = 0x5eed;
+
+/// A similarity score with how unusual it is for pages of these lengths
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Significance {
+    /// The cosine similarity of the pages
+    pub similarity: f64,
+    /// Mean similarity of the permuted pages
+    pub null_mean: f64,
+    /// Standard deviation of the similarity of the permuted pages
+    pub null_std_dev: f64,
+    /// Standard deviations above the null mean, 0 if the null distribution has no spread
+    pub z_score: f64,
+    /// Share of permutations scoring at least the similarity, with the usual +1
+    /// correction so it is never exactly 0
+    pub p_value: f64,
+}
+
+/// Tests whether the similarity of two pages is higher than chance. The term
+/// weights of the second page are shuffled over the shared vocabulary many times,
+/// keeping both pages' lengths and frequency profiles but not which terms they share,
+/// and the real similarity is compared against the shuffled ones. A cosine of 0.3
+/// is unremarkable for two stubs but significant for two long articles
+///
+/// # Arguments
+///
+/// * `a` - The first page
+/// * `b` - The second page
+/// * `permutations` - Number of shuffles, more give a finer p-value
+///
+/// # Returns
+///
+/// Ok(Significance) - the similarity, its z-score and p-value
+/// Err(WikiError::InputError) - error if `permutations` is 0
+pub fn similarity_significance(
+    a: &Page,
+    b: &Page,
+    permutations: usize,
+) -> Result<Significance, WikiError> {
+    similarity_significance_with(a, b, permutations, &VectorOptions::default())
+}
+
+/// Tests whether the similarity of two pages is higher than chance, vectorized
+/// with the given options, see `similarity_significance`
+///
+/// # Arguments
+///
+/// * `a` - The first page
+/// * `b` - The second page
+/// * `permutations` - Number of shuffles, more give a finer p-value
+/// * `options` - How the pages are vectorized
+///
+/// # Returns
+///
+/// Ok(Significance) - the similarity, its z-score and p-value
+/// Err(WikiError::InputError) - error if `permutations` is 0
+pub fn similarity_significance_with(
+    a: &Page,
+    b: &Page,
+    permutations: usize,
+    options: &VectorOptions,
+) -> Result<Significance, WikiError> {
+    if permutations == 0 {
+        return Err(WikiError::InputError(
+            "Significance needs at least one permutation".to_owned(),
+        ));
+    }
+
+    let vocabulary = Vocabulary::from_pages([a, b], options);
+    let vec_a = vocabulary.vectorize(a, options);
+    let mut vec_b = vocabulary.vectorize(b, options);
+
+    let similarity = zero_nan(cosine_sim(&vec_a, &vec_b));
+
+    let mut random = SEED;
+    let mut null = Vec::with_capacity(permutations);
+    for _ in 0..permutations {
+        // Fisher-Yates
+        for i in (1..vec_b.len()).rev() {
+            random = splitmix64(random);
+            let j = (random % (i as u64 + 1)) as usize;
+            vec_b.swap(i, j);
+        }
+
+        null.push(zero_nan(cosine_sim(&vec_a, &vec_b)));
+    }
+
+    let null_mean = null.iter().sum::<f64>() / permutations as f64;
+    let null_std_dev = (null
+        .iter()
+        .map(|sim| (sim - null_mean).powi(2))
+        .sum::<f64>()
+        / permutations as f64)
+        .sqrt();
+
+    let z_score = if null_std_dev > 0.0 {
+        (similarity - null_mean) / null_std_dev
+    } else {
+        0.0
+    };
+    let at_least = null.iter().filter(|&&sim| sim >= similarity).count();
+
+    Ok(Significance {
+        similarity,
+        null_mean,
+        null_std_dev,
+        z_score,
+        p_value: (at_least + 1) as f64 / (permutations + 1) as f64,
+    })
+}
+
+fn zero_nan(sim: f64) -> f64 {
+    if sim.is_nan() {
+        0.0
+    } else {
+        sim
+    }
+}
@@ -0,0 +1,252 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::mpsc::Sender,
+};
+
+use log::debug;
+
+use super::{Page, WikiError};
+
+/// A destination for fetched pages.
+/// Anything that produces pages (a traversal, a batch fetch, etc) can hand
+/// them to a sink without caring where they end up
+pub trait PageSink {
+    /// Accept a single page
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - the page to store
+    ///
+    /// # Returns
+    ///
+    /// Ok(()) - page was accepted
+    /// Err(WikiError) - error if the page could not be stored
+    fn accept(&mut self, page: &Page) -> Result<(), WikiError>;
+
+    /// Flush any buffered pages to the underlying destination.
+    /// Default impl is a no-op for unbuffered sinks
+    fn flush(&mut self) -> Result<(), WikiError> {
+        Ok(())
+    }
+}
+
+/// Collect pages into memory
+impl PageSink for Vec<Page> {
+    fn accept(&mut self, page: &Page) -> Result<(), WikiError> {
+        self.push(page.clone());
+        Ok(())
+    }
+}
+
+/// Streams pages to any writer as newline delimited JSON, one page per line.
+/// Each page is serialized before anything is written, so a page that fails
+/// never leaves half a line behind
+pub struct NdjsonSink<W: Write> {
+    writer: W,
+    flush_each_page: bool,
+}
+
+impl<W: Write> NdjsonSink<W> {
+    /// Wraps a writer as a sink, flushing after every page so downstream consumers
+    /// (`jq`, another process on a pipe) see each page as soon as it is accepted
+    /// rather than when the writer's buffer fills
+    pub fn new(writer: W) -> Self {
+        NdjsonSink::with_flush(writer, true)
+    }
+
+    /// Wraps a writer as a sink
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - where the pages are written
+    /// * `flush_each_page` - flush the writer after every page, instead of only
+    ///   on `PageSink::flush`
+    pub fn with_flush(writer: W, flush_each_page: bool) -> Self {
+        NdjsonSink {
+            writer,
+            flush_each_page,
+        }
+    }
+
+    /// Gets the wrapped writer back
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl NdjsonSink<std::io::Stdout> {
+    /// A sink streaming to standard output
+    pub fn stdout() -> Self {
+        NdjsonSink::new(std::io::stdout())
+    }
+}
+
+impl<W: Write> PageSink for NdjsonSink<W> {
+    fn accept(&mut self, page: &Page) -> Result<(), WikiError> {
+        let mut line =
+            serde_json::to_vec(page).map_err(|err| WikiError::IOError(err.to_string()))?;
+        line.push(b'\n');
+
+        self.writer
+            .write_all(&line)
+            .map_err(|err| WikiError::IOError(err.to_string()))?;
+
+        if self.flush_each_page {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), WikiError> {
+        self.writer
+            .flush()
+            .map_err(|err| WikiError::IOError(err.to_string()))
+    }
+}
+
+/// Writes pages to a file as newline delimited JSON, one page per line
+pub type FileSink = NdjsonSink<BufWriter<File>>;
+
+impl FileSink {
+    /// Creates (or truncates) the file at `path`. Pages are buffered until
+    /// `PageSink::flush`, see `with_flush`
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path of the output file
+    ///
+    /// # Returns
+    ///
+    /// Ok(FileSink) - the new sink
+    /// Err(WikiError::IOError) - error if the file could not be created
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, WikiError> {
+        debug!("Creating FileSink at {:?}", path.as_ref());
+        let file = File::create(path).map_err(|err| WikiError::IOError(err.to_string()))?;
+
+        Ok(NdjsonSink::with_flush(BufWriter::new(file), false))
+    }
+}
+
+/// Hands every page to a user supplied closure
+pub struct CallbackSink<F>
+where
+    F: FnMut(&Page) -> Result<(), WikiError>,
+{
+    callback: F,
+}
+
+impl<F> CallbackSink<F>
+where
+    F: FnMut(&Page) -> Result<(), WikiError>,
+{
+    /// Wraps a closure as a sink
+    pub fn new(callback: F) -> Self {
+        CallbackSink { callback }
+    }
+}
+
+impl<F> PageSink for CallbackSink<F>
+where
+    F: FnMut(&Page) -> Result<(), WikiError>,
+{
+    fn accept(&mut self, page: &Page) -> Result<(), WikiError> {
+        (self.callback)(page)
+    }
+}
+
+/// Sends every page down an mpsc channel, for consuming pages on another thread
+pub struct ChannelSink {
+    sender: Sender<Page>,
+}
+
+impl ChannelSink {
+    /// Wraps the sending half of a channel as a sink
+    pub fn new(sender: Sender<Page>) -> Self {
+        ChannelSink { sender }
+    }
+}
+
+impl PageSink for ChannelSink {
+    fn accept(&mut self, page: &Page) -> Result<(), WikiError> {
+        self.sender
+            .send(page.clone())
+            .map_err(|_| WikiError::IOError("Receiving end of channel was dropped".to_owned()))
+    }
+}
+
+/// Stores pages and their outlinks in a sqlite database.
+/// Requires the `sqlite` feature
+#[cfg(feature = "sqlite")]
+pub struct SqliteSink {
+    connection: rusqlite::Connection,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteSink {
+    /// Opens (or creates) the database at `path` and creates the
+    /// `pages` and `links` tables if they do not exist yet
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path of the sqlite database
+    ///
+    /// # Returns
+    ///
+    /// Ok(SqliteSink) - the new sink
+    /// Err(WikiError::IOError) - error if the database could not be opened
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, WikiError> {
+        debug!("Opening SqliteSink at {:?}", path.as_ref());
+        let connection =
+            rusqlite::Connection::open(path).map_err(|err| WikiError::IOError(err.to_string()))?;
+
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS pages (
+                    title TEXT PRIMARY KEY,
+                    content TEXT NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS links (
+                    source TEXT NOT NULL,
+                    title TEXT NOT NULL,
+                    outlink TEXT NOT NULL
+                );",
+            )
+            .map_err(|err| WikiError::IOError(err.to_string()))?;
+
+        Ok(SqliteSink { connection })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl PageSink for SqliteSink {
+    fn accept(&mut self, page: &Page) -> Result<(), WikiError> {
+        let tx = self
+            .connection
+            .transaction()
+            .map_err(|err| WikiError::IOError(err.to_string()))?;
+
+        tx.execute(
+            "INSERT OR REPLACE INTO pages (title, content) VALUES (?1, ?2)",
+            (&page.title, &page.content),
+        )
+        .map_err(|err| WikiError::IOError(err.to_string()))?;
+
+        // re-accepting a page replaces its links instead of duplicating them
+        tx.execute("DELETE FROM links WHERE source = ?1", [&page.title])
+            .map_err(|err| WikiError::IOError(err.to_string()))?;
+
+        for link in &page.links {
+            tx.execute(
+                "INSERT INTO links (source, title, outlink) VALUES (?1, ?2, ?3)",
+                (&page.title, &link.title, &link.outlink),
+            )
+            .map_err(|err| WikiError::IOError(err.to_string()))?;
+        }
+
+        tx.commit()
+            .map_err(|err| WikiError::IOError(err.to_string()))
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::{HashMap, HashSet};
+
+use rayon::prelude::*;
+
+use super::{
+    cosine_sim_f32, count_terms, embeddings::WordEmbeddings, prepare_document, Page, VectorOptions,
+};
+
+// term pairs less similar than this are treated as unrelated, keeps
+// the many weak character n-gram overlaps from blurring every page together
+const DEFAULT_MIN_TERM_SIMILARITY: f64 = 0.5;
+
+/// How similar two cleaned terms are, the term-term matrix of `soft_cosine_sim`
+pub trait TermSimilarity: Send + Sync {
+    /// Similarity of two different terms
+    ///
+    /// # Returns
+    ///
+    /// The similarity [0-1], 0 for unrelated terms
+    fn term_similarity(&self, a: &str, b: &str) -> f64;
+}
+
+/// Terms are similar as far as their word vectors point the same way.
+/// Terms without a vector are unrelated to everything
+impl TermSimilarity for WordEmbeddings {
+    fn term_similarity(&self, a: &str, b: &str) -> f64 {
+        match (self.get(a), self.get(b)) {
+            (Some(a), Some(b)) => cosine_sim_f32(a, b).max(0.0),
+            _ => 0.0,
+        }
+    }
+}
+
+/// Terms are similar as far as they share character n-grams (Dice coefficient),
+/// so `"volcanic"` and `"volcano"` match without any pretrained vectors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CharNgramSimilarity {
+    /// Length of the n-grams, 3 works well for english
+    pub n: usize,
+}
+
+impl Default for CharNgramSimilarity {
+    fn default() -> Self {
+        CharNgramSimilarity { n: 3 }
+    }
+}
+
+impl TermSimilarity for CharNgramSimilarity {
+    fn term_similarity(&self, a: &str, b: &str) -> f64 {
+        let (a, b) = (char_ngrams(a, self.n), char_ngrams(b, self.n));
+
+        if a.is_empty() || b.is_empty() {
+            return 0.0;
+        }
+
+        2.0 * a.intersection(&b).count() as f64 / (a.len() + b.len()) as f64
+    }
+}
+
+/// The soft cosine similarity of two pages: cosine similarity where different but
+/// related terms also count towards the score, weighted by how related they are.
+/// Bridges pages covering the same subject with largely different vocabulary.
+/// Term pairs under 0.5 similarity are ignored
+///
+/// # Arguments
+///
+/// * `a` - The first page
+/// * `b` - The second page
+/// * `similarity` - Where term similarities come from, ie `WordEmbeddings`
+///
+/// # Returns
+///
+/// The similarity [0-1], 0 if either page has no terms
+pub fn soft_cosine_sim<S: TermSimilarity + ?Sized>(a: &Page, b: &Page, similarity: &S) -> f64 {
+    soft_cosine_sim_with(
+        a,
+        b,
+        similarity,
+        DEFAULT_MIN_TERM_SIMILARITY,
+        &VectorOptions::default(),
+    )
+}
+
+/// The soft cosine similarity of two pages, see `soft_cosine_sim`
+///
+/// # Arguments
+///
+/// * `a` - The first page
+/// * `b` - The second page
+/// * `similarity` - Where term similarities come from
+/// * `min_term_similarity` - Term pairs less similar than this count as unrelated,
+///   0 keeps every pair
+/// * `options` - How the pages are cleaned
+///
+/// # Returns
+///
+/// The similarity [0-1], 0 if either page has no terms
+pub fn soft_cosine_sim_with<S: TermSimilarity + ?Sized>(
+    a: &Page,
+    b: &Page,
+    similarity: &S,
+    min_term_similarity: f64,
+    options: &VectorOptions,
+) -> f64 {
+    let counts_a = count_terms(&prepare_document(a, options).content);
+    let counts_b = count_terms(&prepare_document(b, options).content);
+
+    let mut terms: Vec<&str> = counts_a
+        .keys()
+        .chain(counts_b.keys())
+        .map(String::as_str)
+        .collect::<HashSet<&str>>()
+        .into_iter()
+        .collect();
+    terms.sort_unstable();
+
+    let vector = |counts: &HashMap<String, usize>| -> Vec<f64> {
+        terms
+            .iter()
+            .map(|&term| counts.get(term).copied().unwrap_or(0) as f64)
+            .collect()
+    };
+    let (vec_a, vec_b) = (vector(&counts_a), vector(&counts_b));
+
+    // sparse upper triangle of the term similarity matrix, the diagonal is 1
+    let related: Vec<(usize, usize, f64)> = (0..terms.len())
+        .into_par_iter()
+        .flat_map_iter(|i| {
+            let terms = &terms;
+            (i + 1..terms.len()).filter_map(move |j| {
+                let sim = similarity.term_similarity(terms[i], terms[j]);
+                (sim > 0.0 && sim >= min_term_similarity).then_some((i, j, sim))
+            })
+        })
+        .collect();
+
+    // x^T S y
+    let soft_dot = |x: &[f64], y: &[f64]| -> f64 {
+        let diagonal: f64 = x.iter().zip(y).map(|(x, y)| x * y).sum();
+        let off_diagonal: f64 = related
+            .iter()
+            .map(|&(i, j, sim)| sim * (x[i] * y[j] + x[j] * y[i]))
+            .sum();
+
+        diagonal + off_diagonal
+    };
+
+    let norm = (soft_dot(&vec_a, &vec_a) * soft_dot(&vec_b, &vec_b)).sqrt();
+    if norm == 0.0 {
+        return 0.0;
+    }
+
+    (soft_dot(&vec_a, &vec_b) / norm).min(1.0)
+}
+
+// n-grams of the term padded with spaces, so short terms still have some
+fn char_ngrams(term: &str, n: usize) -> HashSet<String> {
+    let padded: Vec<char> = format!(" {} ", term).chars().collect();
+
+    padded
+        .windows(n.max(1))
+        .map(|gram| gram.iter().collect())
+        .collect()
+}
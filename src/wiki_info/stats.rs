@@ -0,0 +1,89 @@
+use std::{collections::HashMap, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use super::{clean_document_with, count_terms, CleanOptions, Page};
+
+// average adult silent reading speed for non fiction
+const WORDS_PER_MINUTE: f64 = 238.0;
+
+/// Size and link statistics of a page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct PageStats {
+    /// Whitespace separated words in the content
+    pub word_count: usize,
+    /// Distinct terms left after cleaning, see `clean_document`
+    pub unique_terms: usize,
+    /// Distinct pages linked to
+    pub outlink_count: usize,
+    /// Links per word, counting every occurrence of a link
+    pub link_density: f64,
+    /// Distinct citations in the reference lists
+    pub reference_count: usize,
+    /// Estimated time to read the content
+    pub reading_time: Duration,
+}
+
+impl Page {
+    /// Computes statistics about the page's size and links
+    ///
+    /// # Returns
+    ///
+    /// The PageStats of this page
+    pub fn stats(&self) -> PageStats {
+        let word_count = self.content.split_whitespace().count();
+
+        let unique_terms = self.term_frequencies(&CleanOptions::default()).len();
+
+        // links built by hand may not have counted their occurrences
+        let link_occurrences: usize = self.links.iter().map(|link| link.count.max(1)).sum();
+        let link_density = if word_count == 0 {
+            0.0
+        } else {
+            link_occurrences as f64 / word_count as f64
+        };
+
+        PageStats {
+            word_count,
+            unique_terms,
+            outlink_count: self.links.len(),
+            link_density,
+            reference_count: self.reference_count,
+            reading_time: Duration::from_secs_f64(word_count as f64 / WORDS_PER_MINUTE * 60.0),
+        }
+    }
+
+    /// Counts how often every term occurs in the cleaned content
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - How the content is cleaned, see `clean_document_with`
+    ///
+    /// # Returns
+    ///
+    /// The count of every term
+    pub fn term_frequencies(&self, options: &CleanOptions) -> HashMap<String, usize> {
+        count_terms(&clean_document_with(self, options).content)
+    }
+
+    /// The most frequent terms of the cleaned content
+    ///
+    /// # Arguments
+    ///
+    /// * `k` - the maximum number of terms
+    ///
+    /// # Returns
+    ///
+    /// Up to `k` (term, count) pairs, most frequent first, ties in alphabetical order
+    pub fn top_terms(&self, k: usize) -> Vec<(String, usize)> {
+        let mut terms: Vec<(String, usize)> = self
+            .term_frequencies(&CleanOptions::default())
+            .into_iter()
+            .collect();
+
+        terms.sort_by(|(a_term, a), (b_term, b)| b.cmp(a).then_with(|| a_term.cmp(b_term)));
+        terms.truncate(k);
+
+        terms
+    }
+}
@@ -0,0 +1,94 @@
+use std::borrow::Cow;
+
+use rust_stemmers::Algorithm;
+
+/// A Snowball stemmer, reduces words to a common stem so inflections of a word
+/// (`"government"`, `"governments"`) map to the same vocabulary entry.
+/// `Stemmer::English` is the Porter2 algorithm
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Stemmer {
+    Danish,
+    Dutch,
+    #[default]
+    English,
+    Finnish,
+    French,
+    German,
+    Hungarian,
+    Italian,
+    Norwegian,
+    Portuguese,
+    Romanian,
+    Russian,
+    Spanish,
+    Swedish,
+    Turkish,
+}
+
+impl Stemmer {
+    /// Gets the stemmer of a language
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - ISO 639-1 or 639-3 language code, ie `"de"` or `"deu"` as given
+    ///   by `language::detect_language`
+    ///
+    /// # Returns
+    ///
+    /// The stemmer, None if there is no stemmer for the language
+    pub fn for_language(code: &str) -> Option<Self> {
+        let stemmer = match code.to_lowercase().as_str() {
+            "da" | "dan" => Stemmer::Danish,
+            "nl" | "nld" | "dut" => Stemmer::Dutch,
+            "en" | "eng" => Stemmer::English,
+            "fi" | "fin" => Stemmer::Finnish,
+            "fr" | "fra" | "fre" => Stemmer::French,
+            "de" | "deu" | "ger" => Stemmer::German,
+            "hu" | "hun" => Stemmer::Hungarian,
+            "it" | "ita" => Stemmer::Italian,
+            "no" | "nor" | "nb" | "nob" => Stemmer::Norwegian,
+            "pt" | "por" => Stemmer::Portuguese,
+            "ro" | "ron" | "rum" => Stemmer::Romanian,
+            "ru" | "rus" => Stemmer::Russian,
+            "es" | "spa" => Stemmer::Spanish,
+            "sv" | "swe" => Stemmer::Swedish,
+            "tr" | "tur" => Stemmer::Turkish,
+            _ => return None,
+        };
+
+        Some(stemmer)
+    }
+
+    /// Stems a single lowercase word
+    ///
+    /// # Arguments
+    ///
+    /// * `word` - the word to stem
+    ///
+    /// # Returns
+    ///
+    /// The stem, borrowed if the word was already a stem
+    pub fn stem<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        rust_stemmers::Stemmer::create(self.algorithm()).stem(word)
+    }
+
+    fn algorithm(&self) -> Algorithm {
+        match self {
+            Stemmer::Danish => Algorithm::Danish,
+            Stemmer::Dutch => Algorithm::Dutch,
+            Stemmer::English => Algorithm::English,
+            Stemmer::Finnish => Algorithm::Finnish,
+            Stemmer::French => Algorithm::French,
+            Stemmer::German => Algorithm::German,
+            Stemmer::Hungarian => Algorithm::Hungarian,
+            Stemmer::Italian => Algorithm::Italian,
+            Stemmer::Norwegian => Algorithm::Norwegian,
+            Stemmer::Portuguese => Algorithm::Portuguese,
+            Stemmer::Romanian => Algorithm::Romanian,
+            Stemmer::Russian => Algorithm::Russian,
+            Stemmer::Spanish => Algorithm::Spanish,
+            Stemmer::Swedish => Algorithm::Swedish,
+            Stemmer::Turkish => Algorithm::Turkish,
+        }
+    }
+}
@@ -1,133 +1,349 @@
+use std::{
+    collections::HashSet,
+    fs,
+    hash::{Hash, Hasher},
+    path::Path,
+    sync::Arc,
+};
+
 use lazy_static::lazy_static;
 
+use super::WikiError;
+
+// the original english list
+const ENGLISH: [&str; 127] = [
+    "i",
+    "me",
+    "my",
+    "myself",
+    "we",
+    "our",
+    "ours",
+    "ourselves",
+    "you",
+    "your",
+    "yours",
+    "yourself",
+    "yourselves",
+    "he",
+    "him",
+    "his",
+    "himself",
+    "she",
+    "her",
+    "hers",
+    "herself",
+    "it",
+    "its",
+    "itself",
+    "they",
+    "them",
+    "their",
+    "theirs",
+    "themselves",
+    "what",
+    "which",
+    "who",
+    "whom",
+    "this",
+    "that",
+    "these",
+    "those",
+    "am",
+    "is",
+    "are",
+    "was",
+    "were",
+    "be",
+    "been",
+    "being",
+    "have",
+    "has",
+    "had",
+    "having",
+    "do",
+    "does",
+    "did",
+    "doing",
+    "a",
+    "an",
+    "the",
+    "and",
+    "but",
+    "if",
+    "or",
+    "because",
+    "as",
+    "until",
+    "while",
+    "of",
+    "at",
+    "by",
+    "for",
+    "with",
+    "about",
+    "against",
+    "between",
+    "into",
+    "through",
+    "during",
+    "before",
+    "after",
+    "above",
+    "below",
+    "to",
+    "from",
+    "up",
+    "down",
+    "in",
+    "out",
+    "on",
+    "off",
+    "over",
+    "under",
+    "again",
+    "further",
+    "then",
+    "once",
+    "here",
+    "there",
+    "when",
+    "where",
+    "why",
+    "how",
+    "all",
+    "any",
+    "both",
+    "each",
+    "few",
+    "more",
+    "most",
+    "other",
+    "some",
+    "such",
+    "no",
+    "nor",
+    "not",
+    "only",
+    "own",
+    "same",
+    "so",
+    "than",
+    "too",
+    "very",
+    "s",
+    "t",
+    "can",
+    "will",
+    "just",
+    "don",
+    "should",
+    "now",
+];
+
 lazy_static! {
-    pub static ref STOP_WORDS: Vec<String> = vec![
-        "i",
-        "me",
-        "my",
-        "myself",
-        "we",
-        "our",
-        "ours",
-        "ourselves",
-        "you",
-        "your",
-        "yours",
-        "yourself",
-        "yourselves",
-        "he",
-        "him",
-        "his",
-        "himself",
-        "she",
-        "her",
-        "hers",
-        "herself",
-        "it",
-        "its",
-        "itself",
-        "they",
-        "them",
-        "their",
-        "theirs",
-        "themselves",
-        "what",
-        "which",
-        "who",
-        "whom",
-        "this",
-        "that",
-        "these",
-        "those",
-        "am",
-        "is",
-        "are",
-        "was",
-        "were",
-        "be",
-        "been",
-        "being",
-        "have",
-        "has",
-        "had",
-        "having",
-        "do",
-        "does",
-        "did",
-        "doing",
-        "a",
-        "an",
-        "the",
-        "and",
-        "but",
-        "if",
-        "or",
-        "because",
-        "as",
-        "until",
-        "while",
-        "of",
-        "at",
-        "by",
-        "for",
-        "with",
-        "about",
-        "against",
-        "between",
-        "into",
-        "through",
-        "during",
-        "before",
-        "after",
-        "above",
-        "below",
-        "to",
-        "from",
-        "up",
-        "down",
-        "in",
-        "out",
-        "on",
-        "off",
-        "over",
-        "under",
-        "again",
-        "further",
-        "then",
-        "once",
-        "here",
-        "there",
-        "when",
-        "where",
-        "why",
-        "how",
-        "all",
-        "any",
-        "both",
-        "each",
-        "few",
-        "more",
-        "most",
-        "other",
-        "some",
-        "such",
-        "no",
-        "nor",
-        "not",
-        "only",
-        "own",
-        "same",
-        "so",
-        "than",
-        "too",
-        "very",
-        "s",
-        "t",
-        "can",
-        "will",
-        "just",
-        "don",
-        "should",
-        "now",
-    ].iter().map(|s| s.to_string()).collect::<Vec<String>>();
+    /// The built in english stop words
+    pub static ref STOP_WORDS: HashSet<&'static str> = ENGLISH.iter().copied().collect();
+    // shared by every english StopWords until one is modified
+    static ref ENGLISH_STOP_WORDS: StopWords = StopWords::from_words(ENGLISH);
+}
+
+const GERMAN: [&str; 60] = [
+    "aber", "alle", "als", "also", "am", "an", "auch", "auf", "aus", "bei", "bin", "bis", "das",
+    "dass", "dem", "den", "der", "des", "die", "doch", "du", "durch", "ein", "eine", "einem",
+    "einen", "einer", "es", "für", "hat", "ich", "ihr", "im", "in", "ist", "ja", "kann", "mit",
+    "nach", "nicht", "noch", "nur", "oder", "sich", "sie", "sind", "so", "um", "und", "uns", "von",
+    "vor", "war", "was", "wenn", "wie", "wir", "wird", "zu", "zum",
+];
+
+const FRENCH: [&str; 60] = [
+    "au", "aux", "avec", "ce", "ces", "cette", "dans", "de", "des", "du", "elle", "en", "est",
+    "et", "eux", "il", "ils", "je", "la", "le", "les", "leur", "lui", "ma", "mais", "me", "mes",
+    "moi", "mon", "ne", "nos", "notre", "nous", "on", "ou", "où", "par", "pas", "pour", "qu",
+    "que", "qui", "sa", "se", "ses", "son", "sont", "sur", "ta", "te", "tes", "toi", "ton", "tu",
+    "un", "une", "vos", "votre", "vous", "été",
+];
+
+const SPANISH: [&str; 60] = [
+    "a", "al", "algo", "como", "con", "cuando", "de", "del", "desde", "donde", "el", "ella",
+    "ellos", "en", "entre", "era", "es", "esa", "ese", "esta", "este", "está", "fue", "ha", "han",
+    "hay", "la", "las", "le", "les", "lo", "los", "me", "mi", "muy", "más", "ni", "no", "nos", "o",
+    "para", "pero", "por", "que", "se", "sin", "sobre", "su", "sus", "también", "te", "tiene",
+    "todo", "tu", "un", "una", "uno", "y", "ya", "yo",
+];
+
+const ITALIAN: [&str; 50] = [
+    "a", "al", "alla", "anche", "che", "chi", "ci", "come", "con", "da", "dal", "dei", "del",
+    "della", "di", "e", "è", "era", "gli", "ha", "i", "il", "in", "io", "la", "le", "lo", "ma",
+    "mi", "ne", "negli", "nel", "nella", "non", "o", "per", "più", "quando", "questo", "se", "si",
+    "sono", "su", "sua", "suo", "tra", "un", "una", "uno", "anno",
+];
+
+const DUTCH: [&str; 50] = [
+    "aan", "al", "als", "bij", "dan", "dat", "de", "der", "deze", "die", "dit", "door", "een",
+    "en", "er", "had", "heb", "het", "hij", "hoe", "ik", "in", "is", "je", "kan", "maar", "me",
+    "met", "mij", "na", "naar", "niet", "nog", "nu", "of", "om", "ook", "op", "over", "te", "tot",
+    "uit", "van", "voor", "was", "wat", "we", "werd", "zij", "zo",
+];
+
+const PORTUGUESE: [&str; 50] = [
+    "a", "ao", "aos", "as", "com", "como", "da", "das", "de", "do", "dos", "e", "ela", "ele",
+    "eles", "em", "entre", "era", "essa", "esse", "esta", "este", "foi", "há", "isso", "já",
+    "mais", "mas", "na", "nas", "no", "nos", "não", "o", "os", "ou", "para", "pela", "pelo", "por",
+    "que", "se", "sem", "ser", "seu", "sua", "são", "também", "um", "uma",
+];
+
+/// A set of words removed from documents before vectorizing.
+/// Words are matched lowercased. The default is the built in english list.
+/// Clones share the word set, so passing lists around is cheap
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StopWords {
+    words: Arc<HashSet<String>>,
+}
+
+impl Default for StopWords {
+    fn default() -> Self {
+        StopWords::english()
+    }
+}
+
+impl StopWords {
+    /// The built in english list
+    pub fn english() -> Self {
+        ENGLISH_STOP_WORDS.clone()
+    }
+
+    /// An empty list, nothing is removed
+    pub fn none() -> Self {
+        StopWords {
+            words: Arc::new(HashSet::new()),
+        }
+    }
+
+    /// Gets the built in list of a language
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - ISO 639-1 or 639-3 language code, ie `"de"` or `"deu"` as given
+    ///   by `language::detect_language`
+    ///
+    /// # Returns
+    ///
+    /// The list, None if there is no built in list for the language
+    pub fn for_language(code: &str) -> Option<Self> {
+        let words: &[&str] = match code.to_lowercase().as_str() {
+            "en" | "eng" => return Some(StopWords::english()),
+            "de" | "deu" | "ger" => &GERMAN,
+            "fr" | "fra" | "fre" => &FRENCH,
+            "es" | "spa" => &SPANISH,
+            "it" | "ita" => &ITALIAN,
+            "nl" | "nld" | "dut" => &DUTCH,
+            "pt" | "por" => &PORTUGUESE,
+            _ => return None,
+        };
+
+        Some(StopWords::from_words(words))
+    }
+
+    /// Builds a list from any words
+    pub fn from_words<I>(words: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        StopWords {
+            words: Arc::new(lowercase_words(words).collect()),
+        }
+    }
+
+    /// Loads a list from a file with one word per line.
+    /// Blank lines and lines starting with `#` are ignored
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path of the word list
+    ///
+    /// # Returns
+    ///
+    /// Ok(StopWords) - the loaded list
+    /// Err(WikiError::IOError) - error if the file could not be read
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, WikiError> {
+        let contents =
+            fs::read_to_string(path).map_err(|err| WikiError::IOError(err.to_string()))?;
+
+        Ok(StopWords::from_words(
+            contents
+                .lines()
+                .filter(|line| !line.trim_start().starts_with('#')),
+        ))
+    }
+
+    /// Adds words to the list
+    pub fn with_words<I>(mut self, words: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        Arc::make_mut(&mut self.words).extend(lowercase_words(words));
+        self
+    }
+
+    /// Removes words from the list, ie to keep "who" for a dataset about bands
+    pub fn without_words<I>(mut self, words: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let set = Arc::make_mut(&mut self.words);
+        for word in lowercase_words(words) {
+            set.remove(&word);
+        }
+        self
+    }
+
+    /// Whether a word is a stop word
+    pub fn contains(&self, word: &str) -> bool {
+        // cleaned words are already lowercase, only allocate for the rest
+        if word.chars().any(char::is_uppercase) {
+            self.words.contains(&word.to_lowercase())
+        } else {
+            self.words.contains(word)
+        }
+    }
+
+    /// Number of words in the list
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Whether the list is empty
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// The words of the list, sorted
+    pub fn to_sorted_vec(&self) -> Vec<String> {
+        let mut words: Vec<String> = self.words.iter().cloned().collect();
+        words.sort();
+
+        words
+    }
+}
+
+// hashed sorted, hash set order differs between runs
+impl Hash for StopWords {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_sorted_vec().hash(state);
+    }
+}
+
+fn lowercase_words<I>(words: I) -> impl Iterator<Item = String>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    words
+        .into_iter()
+        .map(|word| word.as_ref().trim().to_lowercase())
+        .filter(|word| !word.is_empty())
 }
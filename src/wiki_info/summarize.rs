@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use super::{clean_document, cosine_sim, text::split_sentences, Page};
+
+/// Builds an extractive summary of a page from its most central sentences.
+/// Sentences are treated as documents: each is weighted by TF-IDF over the
+/// sentences of the page and scored by its cosine similarity to their centroid
+///
+/// # Arguments
+///
+/// * `page` - the page to summarize
+/// * `n_sentences` - the maximum number of sentences in the summary
+///
+/// # Returns
+///
+/// The chosen sentences in page order, joined by spaces
+pub fn summarize(page: &Page, n_sentences: usize) -> String {
+    let sentences = split_sentences(&page.content);
+    if sentences.len() <= n_sentences {
+        return sentences.join(" ");
+    }
+
+    let terms: Vec<Vec<String>> = sentences
+        .iter()
+        .map(|&sentence| {
+            let sentence = Page {
+                content: sentence.to_owned(),
+                ..Default::default()
+            };
+
+            clean_document(&sentence)
+                .content
+                .split_whitespace()
+                .map(str::to_owned)
+                .collect()
+        })
+        .collect();
+
+    let mut vocab: HashMap<&str, usize> = HashMap::new();
+    let mut document_frequency: Vec<usize> = Vec::new();
+    for sentence in &terms {
+        let mut seen = vec![false; document_frequency.len()];
+        for term in sentence {
+            let id = *vocab.entry(term).or_insert_with(|| {
+                document_frequency.push(0);
+                seen.push(false);
+                document_frequency.len() - 1
+            });
+
+            if !seen[id] {
+                seen[id] = true;
+                document_frequency[id] += 1;
+            }
+        }
+    }
+
+    // smoothed so terms in every sentence still count a little
+    let n = sentences.len() as f64;
+    let idf: Vec<f64> = document_frequency
+        .iter()
+        .map(|&df| (n / df as f64).ln() + 1.0)
+        .collect();
+
+    let vectors: Vec<Vec<f64>> = terms
+        .iter()
+        .map(|sentence| {
+            let mut vector = vec![0.0; vocab.len()];
+            for term in sentence {
+                let id = vocab[term.as_str()];
+                vector[id] += idf[id];
+            }
+
+            vector
+        })
+        .collect();
+
+    let mut centroid = vec![0.0; vocab.len()];
+    for vector in &vectors {
+        for (sum, weight) in centroid.iter_mut().zip(vector) {
+            *sum += weight / n;
+        }
+    }
+
+    let mut ranked: Vec<(usize, f64)> = vectors
+        .iter()
+        .map(|vector| cosine_sim(vector, &centroid))
+        .map(|score| if score.is_nan() { 0.0 } else { score })
+        .enumerate()
+        .collect();
+
+    // first sentence wins ties, leads tend to be good summaries
+    ranked.sort_by(|(i, a), (j, b)| b.total_cmp(a).then_with(|| i.cmp(j)));
+
+    let mut chosen: Vec<usize> = ranked
+        .into_iter()
+        .take(n_sentences)
+        .map(|(i, _)| i)
+        .collect();
+    chosen.sort_unstable();
+
+    chosen
+        .into_iter()
+        .map(|i| sentences[i])
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
@@ -0,0 +1,160 @@
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+
+use super::{clean_meta_content, text::normalize_text, visible_text, wiki_href, HyperLink, Page};
+
+/// A `wikitable` parsed into a grid of cells.
+/// Cells spanning several rows or columns are repeated in every slot they cover,
+/// so every row of a well formed table has the same length
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
+pub struct Table {
+    pub caption: Option<String>,
+    /// Text of the leading header row, empty if the table has none
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<TableCell>>,
+}
+
+/// A single table cell
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
+pub struct TableCell {
+    pub text: String,
+    /// Wiki links inside the cell, resolved to full urls
+    pub links: Vec<HyperLink>,
+}
+
+/// Gets the `wikitable`s of a page. Tables are parsed along with the page,
+/// whether or not `ParseOptions::include_tables` keeps their text in the content
+///
+/// # Arguments
+///
+/// * `page` - the page to get the tables of
+///
+/// # Returns
+///
+/// The tables in document order
+pub fn extract_tables(page: &Page) -> Vec<Table> {
+    page.tables.clone()
+}
+
+/// Parses every `wikitable` below an element, ie the content root of a page
+pub(crate) fn extract_tables_from(element: ElementRef) -> Vec<Table> {
+    let selector = Selector::parse("table.wikitable").unwrap();
+
+    element.select(&selector).map(parse_table).collect()
+}
+
+/// Parses a single `table` element into a Table
+pub(crate) fn parse_table(table: ElementRef) -> Table {
+    let caption_selector = Selector::parse("caption").unwrap();
+    let row_selector = Selector::parse("tr").unwrap();
+
+    let caption = table
+        .select(&caption_selector)
+        .next()
+        .map(|caption| clean_meta_content(&visible_text(caption)));
+
+    let mut grid: Vec<Vec<TableCell>> = Vec::new();
+    // (rows left, cell) for every column still covered by a rowspan from above
+    let mut pending: Vec<Option<(usize, TableCell)>> = Vec::new();
+    let mut header_row = None;
+
+    // nested tables have their own rows, only take rows belonging to this table
+    let rows = table
+        .select(&row_selector)
+        .filter(|row| row.ancestors().filter_map(ElementRef::wrap).find(is_table) == Some(table));
+
+    for row in rows {
+        let mut cells: Vec<TableCell> = Vec::new();
+        let mut all_headers = true;
+
+        let row_cells = row
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|cell| matches!(cell.value().name(), "td" | "th"));
+
+        for cell in row_cells {
+            fill_pending(&mut cells, &mut pending);
+
+            all_headers &= cell.value().name() == "th";
+            let parsed = parse_cell(cell);
+            let colspan = span(cell, "colspan");
+            let rowspan = span(cell, "rowspan");
+
+            for _ in 0..colspan {
+                let column = cells.len();
+                if rowspan > 1 {
+                    if pending.len() <= column {
+                        pending.resize(column + 1, None);
+                    }
+                    pending[column] = Some((rowspan - 1, parsed.clone()));
+                }
+                cells.push(parsed.clone());
+            }
+        }
+
+        fill_pending(&mut cells, &mut pending);
+
+        if cells.is_empty() {
+            continue;
+        }
+
+        if grid.is_empty() && header_row.is_none() && all_headers {
+            header_row = Some(cells.into_iter().map(|cell| cell.text).collect());
+        } else {
+            grid.push(cells);
+        }
+    }
+
+    Table {
+        caption,
+        headers: header_row.unwrap_or_default(),
+        rows: grid,
+    }
+}
+
+// push cells carried down by rowspans into the current row position
+fn fill_pending(cells: &mut Vec<TableCell>, pending: &mut [Option<(usize, TableCell)>]) {
+    while let Some(slot) = pending.get_mut(cells.len()) {
+        match slot.take() {
+            Some((rows_left, cell)) => {
+                cells.push(cell.clone());
+                if rows_left > 1 {
+                    *slot = Some((rows_left - 1, cell));
+                }
+            }
+            None => break,
+        }
+    }
+}
+
+fn is_table(element: &ElementRef) -> bool {
+    element.value().name() == "table"
+}
+
+fn span(cell: ElementRef, attr: &str) -> usize {
+    cell.value()
+        .attr(attr)
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .filter(|&value| value > 0)
+        .unwrap_or(1)
+}
+
+fn parse_cell(cell: ElementRef) -> TableCell {
+    let link_selector = Selector::parse("a[href]").unwrap();
+
+    let links = cell
+        .select(&link_selector)
+        .filter_map(|link| Some((link, wiki_href(link)?)))
+        .enumerate()
+        .map(|(position, (link, (href, exists)))| HyperLink {
+            position,
+            exists,
+            ..HyperLink::from_href(normalize_text(&link.text().collect::<String>()), &href)
+        })
+        .collect();
+
+    TableCell {
+        text: clean_meta_content(&visible_text(cell)),
+        links,
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+
+use super::{build_vocab, prepare_document, Page, VectorOptions};
+
+/// A document reduced to the set of terms it contains, backed by a bitset.
+/// Term frequencies are thrown away, which makes set operations very cheap
+/// for coarse first pass similarity over large corpora
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TermSet {
+    bits: Vec<u64>,
+}
+
+impl TermSet {
+    /// Builds the term set of a page within a shared vocabulary.
+    /// Terms outside the vocabulary are ignored
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page to convert
+    /// * `vocab` - shared vocabulary that you want to use
+    /// * `options` - How the page is cleaned before taking its terms
+    ///
+    /// # Returns
+    ///
+    /// The set of vocabulary terms in the page
+    pub fn from_page(page: &Page, vocab: &HashMap<String, usize>, options: &VectorOptions) -> Self {
+        let mut bits = vec![0u64; vocab.len().div_ceil(64)];
+
+        for word in prepare_document(page, options).content.split_whitespace() {
+            if let Some(&id) = vocab.get(word) {
+                bits[id / 64] |= 1 << (id % 64);
+            }
+        }
+
+        TermSet { bits }
+    }
+
+    /// Number of terms in the set
+    pub fn len(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Whether the set has no terms
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|&word| word == 0)
+    }
+
+    /// Whether the term with the given vocabulary id is in the set
+    pub fn contains(&self, id: usize) -> bool {
+        self.bits
+            .get(id / 64)
+            .is_some_and(|word| word & (1 << (id % 64)) != 0)
+    }
+
+    /// Number of terms in both sets
+    pub fn intersection_len(&self, other: &TermSet) -> usize {
+        self.bits
+            .iter()
+            .zip(&other.bits)
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum()
+    }
+
+    /// Number of terms in either set
+    pub fn union_len(&self, other: &TermSet) -> usize {
+        let (longer, shorter) = if self.bits.len() >= other.bits.len() {
+            (&self.bits, &other.bits)
+        } else {
+            (&other.bits, &self.bits)
+        };
+
+        longer
+            .iter()
+            .enumerate()
+            .map(|(i, a)| (a | shorter.get(i).copied().unwrap_or(0)).count_ones() as usize)
+            .sum()
+    }
+
+    /// The Jaccard similarity of two sets, |A ∩ B| / |A ∪ B|
+    ///
+    /// # Returns
+    ///
+    /// The set similarity [0-1], 0 if both sets are empty
+    pub fn jaccard(&self, other: &TermSet) -> f64 {
+        let union = self.union_len(other);
+
+        if union == 0 {
+            return 0.0;
+        }
+
+        self.intersection_len(other) as f64 / union as f64
+    }
+
+    /// The overlap coefficient of two sets, |A ∩ B| / min(|A|, |B|).
+    /// A page whose terms are all in the other scores 1, however short it is
+    ///
+    /// # Returns
+    ///
+    /// The set similarity [0-1], 0 if either set is empty
+    pub fn overlap(&self, other: &TermSet) -> f64 {
+        let smaller = self.len().min(other.len());
+
+        if smaller == 0 {
+            return 0.0;
+        }
+
+        self.intersection_len(other) as f64 / smaller as f64
+    }
+}
+
+/// Builds the term sets of a set of pages over one shared vocabulary
+///
+/// # Arguments
+///
+/// * `pages` - The pages to convert
+/// * `options` - How the pages are cleaned
+///
+/// # Returns
+///
+/// One term set per page, in the same order
+pub fn term_sets(pages: &[Page], options: &VectorOptions) -> Vec<TermSet> {
+    let vocab = build_vocab(pages.iter(), options);
+
+    pages
+        .par_iter()
+        .map(|page| TermSet::from_page(page, &vocab, options))
+        .collect()
+}
@@ -0,0 +1,128 @@
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Decodes any HTML entities left in a string (`&amp;`, `&#160;`) and applies
+/// Unicode NFC normalization, so the same text always maps to the same terms
+pub(crate) fn normalize_text(text: &str) -> String {
+    html_escape::decode_html_entities(text).nfc().collect()
+}
+
+/// Folds diacritics into their base letters, `"Besançon"` becomes `"Besancon"`,
+/// so text typed with and without accents matches.
+/// Letters that do not decompose (`ß`, `æ`, `ø`, ...) are spelled out in ASCII
+pub fn fold_diacritics(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+
+    for c in text.nfd().filter(|&c| !is_combining_mark(c)) {
+        match c {
+            'ß' => folded.push_str("ss"),
+            'æ' => folded.push_str("ae"),
+            'Æ' => folded.push_str("AE"),
+            'œ' => folded.push_str("oe"),
+            'Œ' => folded.push_str("OE"),
+            'ø' => folded.push('o'),
+            'Ø' => folded.push('O'),
+            'đ' | 'ð' => folded.push('d'),
+            'Đ' | 'Ð' => folded.push('D'),
+            'ł' => folded.push('l'),
+            'Ł' => folded.push('L'),
+            'ı' => folded.push('i'),
+            'þ' => folded.push_str("th"),
+            'Þ' => folded.push_str("TH"),
+            _ => folded.push(c),
+        }
+    }
+
+    folded.nfc().collect()
+}
+
+/// Splits text into sentences, keeping the terminating punctuation.
+/// A sentence ends at `.`, `!` or `?` (and any closing quotes or brackets) followed
+/// by whitespace, except after common abbreviations (`Dr.`, `St.`), initials
+/// (`J. R. R. Tolkien`) and before a lowercase word or number (`e.g. the`, `c. 1500`)
+///
+/// # Arguments
+///
+/// * `text` - the text to split
+///
+/// # Returns
+///
+/// The trimmed sentences, as slices of `text`
+pub fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if !matches!(c, '.' | '!' | '?') {
+            continue;
+        }
+
+        let mut end = i + c.len_utf8();
+        while let Some(&(j, next)) = chars.peek() {
+            if !matches!(
+                next,
+                '.' | '!' | '?' | ')' | ']' | '"' | '\'' | '”' | '’' | '»'
+            ) {
+                break;
+            }
+            end = j + next.len_utf8();
+            chars.next();
+        }
+
+        let next_is_space = chars.peek().is_none_or(|(_, next)| next.is_whitespace());
+        if !next_is_space {
+            continue;
+        }
+
+        if c == '.' && !ends_sentence(&text[start..i], text[end..].trim_start()) {
+            continue;
+        }
+
+        let sentence = text[start..end].trim();
+        if !sentence.is_empty() {
+            sentences.push(sentence);
+        }
+        start = end;
+    }
+
+    let rest = text[start..].trim();
+    if !rest.is_empty() {
+        sentences.push(rest);
+    }
+
+    sentences
+}
+
+// abbreviations that are almost never the end of a sentence
+const ABBREVIATIONS: [&str; 24] = [
+    "mr", "mrs", "ms", "dr", "prof", "st", "jr", "sr", "gen", "col", "lt", "sgt", "capt", "rev",
+    "hon", "mt", "ft", "vol", "vs", "approx", "fig", "ca", "eg", "ie",
+];
+
+/// Whether a period ends a sentence, given the text before it and after it
+fn ends_sentence(before: &str, after: &str) -> bool {
+    let word = before
+        .rsplit(|c: char| c.is_whitespace() || c == '(')
+        .next()
+        .unwrap_or_default();
+
+    // initials, ie the "R" of "J. R. R. Tolkien"
+    let mut letters = word.chars();
+    if let (Some(letter), None) = (letters.next(), letters.next()) {
+        if letter.is_uppercase() {
+            return false;
+        }
+    }
+
+    // dotted abbreviations are matched without their inner dots, "e.g" -> "eg"
+    let word = word.replace('.', "").to_lowercase();
+    if ABBREVIATIONS.contains(&word.as_str()) {
+        return false;
+    }
+
+    // "e.g. the", "approx. 5 km": a sentence never starts lowercase or with a digit
+    match after.chars().next() {
+        Some(next) => !(next.is_lowercase() || next.is_ascii_digit()),
+        None => true,
+    }
+}
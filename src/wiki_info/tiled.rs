@@ -0,0 +1,354 @@
+use std::{
+    collections::HashMap,
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use log::debug;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    build_vocab,
+    cluster::sparse_dot,
+    hashing::{fnv1a, Fnv1aHasher},
+    prepare_document,
+    tokenize::SharedTokenizer,
+    CleanOptions, LengthNormalization, Page, VectorOptions, WikiError,
+};
+
+const MANIFEST: &str = "manifest.json";
+
+/// Describes a checkpointed matrix, written next to its tiles
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Manifest {
+    tile_size: usize,
+    titles: Vec<String>,
+    /// See `options_fingerprint`
+    #[serde(default)]
+    options: u64,
+    /// fnv1a of each page's content, in `titles` order
+    #[serde(default)]
+    contents: Vec<u64>,
+    /// Whether the pages were tokenized by anything but the default tokenizer
+    #[serde(default)]
+    custom_tokenizer: bool,
+}
+
+/// A pairwise cosine similarity matrix stored on disk as square tiles.
+/// Tiles are written one at a time as they are computed, so a long computation
+/// can be killed and resumed, and the full matrix never has to fit in memory.
+/// Only tiles on or above the diagonal are stored, the matrix is symmetric
+#[derive(Debug, Clone)]
+pub struct TiledSimilarityMatrix {
+    dir: PathBuf,
+    size: usize,
+    tile_size: usize,
+}
+
+/// A page vector holding only its non zero (term id, weight) entries,
+/// already scaled to unit length
+type SparseVec = Vec<(usize, f64)>;
+
+impl TiledSimilarityMatrix {
+    /// Computes (or resumes computing) the similarity matrix of a page set into `dir`.
+    /// Tiles already present in `dir` are not recomputed
+    ///
+    /// # Arguments
+    ///
+    /// * `pages` - The pages to compare, every page against every page
+    /// * `options` - How the pages are vectorized, scores are always plain cosine
+    /// * `tile_size` - Rows and columns per tile, must not be 0
+    /// * `dir` - Checkpoint directory, created if missing
+    ///
+    /// # Returns
+    ///
+    /// Ok(TiledSimilarityMatrix) - handle to the finished matrix
+    /// Err(WikiError::IOError) - error if the checkpoint can not be written
+    /// Err(WikiError::InputError) - error if the checkpoint belongs to a different
+    /// tile size, page set, page contents or vector options. Custom tokenizers can not
+    /// be compared, a checkpoint computed with one is never resumed
+    pub fn compute<P: AsRef<Path>>(
+        pages: &[Page],
+        options: &VectorOptions,
+        tile_size: usize,
+        dir: P,
+    ) -> Result<Self, WikiError> {
+        assert!(tile_size > 0, "tile_size must not be 0");
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(io_error)?;
+
+        let manifest = Manifest {
+            tile_size,
+            titles: pages.iter().map(|page| page.title.clone()).collect(),
+            options: options_fingerprint(options),
+            contents: pages
+                .iter()
+                .map(|page| fnv1a(page.content.as_bytes()))
+                .collect(),
+            custom_tokenizer: options.clean.tokenizer != SharedTokenizer::default(),
+        };
+        check_manifest(&dir, &manifest)?;
+
+        let matrix = TiledSimilarityMatrix {
+            dir,
+            size: pages.len(),
+            tile_size,
+        };
+
+        let vocab = build_vocab(pages.iter(), options);
+        let vectors: Vec<SparseVec> = pages
+            .par_iter()
+            .map(|page| sparse_unit_vec(page, &vocab, options))
+            .collect();
+
+        let tiles = matrix.tiles_per_side();
+        for tile_row in 0..tiles {
+            for tile_col in tile_row..tiles {
+                let path = matrix.tile_path(tile_row, tile_col);
+                if path.exists() {
+                    debug!(
+                        "Tile ({}, {}) already computed, skipping",
+                        tile_row, tile_col
+                    );
+                    continue;
+                }
+
+                let tile = matrix.compute_tile(&vectors, tile_row, tile_col);
+                write_tile(&path, &tile)?;
+            }
+        }
+
+        Ok(matrix)
+    }
+
+    /// Opens a finished matrix from its checkpoint directory
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Checkpoint directory passed to `compute`
+    ///
+    /// # Returns
+    ///
+    /// Ok(TiledSimilarityMatrix) - handle to the matrix
+    /// Err(WikiError::IOError) - error if there is no readable manifest in `dir`
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, WikiError> {
+        let dir = dir.as_ref().to_path_buf();
+        let manifest = read_manifest(&dir)?
+            .ok_or_else(|| WikiError::IOError(format!("No matrix manifest in {:?}", dir)))?;
+
+        Ok(TiledSimilarityMatrix {
+            dir,
+            size: manifest.titles.len(),
+            tile_size: manifest.tile_size,
+        })
+    }
+
+    /// Number of rows (and columns) of the matrix
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Rows and columns per tile
+    pub fn tile_size(&self) -> usize {
+        self.tile_size
+    }
+
+    /// Gets the similarity of pages `i` and `j`, reading its tile from disk
+    ///
+    /// # Returns
+    ///
+    /// Ok(f64) - the cosine similarity of the pages
+    /// Err(WikiError::IOError) - error if the tile can not be read
+    pub fn get(&self, i: usize, j: usize) -> Result<f64, WikiError> {
+        assert!(i < self.size && j < self.size, "matrix index out of bounds");
+        let (i, j) = if i <= j { (i, j) } else { (j, i) };
+
+        let (tile_row, tile_col) = (i / self.tile_size, j / self.tile_size);
+        let tile = self.read_tile(tile_row, tile_col)?;
+        let cols = self.tile_extent(tile_col);
+
+        Ok(tile[(i % self.tile_size) * cols + j % self.tile_size])
+    }
+
+    /// Reads a whole stored tile, row major. Only tiles with `tile_row <= tile_col` exist
+    ///
+    /// # Returns
+    ///
+    /// Ok(Vec<f64>) - the tile values
+    /// Err(WikiError::IOError) - error if the tile can not be read
+    pub fn read_tile(&self, tile_row: usize, tile_col: usize) -> Result<Vec<f64>, WikiError> {
+        let bytes = fs::read(self.tile_path(tile_row, tile_col)).map_err(io_error)?;
+
+        Ok(bytes
+            .chunks_exact(8)
+            .map(|chunk| f64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect())
+    }
+
+    fn tiles_per_side(&self) -> usize {
+        self.size.div_ceil(self.tile_size)
+    }
+
+    // the last tile of a row or column can be short
+    fn tile_extent(&self, tile: usize) -> usize {
+        self.tile_size.min(self.size - tile * self.tile_size)
+    }
+
+    fn tile_path(&self, tile_row: usize, tile_col: usize) -> PathBuf {
+        self.dir.join(format!("tile_{}_{}.bin", tile_row, tile_col))
+    }
+
+    fn compute_tile(&self, vectors: &[SparseVec], tile_row: usize, tile_col: usize) -> Vec<f64> {
+        let row_start = tile_row * self.tile_size;
+        let col_start = tile_col * self.tile_size;
+        let cols = self.tile_extent(tile_col);
+
+        (0..self.tile_extent(tile_row))
+            .into_par_iter()
+            .flat_map_iter(|row| {
+                let a = &vectors[row_start + row];
+                (0..cols).map(move |col| sparse_dot(a, &vectors[col_start + col]))
+            })
+            .collect()
+    }
+}
+
+fn io_error(err: std::io::Error) -> WikiError {
+    WikiError::IOError(err.to_string())
+}
+
+fn read_manifest(dir: &Path) -> Result<Option<Manifest>, WikiError> {
+    let path = dir.join(MANIFEST);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let bytes = fs::read(path).map_err(io_error)?;
+    serde_json::from_slice(&bytes)
+        .map(Some)
+        .map_err(|err| WikiError::IOError(format!("Invalid matrix manifest: {}", err)))
+}
+
+// Every option is hashed explicitly, destructured so a new option can not be missed.
+// Tokenizers are opaque and are left out, see `check_manifest`
+fn options_fingerprint(options: &VectorOptions) -> u64 {
+    let VectorOptions {
+        max_words,
+        token_cap,
+        length_normalization,
+        clean,
+    } = options;
+    let CleanOptions {
+        tokenizer: _,
+        ascii_only,
+        fold_diacritics,
+        keep_numbers,
+        punctuation,
+        min_token_length,
+        lowercase,
+        remove_stop_words,
+        stop_words,
+        expand_acronyms,
+        stemmer,
+        #[cfg(feature = "lemmatizer")]
+        lemmatizer,
+    } = clean;
+
+    let mut hasher = Fnv1aHasher::default();
+    (max_words, token_cap).hash(&mut hasher);
+    match length_normalization {
+        LengthNormalization::Cosine => 0u8.hash(&mut hasher),
+        LengthNormalization::Pivoted { slope, pivot } => {
+            (1u8, slope.to_bits(), pivot.map(f64::to_bits)).hash(&mut hasher)
+        }
+    }
+    (
+        ascii_only,
+        fold_diacritics,
+        keep_numbers,
+        punctuation,
+        min_token_length,
+        lowercase,
+        remove_stop_words,
+        stop_words,
+        expand_acronyms,
+        stemmer,
+    )
+        .hash(&mut hasher);
+    #[cfg(feature = "lemmatizer")]
+    lemmatizer.hash(&mut hasher);
+
+    hasher.finish()
+}
+
+// a resumed run must be for the exact same pages and options, or the tiles are meaningless
+fn check_manifest(dir: &Path, manifest: &Manifest) -> Result<(), WikiError> {
+    match read_manifest(dir)? {
+        Some(existing) if existing.custom_tokenizer || manifest.custom_tokenizer => {
+            Err(WikiError::InputError(format!(
+                "Checkpoint in {:?} can not be resumed with a custom tokenizer",
+                dir
+            )))
+        }
+        Some(existing) if existing == *manifest => Ok(()),
+        Some(existing) => {
+            let differs = if existing.tile_size != manifest.tile_size {
+                "tile size"
+            } else if existing.titles != manifest.titles {
+                "page set"
+            } else if existing.contents != manifest.contents {
+                "page contents"
+            } else {
+                "vector options"
+            };
+
+            Err(WikiError::InputError(format!(
+                "Checkpoint in {:?} was computed with a different {}",
+                dir, differs
+            )))
+        }
+        None => {
+            let json =
+                serde_json::to_vec(manifest).map_err(|err| WikiError::IOError(err.to_string()))?;
+            fs::write(dir.join(MANIFEST), json).map_err(io_error)
+        }
+    }
+}
+
+// write then rename, so a killed run never leaves a half written tile behind
+fn write_tile(path: &Path, tile: &[f64]) -> Result<(), WikiError> {
+    let bytes: Vec<u8> = tile.iter().flat_map(|value| value.to_le_bytes()).collect();
+    let partial = path.with_extension("partial");
+
+    fs::write(&partial, bytes).map_err(io_error)?;
+    fs::rename(&partial, path).map_err(io_error)
+}
+
+fn sparse_unit_vec(
+    page: &Page,
+    vocab: &HashMap<String, usize>,
+    options: &VectorOptions,
+) -> SparseVec {
+    let mut counts: HashMap<usize, f64> = HashMap::new();
+    for word in prepare_document(page, options).content.split_whitespace() {
+        if let Some(&id) = vocab.get(word) {
+            *counts.entry(id).or_insert(0.0) += 1.0;
+        }
+    }
+
+    let magnitude = counts
+        .values()
+        .map(|count| count * count)
+        .sum::<f64>()
+        .sqrt();
+
+    let mut vector: SparseVec = counts
+        .into_iter()
+        .map(|(id, count)| (id, count / magnitude))
+        .collect();
+    vector.sort_by_key(|(id, _)| *id);
+
+    vector
+}
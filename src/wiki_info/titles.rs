@@ -0,0 +1,143 @@
+use super::text::fold_diacritics;
+
+// common prefix length Jaro-Winkler boosts, and how much each character counts
+const WINKLER_PREFIX: usize = 4;
+const WINKLER_SCALE: f64 = 0.1;
+
+/// The normalized Levenshtein distance between two titles: the number of character
+/// edits between them over the length of the longer one. Titles are compared
+/// ignoring case, accents, underscores and repeated whitespace, so
+/// `"besancon"` and `"Besançon"` are the same title
+///
+/// # Arguments
+///
+/// * `a` - The first title
+/// * `b` - The second title
+///
+/// # Returns
+///
+/// The distance [0-1], 0 for the same title
+pub fn title_distance(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = normalize_title(a).chars().collect();
+    let b: Vec<char> = normalize_title(b).chars().collect();
+
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 0.0;
+    }
+
+    levenshtein(&a, &b) as f64 / longest as f64
+}
+
+/// The Jaro-Winkler similarity of two titles, normalized like `title_distance`.
+/// Favours titles sharing a prefix, so it suits truncated or abbreviated
+/// names (`"Univ of Oxford"`) better than `title_distance`
+///
+/// # Arguments
+///
+/// * `a` - The first title
+/// * `b` - The second title
+///
+/// # Returns
+///
+/// The similarity [0-1], 1 for the same title
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = normalize_title(a).chars().collect();
+    let b: Vec<char> = normalize_title(b).chars().collect();
+
+    let jaro = jaro(&a, &b);
+    let prefix = a
+        .iter()
+        .zip(&b)
+        .take(WINKLER_PREFIX)
+        .take_while(|(x, y)| x == y)
+        .count();
+
+    jaro + prefix as f64 * WINKLER_SCALE * (1.0 - jaro)
+}
+
+/// Finds the title closest to a user entered name, ie to reconcile names with
+/// article titles before fetching
+///
+/// # Arguments
+///
+/// * `query` - The name to look up
+/// * `titles` - The candidate titles
+///
+/// # Returns
+///
+/// (index into `titles`, its `title_distance`) of the closest title, the first
+/// on ties. None if there are no titles
+pub fn closest_title(query: &str, titles: &[String]) -> Option<(usize, f64)> {
+    titles
+        .iter()
+        .map(|title| title_distance(query, title))
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+// lowercase, accents folded and whitespace collapsed, the form titles are compared in
+pub(crate) fn normalize_title(title: &str) -> String {
+    fold_diacritics(&title.replace('_', " "))
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    // a single row of the edit distance table
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, x) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, y) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(x != y);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+fn jaro(a: &[char], b: &[char]) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    // characters match if equal and no further apart than this
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut b_matched = vec![false; b.len()];
+    let mut a_matches = Vec::new();
+
+    for (i, x) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+
+        if let Some(j) = (start..end).find(|&j| !b_matched[j] && b[j] == *x) {
+            b_matched[j] = true;
+            a_matches.push(*x);
+        }
+    }
+
+    if a_matches.is_empty() {
+        return 0.0;
+    }
+
+    let b_matches = b.iter().zip(&b_matched).filter(|(_, &m)| m).map(|(y, _)| y);
+    let transpositions = a_matches
+        .iter()
+        .zip(b_matches)
+        .filter(|(x, y)| x != y)
+        .count()
+        / 2;
+
+    let m = a_matches.len() as f64;
+    (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0
+}
@@ -0,0 +1,123 @@
+use std::{fmt, ops::Deref, sync::Arc};
+
+use lazy_static::lazy_static;
+use unicode_segmentation::UnicodeSegmentation;
+
+lazy_static! {
+    static ref DEFAULT_TOKENIZER: Arc<dyn Tokenizer> = Arc::new(UnicodeTokenizer);
+}
+
+/// A single token of a text
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Token {
+    pub text: String,
+    /// Byte offset of the token in the tokenized text
+    pub offset: usize,
+}
+
+/// Splits text into tokens before cleaning, see `CleanOptions::tokenizer`.
+/// Cleaned documents are stored as space separated tokens, so tokens
+/// should not contain whitespace
+pub trait Tokenizer: Send + Sync {
+    /// Tokenize a text
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - the text to split
+    ///
+    /// # Returns
+    ///
+    /// The tokens in text order
+    fn tokenize(&self, text: &str) -> Vec<Token>;
+}
+
+/// Any closure from text to tokens is a tokenizer
+impl<F> Tokenizer for F
+where
+    F: Fn(&str) -> Vec<Token> + Send + Sync,
+{
+    fn tokenize(&self, text: &str) -> Vec<Token> {
+        self(text)
+    }
+}
+
+/// The default tokenizer: Unicode words (UAX #29). Splits on hyphens but keeps
+/// apostrophes and decimal points inside words (`"don't"`, `"3.14"`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UnicodeTokenizer;
+
+impl Tokenizer for UnicodeTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<Token> {
+        // unicode word boundaries also split CJK text, which has no spaces, into words
+        text.unicode_word_indices()
+            .map(|(offset, word)| Token {
+                text: word.to_owned(),
+                offset,
+            })
+            .collect()
+    }
+}
+
+/// Splits on whitespace only, trimming punctuation around each token.
+/// Keeps tokens like chemical formulas (`"C6H12O6"`) and hyphenated words intact
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WhitespaceTokenizer;
+
+impl Tokenizer for WhitespaceTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<Token> {
+        text.split_whitespace()
+            .filter_map(|word| {
+                let trimmed = word.trim_matches(|c: char| !c.is_alphanumeric());
+                if trimmed.is_empty() {
+                    return None;
+                }
+
+                // both are slices of text, so the offset is their distance
+                let offset = trimmed.as_ptr() as usize - text.as_ptr() as usize;
+
+                Some(Token {
+                    text: trimmed.to_owned(),
+                    offset,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A cloneable handle to a tokenizer, shared between clones of the options
+/// holding it. Handles are equal if they point to the same tokenizer
+#[derive(Clone)]
+pub struct SharedTokenizer(Arc<dyn Tokenizer>);
+
+impl SharedTokenizer {
+    /// Wraps a tokenizer
+    pub fn new<T: Tokenizer + 'static>(tokenizer: T) -> Self {
+        SharedTokenizer(Arc::new(tokenizer))
+    }
+}
+
+impl Default for SharedTokenizer {
+    fn default() -> Self {
+        SharedTokenizer(DEFAULT_TOKENIZER.clone())
+    }
+}
+
+impl Deref for SharedTokenizer {
+    type Target = dyn Tokenizer;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+impl PartialEq for SharedTokenizer {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for SharedTokenizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SharedTokenizer")
+    }
+}
@@ -0,0 +1,252 @@
+use log::debug;
+
+use super::{corpus::Corpus, hashing::splitmix64, matrix::Matrix};
+
+/// Settings of `lda_with`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LdaOptions {
+    /// Number of topics
+    pub topics: usize,
+    /// Number of Gibbs sampling sweeps over the corpus
+    pub iterations: usize,
+    /// Document-topic prior, smaller values give pages fewer topics
+    pub alpha: f64,
+    /// Topic-word prior, smaller values give topics fewer words
+    pub beta: f64,
+    /// Seed of the sampler, the same seed and corpus always give the same model
+    pub seed: u64,
+}
+
+impl Default for LdaOptions {
+    fn default() -> Self {
+        LdaOptions {
+            topics: 10,
+            iterations: 200,
+            alpha: 0.1,
+            beta: 0.01,
+            seed: 0,
+        }
+    }
+}
+
+/// A fitted LDA topic model
+#[derive(Debug, Clone, PartialEq)]
+pub struct LdaModel {
+    /// Word distribution of every topic, one row per topic, one column per corpus term
+    pub topic_words: Matrix<f64>,
+    /// Topic mixture of every document, one row per document id
+    pub document_topics: Matrix<f64>,
+    terms: Vec<String>,
+}
+
+impl LdaModel {
+    /// Number of topics
+    pub fn topics(&self) -> usize {
+        self.topic_words.rows()
+    }
+
+    /// The most probable words of a topic, ie to label it
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The topic
+    /// * `n` - The maximum number of words
+    ///
+    /// # Returns
+    ///
+    /// Up to `n` (word, probability) pairs, most probable first
+    pub fn top_words(&self, topic: usize, n: usize) -> Vec<(String, f64)> {
+        let mut words: Vec<(usize, f64)> = self
+            .topic_words
+            .row(topic)
+            .iter()
+            .copied()
+            .enumerate()
+            .collect();
+        words.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        words
+            .into_iter()
+            .take(n)
+            .map(|(term, probability)| (self.terms[term].clone(), probability))
+            .collect()
+    }
+
+    /// The topic mixture of a document of the corpus
+    pub fn document_mixture(&self, doc: usize) -> &[f64] {
+        self.document_topics.row(doc)
+    }
+
+    /// The most probable topic of a document of the corpus
+    pub fn dominant_topic(&self, doc: usize) -> usize {
+        self.document_mixture(doc)
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map_or(0, |(topic, _)| topic)
+    }
+
+    /// How alike the topic mixtures of two documents of the corpus are, see `topic_similarity`
+    pub fn document_similarity(&self, a: usize, b: usize) -> f64 {
+        topic_similarity(self.document_mixture(a), self.document_mixture(b))
+    }
+}
+
+/// Fits an LDA topic model to a corpus with collapsed Gibbs sampling,
+/// with the default priors, see `LdaOptions`
+///
+/// # Arguments
+///
+/// * `corpus` - The pages to model
+/// * `n_topics` - Number of topics
+/// * `iterations` - Number of sampling sweeps, a few hundred is usually enough
+///
+/// # Returns
+///
+/// The topic-word distributions and the topic mixture of every document
+pub fn lda(corpus: &Corpus, n_topics: usize, iterations: usize) -> LdaModel {
+    lda_with(
+        corpus,
+        &LdaOptions {
+            topics: n_topics,
+            iterations,
+            ..Default::default()
+        },
+    )
+}
+
+/// Fits an LDA topic model to a corpus with collapsed Gibbs sampling
+///
+/// # Arguments
+///
+/// * `corpus` - The pages to model
+/// * `options` - Topics, iterations, priors and seed, `topics` must not be 0
+///
+/// # Returns
+///
+/// The topic-word distributions and the topic mixture of every document
+pub fn lda_with(corpus: &Corpus, options: &LdaOptions) -> LdaModel {
+    assert!(options.topics > 0, "LDA needs at least one topic");
+    debug!(
+        "Fitting LDA with {} topics over {} documents",
+        options.topics,
+        corpus.len()
+    );
+
+    let topics = options.topics;
+    let vocabulary = corpus.terms().len();
+    let mut random = options.seed;
+    let mut next_random = || {
+        random = splitmix64(random);
+        (random >> 11) as f64 / (1u64 << 53) as f64
+    };
+
+    // every occurrence of every term, order does not matter to LDA
+    let tokens: Vec<Vec<usize>> = corpus
+        .documents()
+        .iter()
+        .map(|doc| {
+            doc.term_counts
+                .iter()
+                .flat_map(|&(term, count)| std::iter::repeat_n(term, count))
+                .collect()
+        })
+        .collect();
+
+    let mut document_counts = Matrix::filled(tokens.len(), topics, 0usize);
+    let mut topic_term_counts = Matrix::filled(topics, vocabulary, 0usize);
+    let mut topic_counts = vec![0usize; topics];
+
+    let mut assignments: Vec<Vec<usize>> = tokens
+        .iter()
+        .enumerate()
+        .map(|(doc, terms)| {
+            terms
+                .iter()
+                .map(|&term| {
+                    let topic = ((next_random() * topics as f64) as usize).min(topics - 1);
+                    document_counts[(doc, topic)] += 1;
+                    topic_term_counts[(topic, term)] += 1;
+                    topic_counts[topic] += 1;
+                    topic
+                })
+                .collect()
+        })
+        .collect();
+
+    let beta_sum = options.beta * vocabulary as f64;
+    let mut weights = vec![0.0; topics];
+
+    for _ in 0..options.iterations {
+        for (doc, terms) in tokens.iter().enumerate() {
+            for (position, &term) in terms.iter().enumerate() {
+                let old = assignments[doc][position];
+                document_counts[(doc, old)] -= 1;
+                topic_term_counts[(old, term)] -= 1;
+                topic_counts[old] -= 1;
+
+                let mut total = 0.0;
+                for (topic, weight) in weights.iter_mut().enumerate() {
+                    *weight = (document_counts[(doc, topic)] as f64 + options.alpha)
+                        * (topic_term_counts[(topic, term)] as f64 + options.beta)
+                        / (topic_counts[topic] as f64 + beta_sum);
+                    total += *weight;
+                }
+
+                let mut target = next_random() * total;
+                let mut new = topics - 1;
+                for (topic, &weight) in weights.iter().enumerate() {
+                    target -= weight;
+                    if target <= 0.0 {
+                        new = topic;
+                        break;
+                    }
+                }
+
+                assignments[doc][position] = new;
+                document_counts[(doc, new)] += 1;
+                topic_term_counts[(new, term)] += 1;
+                topic_counts[new] += 1;
+            }
+        }
+    }
+
+    let mut topic_words = Matrix::filled(topics, vocabulary, 0.0);
+    for topic in 0..topics {
+        for term in 0..vocabulary {
+            topic_words[(topic, term)] = (topic_term_counts[(topic, term)] as f64 + options.beta)
+                / (topic_counts[topic] as f64 + beta_sum);
+        }
+    }
+
+    let alpha_sum = options.alpha * topics as f64;
+    let mut document_topics = Matrix::filled(tokens.len(), topics, 0.0);
+    for (doc, terms) in tokens.iter().enumerate() {
+        for topic in 0..topics {
+            document_topics[(doc, topic)] = (document_counts[(doc, topic)] as f64 + options.alpha)
+                / (terms.len() as f64 + alpha_sum);
+        }
+    }
+
+    LdaModel {
+        topic_words,
+        document_topics,
+        terms: corpus.terms().to_vec(),
+    }
+}
+
+/// How alike two topic mixtures are, one minus their Hellinger distance
+///
+/// # Arguments
+///
+/// * `a` - The first topic mixture
+/// * `b` - The second topic mixture, over the same topics
+///
+/// # Returns
+///
+/// The similarity [0-1], 1 for identical mixtures
+pub fn topic_similarity(a: &[f64], b: &[f64]) -> f64 {
+    let affinity: f64 = a.iter().zip(b).map(|(p, q)| (p * q).sqrt()).sum();
+
+    1.0 - (1.0 - affinity.min(1.0)).sqrt()
+}
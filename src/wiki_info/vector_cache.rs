@@ -0,0 +1,198 @@
+use std::{collections::HashMap, sync::Arc};
+
+use super::{
+    clean_document_with, count_terms, hashing, prepare_document, score_vectors, term_frequencies,
+    vocabulary::Vocabulary, CleanOptions, Page, VectorOptions,
+};
+
+// pages are identified by title and a hash of their content,
+// so an edited page is cleaned again
+type PageKey = (String, u64);
+
+/// Caches page vectors for repeated similarity queries against a mostly static pool
+/// of pages. Cleaned term counts are kept per page and vectors per (page, vocabulary
+/// version), so neither cleaning nor vectorizing runs twice for the same page.
+/// When the vocabulary grows only the projection onto the new vocabulary is redone
+#[derive(Debug, Clone, Default)]
+pub struct VectorCache {
+    options: VectorOptions,
+    counts: HashMap<PageKey, Arc<HashMap<String, usize>>>,
+    vectors: HashMap<(PageKey, u64), Arc<[f64]>>,
+    hits: usize,
+    misses: usize,
+}
+
+impl VectorCache {
+    /// Creates an empty cache
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - How pages are vectorized, should match the options the
+    ///   vocabularies were built with
+    pub fn new(options: VectorOptions) -> Self {
+        VectorCache {
+            options,
+            ..Default::default()
+        }
+    }
+
+    /// Gets the vector of a page over a vocabulary, see `Vocabulary::vectorize`
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page to vectorize
+    /// * `vocabulary` - The vocabulary to vectorize over
+    ///
+    /// # Returns
+    ///
+    /// The cached vector, computed first if it was not cached
+    pub fn vector(&mut self, page: &Page, vocabulary: &Vocabulary) -> Arc<[f64]> {
+        let page_key = page_key(page);
+        let key = (page_key, vocabulary.version());
+
+        if let Some(vector) = self.vectors.get(&key) {
+            self.hits += 1;
+            return vector.clone();
+        }
+        self.misses += 1;
+
+        let options = &self.options;
+        let counts = self
+            .counts
+            .entry(key.0.clone())
+            .or_insert_with(|| Arc::new(count_terms(&prepare_document(page, options).content)));
+
+        let vector: Arc<[f64]> = term_frequencies(counts, vocabulary.as_map()).into();
+        self.vectors.insert(key, vector.clone());
+
+        vector
+    }
+
+    /// Scores a page against candidate pages with cached vectors, the same
+    /// scores as `get_top_k_similar_with` gives over the same vocabulary
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page to compare against
+    /// * `candidates` - The pages to score
+    /// * `vocabulary` - The vocabulary to vectorize over, usually frozen over the pool
+    ///
+    /// # Returns
+    ///
+    /// One score per candidate, in candidate order
+    pub fn similarities(
+        &mut self,
+        page: &Page,
+        candidates: &[Page],
+        vocabulary: &Vocabulary,
+    ) -> Vec<f64> {
+        let primary = self.vector(page, vocabulary);
+        let vectors: Vec<Arc<[f64]>> = candidates
+            .iter()
+            .map(|candidate| self.vector(candidate, vocabulary))
+            .collect();
+
+        score_vectors(&primary, &vectors, self.options.length_normalization)
+            .into_iter()
+            .map(|sim| if sim.is_nan() { 0.0 } else { sim })
+            .collect()
+    }
+
+    /// Drops the vectors of every other vocabulary version, ie after the
+    /// vocabulary grew. Cleaned term counts are kept
+    pub fn retain_version(&mut self, version: u64) {
+        self.vectors.retain(|(_, v), _| *v == version);
+    }
+
+    /// Drops everything cached
+    pub fn clear(&mut self) {
+        self.counts.clear();
+        self.vectors.clear();
+    }
+
+    /// Number of cached vectors
+    pub fn len(&self) -> usize {
+        self.vectors.len()
+    }
+
+    /// Whether no vectors are cached
+    pub fn is_empty(&self) -> bool {
+        self.vectors.is_empty()
+    }
+
+    /// Number of vectors served from the cache
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Number of vectors that had to be computed
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// The options pages are vectorized with
+    pub fn options(&self) -> &VectorOptions {
+        &self.options
+    }
+}
+
+/// Keeps the cleaned content of pages, see `clean_document_with`, so display code
+/// can show `Page::content` while similarity code reads the cleaned form, without
+/// cleaning a page twice or keeping two copies of it. Pages are keyed like in
+/// `VectorCache`, so an edited page is cleaned again
+#[derive(Debug, Clone, Default)]
+pub struct CleanCache {
+    options: CleanOptions,
+    cleaned: HashMap<PageKey, Arc<str>>,
+}
+
+impl CleanCache {
+    /// Creates an empty cache
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - How pages are cleaned, the default matches `clean_document`
+    pub fn new(options: CleanOptions) -> Self {
+        CleanCache {
+            options,
+            cleaned: HashMap::new(),
+        }
+    }
+
+    /// Gets the cleaned content of a page
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page to clean
+    ///
+    /// # Returns
+    ///
+    /// The cached cleaned content, computed first if it was not cached
+    pub fn content_clean(&mut self, page: &Page) -> Arc<str> {
+        let options = &self.options;
+
+        self.cleaned
+            .entry(page_key(page))
+            .or_insert_with(|| clean_document_with(page, options).content.into())
+            .clone()
+    }
+
+    /// Drops everything cached
+    pub fn clear(&mut self) {
+        self.cleaned.clear();
+    }
+
+    /// Number of cached pages
+    pub fn len(&self) -> usize {
+        self.cleaned.len()
+    }
+
+    /// Whether no pages are cached
+    pub fn is_empty(&self) -> bool {
+        self.cleaned.is_empty()
+    }
+}
+
+fn page_key(page: &Page) -> PageKey {
+    (page.title.clone(), hashing::fnv1a(page.content.as_bytes()))
+}
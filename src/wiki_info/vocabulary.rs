@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use super::{
+    hashing, page_to_vec_f32_with, page_to_vec_with, prepare_document, Page, VectorOptions,
+};
+
+/// A vocabulary of cleaned terms with stable ids: terms are only ever appended,
+/// so a term keeps its id as the vocabulary grows. Vectors computed before the
+/// vocabulary grew stay comparable with `cosine_sim`, they are just shorter.
+/// A frozen vocabulary no longer grows, new terms are ignored
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Vocabulary {
+    ids: HashMap<String, usize>,
+    terms: Vec<String>,
+    frozen: bool,
+    // fingerprint of the terms in id order, see `version`
+    version: u64,
+}
+
+impl Vocabulary {
+    /// Creates an empty, growable vocabulary
+    pub fn new() -> Self {
+        Vocabulary::default()
+    }
+
+    /// Builds a vocabulary over a set of pages, ids in order of first appearance
+    ///
+    /// # Arguments
+    ///
+    /// * `pages` - The pages to take terms from
+    /// * `options` - How the pages are cleaned
+    ///
+    /// # Returns
+    ///
+    /// The new, growable vocabulary
+    pub fn from_pages<'a>(
+        pages: impl IntoIterator<Item = &'a Page>,
+        options: &VectorOptions,
+    ) -> Self {
+        let mut vocabulary = Vocabulary::new();
+        vocabulary.extend(pages, options);
+
+        vocabulary
+    }
+
+    /// Adds the terms of more pages, unless the vocabulary is frozen
+    pub fn extend<'a>(
+        &mut self,
+        pages: impl IntoIterator<Item = &'a Page>,
+        options: &VectorOptions,
+    ) {
+        for page in pages {
+            for word in prepare_document(page, options).content.split_whitespace() {
+                self.add_term(word);
+            }
+        }
+    }
+
+    /// Adds a single cleaned term
+    ///
+    /// # Returns
+    ///
+    /// The id of the term, None if it is new and the vocabulary is frozen
+    pub fn add_term(&mut self, term: &str) -> Option<usize> {
+        if let Some(&id) = self.ids.get(term) {
+            return Some(id);
+        }
+        if self.frozen {
+            return None;
+        }
+
+        let id = self.terms.len();
+        self.ids.insert(term.to_owned(), id);
+        self.terms.push(term.to_owned());
+        self.version = hashing::splitmix64(self.version ^ hashing::fnv1a(term.as_bytes()));
+
+        Some(id)
+    }
+
+    /// Stops the vocabulary from growing, ie once the candidate pool is known
+    pub fn freeze(&mut self) {
+        self.frozen = true;
+    }
+
+    /// Lets the vocabulary grow again
+    pub fn unfreeze(&mut self) {
+        self.frozen = false;
+    }
+
+    /// Whether new terms are ignored
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Identifies the exact terms of the vocabulary, changes whenever a term is added.
+    /// Vocabularies with the same version produce the same vectors, see `VectorCache`
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Gets the id of a cleaned term
+    pub fn id(&self, term: &str) -> Option<usize> {
+        self.ids.get(term).copied()
+    }
+
+    /// Gets the term with an id
+    pub fn term(&self, id: usize) -> Option<&str> {
+        self.terms.get(id).map(String::as_str)
+    }
+
+    /// Every term, indexed by id
+    pub fn terms(&self) -> &[String] {
+        &self.terms
+    }
+
+    /// Number of terms
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Whether there are no terms
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// The term to id map, as taken by `page_to_vec_with`
+    pub fn as_map(&self) -> &HashMap<String, usize> {
+        &self.ids
+    }
+
+    /// Takes the term to id map out
+    pub fn into_map(self) -> HashMap<String, usize> {
+        self.ids
+    }
+
+    /// Convert a Page into its term frequency vector over this vocabulary,
+    /// see `page_to_vec_with`. Terms outside the vocabulary are ignored
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page to convert
+    /// * `options` - How the page is cleaned, should match the options the
+    ///   vocabulary was built with
+    ///
+    /// # Returns
+    ///
+    /// A vector with one term frequency per vocabulary term
+    pub fn vectorize(&self, page: &Page, options: &VectorOptions) -> Vec<f64> {
+        page_to_vec_with(page, &self.ids, options)
+    }
+
+    /// Convert a Page into its single precision term frequency vector, see `vectorize`
+    pub fn vectorize_f32(&self, page: &Page, options: &VectorOptions) -> Vec<f32> {
+        page_to_vec_f32_with(page, &self.ids, options)
+    }
+}
@@ -0,0 +1,411 @@
+use lazy_static::lazy_static;
+use log::debug;
+use regex::Regex;
+use reqwest::blocking::{Client, RequestBuilder};
+
+use super::{
+    clean_meta_content, client, dedup_links, handle_response, text::normalize_text, Coordinates,
+    HyperLink, Namespace, Page, WikiError,
+};
+
+lazy_static! {
+    static ref COMMENT: Regex = Regex::new(r"(?s)<!--.*?-->").unwrap();
+    static ref REF: Regex = Regex::new(r"(?s)<ref[^>]*/>|<ref[^>]*>.*?</ref>").unwrap();
+    static ref HEADING: Regex = Regex::new(r"^(={2,6})\s*(.*?)\s*={2,6}\s*$").unwrap();
+    static ref EXTERNAL_LINK: Regex = Regex::new(r"\[https?://[^\s\]]+\s*([^\]]*)\]").unwrap();
+    static ref HTML_TAG: Regex = Regex::new(r"</?[a-zA-Z][^>]*>").unwrap();
+    static ref EMPHASIS: Regex = Regex::new(r"'{2,}").unwrap();
+    static ref MAGIC_WORD: Regex = Regex::new(r"__[A-Z]+__").unwrap();
+    static ref COORD: Regex = Regex::new(r"\{\{\s*[Cc]oord\s*\|([^{}]*)\}\}").unwrap();
+    // [[fr:Paris]] style interlanguage links
+    static ref LANGUAGE_PREFIX: Regex = Regex::new(r"^[a-z]{2,3}(-[a-z]+)?:").unwrap();
+}
+
+// interwiki prefixes of sister projects, not languages, ie [[wikt:word]]
+const PROJECT_PREFIXES: [&str; 25] = [
+    "b",
+    "c",
+    "commons",
+    "d",
+    "foundation",
+    "m",
+    "meta",
+    "mw",
+    "n",
+    "phab",
+    "q",
+    "s",
+    "species",
+    "v",
+    "voy",
+    "w",
+    "wikibooks",
+    "wikidata",
+    "wikinews",
+    "wikiquote",
+    "wikisource",
+    "wikiversity",
+    "wikivoyage",
+    "wikt",
+    "wiktionary",
+];
+
+/// Gets a Page from a title &str by fetching its raw wikitext instead of rendered HTML.
+/// Wikitext is far more stable than the rendered page and exposes link targets exactly
+///
+/// # Arguments
+/// * `title` - The title of the page
+///
+/// # Returns
+///
+/// Ok(Page) - the new wiki page struct
+/// Err(WikiError) - error if fetching fails
+pub fn page_from_title_wikitext(title: &str) -> Result<Page, WikiError> {
+    debug!("page_from_title_wikitext called with title: {}", title);
+    let client = client::get_client();
+
+    let response = wikitext_request(&client, title).send().map_err(|err| {
+        WikiError::NetworkingError(format!("Request error with status {:?}", err.status()))
+    })?;
+
+    let source = handle_response(response)?;
+
+    Ok(parse_wikitext(title, &source))
+}
+
+// the title goes through query encoding, titles like "AT&T" or "C++" are not url safe
+pub(crate) fn wikitext_request(client: &Client, title: &str) -> RequestBuilder {
+    client.get("https://en.wikipedia.org/w/index.php").query(&[
+        ("title", title.replace(' ', "_").as_str()),
+        ("action", "raw"),
+    ])
+}
+
+/// Parses raw wikitext into a Page.
+/// Templates and tables are dropped from the content, except for the infobox type
+/// and coordinates which are read from their templates
+///
+/// # Arguments
+///
+/// * `title` - title of the page being parsed
+/// * `source` - the raw wikitext
+///
+/// # Returns
+///
+/// Page struct representing the given wiki page
+pub fn parse_wikitext(title: &str, source: &str) -> Page {
+    let source = COMMENT.replace_all(source, "");
+
+    // self closing refs reuse a named citation
+    let reference_count = REF
+        .find_iter(&source)
+        .filter(|reference| !reference.as_str().ends_with("/>"))
+        .count();
+    let source = REF.replace_all(&source, "");
+
+    let coordinates = COORD
+        .captures(&source)
+        .and_then(|coord| parse_coord_params(&coord[1]));
+
+    let (text, templates) = strip_blocks(&source);
+
+    let infobox_type = templates.iter().find_map(|template| {
+        let name = template.split('|').next()?.trim();
+        let kind = name
+            .strip_prefix("Infobox")
+            .or_else(|| name.strip_prefix("infobox"))?;
+
+        Some(format!(
+            "ib-{}",
+            kind.trim().to_lowercase().replace([' ', '_'], "-")
+        ))
+    });
+
+    let mut page = Page {
+        title: title.trim().replace('_', " "),
+        infobox_type,
+        coordinates,
+        reference_count,
+        ..Default::default()
+    };
+
+    let mut content = String::new();
+    let mut summary = String::new();
+    let mut current_section: Option<String> = None;
+
+    for line in text.lines() {
+        if let Some(heading) = HEADING.captures(line) {
+            let heading = heading[2].to_owned();
+            content.push_str(&heading);
+            content.push(' ');
+
+            current_section = Some(heading);
+            continue;
+        }
+
+        let mut links = Vec::new();
+        let line = replace_links(line, &mut links, &mut page.categories, &mut page.interwiki);
+        // positions count links across the whole page
+        for link in links.iter_mut() {
+            link.position += page.links.len();
+        }
+
+        if current_section.as_deref() == Some("See also") {
+            page.see_also.extend(links.iter().cloned());
+        }
+        page.links.extend(links);
+
+        let line = EXTERNAL_LINK.replace_all(&line, "$1");
+        let line = HTML_TAG.replace_all(&line, "");
+        let line = EMPHASIS.replace_all(&line, "");
+        let line = MAGIC_WORD.replace_all(&line, "");
+        let line = line.trim_start_matches(['*', '#', ':', ';', ' ']);
+
+        if current_section.is_none() {
+            summary.push_str(line);
+            summary.push(' ');
+        }
+        content.push_str(line);
+        content.push(' ');
+    }
+
+    page.links = dedup_links(page.links);
+    page.content = clean_meta_content(&content);
+    page.summary = clean_meta_content(&summary);
+
+    page
+}
+
+/// Removes `{{templates}}` and `{| tables |}` (nested to any depth) from wikitext
+///
+/// # Returns
+///
+/// (the remaining text, the inner source of every top level template)
+fn strip_blocks(source: &str) -> (String, Vec<String>) {
+    let mut text = String::new();
+    let mut templates = Vec::new();
+
+    // open blocks, true for a template, false for a table
+    let mut stack: Vec<bool> = Vec::new();
+    let mut template_start = 0;
+    let mut at_line_start = true;
+    let mut i = 0;
+
+    while i < source.len() {
+        let rest = &source[i..];
+
+        if rest.starts_with("{{") {
+            if stack.is_empty() {
+                template_start = i + 2;
+            }
+            stack.push(true);
+            i += 2;
+        } else if rest.starts_with("}}") && stack.last() == Some(&true) {
+            stack.pop();
+            if stack.is_empty() {
+                templates.push(source[template_start..i].to_owned());
+            }
+            i += 2;
+        } else if at_line_start && rest.starts_with("{|") {
+            stack.push(false);
+            i += 2;
+        } else if at_line_start && rest.starts_with("|}") && stack.last() == Some(&false) {
+            stack.pop();
+            i += 2;
+        } else {
+            let c = rest.chars().next().unwrap();
+            if stack.is_empty() {
+                text.push(c);
+            }
+
+            // leading whitespace does not end the start of a line
+            if c == '\n' {
+                at_line_start = true;
+            } else if !c.is_whitespace() {
+                at_line_start = false;
+            }
+
+            i += c.len_utf8();
+            continue;
+        }
+
+        at_line_start = false;
+    }
+
+    (text, templates)
+}
+
+/// Replaces every `[[link]]` in a line with its label, recording article links,
+/// categories and interlanguage links. File and interlanguage links are dropped
+/// from the text, links into other namespaces or sister projects keep their label
+/// but are not recorded. A leading colon (`[[:Category:Foo]]`, `[[:fr:Paris]]`)
+/// makes any of them a plain inline link
+fn replace_links(
+    line: &str,
+    links: &mut Vec<HyperLink>,
+    categories: &mut Vec<String>,
+    interwiki: &mut Vec<(String, String)>,
+) -> String {
+    let mut out = String::new();
+    let mut rest = line;
+
+    while let Some(start) = rest.find("[[") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+
+        // links can nest, ie links inside file captions
+        let mut depth = 1;
+        let mut end = None;
+        let mut j = 0;
+        while j < after.len() {
+            if after[j..].starts_with("[[") {
+                depth += 1;
+                j += 2;
+            } else if after[j..].starts_with("]]") {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(j);
+                    break;
+                }
+                j += 2;
+            } else {
+                j += after[j..].chars().next().unwrap().len_utf8();
+            }
+        }
+
+        let Some(end) = end else {
+            // unbalanced, keep the rest as plain text
+            out.push_str(&rest[start..]);
+            return out;
+        };
+
+        let inner = &after[..end];
+        rest = &after[end + 2..];
+
+        let (target, label) = match inner.split_once('|') {
+            Some((target, label)) => (target.trim(), label.trim()),
+            None => (inner.trim(), inner.trim()),
+        };
+        let (target, label, inline) = match target.strip_prefix(':') {
+            Some(stripped) if label == target => (stripped.trim(), stripped.trim(), true),
+            Some(stripped) => (stripped.trim(), label, true),
+            None => (target, label, false),
+        };
+
+        let prefix = target
+            .split_once(':')
+            .map(|(prefix, _)| prefix.trim().to_lowercase());
+        let project = prefix
+            .as_deref()
+            .is_some_and(|prefix| PROJECT_PREFIXES.contains(&prefix));
+        let language = !project
+            && LANGUAGE_PREFIX.is_match(target)
+            && Namespace::from_title(target) == Namespace::Article;
+
+        if project || (language && inline) {
+            // a page on another wiki, only the label is text
+            out.push_str(label);
+        } else if language {
+            if let Some((lang, title)) = target.split_once(':') {
+                interwiki.push((lang.to_owned(), title.trim().replace('_', " ")));
+            }
+        } else if let (false, Some(category)) =
+            (inline, strip_prefix_ignore_case(target, "category:"))
+        {
+            categories.push(category.trim().replace('_', " "));
+        } else if !inline
+            && (strip_prefix_ignore_case(target, "file:").is_some()
+                || strip_prefix_ignore_case(target, "image:").is_some())
+        {
+            continue;
+        } else {
+            let namespace = Namespace::from_title(target);
+            if !target.starts_with('#') && namespace == Namespace::Article {
+                links.push(HyperLink {
+                    position: links.len(),
+                    ..HyperLink::from_href(
+                        normalize_text(label),
+                        &format!("/wiki/{}", target_slug(target)),
+                    )
+                });
+            }
+            out.push_str(label);
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = text.get(..prefix.len())?;
+
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &text[prefix.len()..])
+}
+
+// wiki titles always start uppercase and use underscores in urls
+fn target_slug(target: &str) -> String {
+    let slug = target.trim().replace(' ', "_");
+    let mut chars = slug.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => slug,
+    }
+}
+
+/// Parses the positional parameters of a `{{coord}}` template, in decimal
+/// (`48.85|2.35`) or degree/minute/second (`48|51|N|2|21|E`) form
+fn parse_coord_params(params: &str) -> Option<Coordinates> {
+    let positional: Vec<&str> = params
+        .split('|')
+        .map(str::trim)
+        .filter(|param| !param.contains('='))
+        .collect();
+
+    let hemisphere = |param: &&str| matches!(*param, "N" | "S" | "E" | "W");
+
+    let coordinates = match positional.iter().position(hemisphere) {
+        None => Coordinates {
+            latitude: positional.first()?.parse().ok()?,
+            longitude: positional.get(1)?.parse().ok()?,
+        },
+        Some(lat_end) => {
+            let lon_end = lat_end + 1 + positional[lat_end + 1..].iter().position(hemisphere)?;
+
+            let latitude = dms(&positional[..lat_end])?;
+            let longitude = dms(&positional[lat_end + 1..lon_end])?;
+
+            Coordinates {
+                latitude: if positional[lat_end] == "S" {
+                    -latitude
+                } else {
+                    latitude
+                },
+                longitude: if positional[lon_end] == "W" {
+                    -longitude
+                } else {
+                    longitude
+                },
+            }
+        }
+    };
+
+    let in_range = coordinates.latitude.abs() <= 90.0 && coordinates.longitude.abs() <= 180.0;
+    in_range.then_some(coordinates)
+}
+
+// degrees, minutes, seconds to decimal degrees
+fn dms(parts: &[&str]) -> Option<f64> {
+    if parts.is_empty() || parts.len() > 3 {
+        return None;
+    }
+
+    let mut value = 0.0;
+    for (part, scale) in parts.iter().zip([1.0, 60.0, 3600.0]) {
+        value += part.parse::<f64>().ok()? / scale;
+    }
+
+    Some(value)
+}